@@ -2,6 +2,9 @@
 //!
 //! All of the `http_cache_semantics` logic is contained entirely within `fn make_a_request()`
 
+// The example is free to use newer std APIs than the library's MSRV
+#![allow(clippy::incompatible_msrv)]
+
 use std::{
     collections::HashMap,
     sync::{LazyLock, Mutex},
@@ -67,7 +70,7 @@ fn main() {
             .interact()
             .unwrap();
         match selection {
-            0 => make_a_request(&mut cache, config),
+            0 => make_a_request(&mut cache, config.clone()),
             1 => advance_time(),
             2 => list_cache_entries(&cache),
             3 => break,
@@ -101,13 +104,27 @@ fn make_a_request(cache: &mut Cache, config: Config) {
                     let new_req = Req::from_parts(request, ());
                     let mut resp = server::get(new_req.clone());
                     let after_resp = policy.after_response(&new_req, &resp, current_time());
-                    let (not_modified, new_policy, new_resp) = match after_resp {
-                        AfterResponse::NotModified(p, r) => (true, p, r),
-                        AfterResponse::Modified(p, r) => (false, p, r),
+                    let (new_policy, not_modified_resp) = match after_resp {
+                        AfterResponse::NotModified(p, r) => (p, Some(r)),
+                        AfterResponse::Modified(p, _) => (p, None),
+                        AfterResponse::Partial(..) => {
+                            unreachable!("this example never makes range requests")
+                        }
+                        AfterResponse::FailedRevalidation(..) => {
+                            unreachable!("this example's server never fails")
+                        }
+                        AfterResponse::Mismatch(request) => {
+                            println!("{} refetching", bold("mismatched validators!").red());
+                            let new_req = Req::from_parts(request, ());
+                            resp = server::get(new_req.clone());
+                            let p =
+                                CachePolicy::with_config(&new_req, &resp, current_time(), config);
+                            (p, None)
+                        }
                     };
                     // NOTE: if the policy isn't storable then you MUST NOT store the entry
                     if new_policy.is_storable() {
-                        if not_modified {
+                        if let Some(new_resp) = not_modified_resp {
                             println!("{} only updating metadata", bold("not modified!").blue());
                             let entry = occupied.get_mut();
                             entry.0 = new_policy;
@@ -0,0 +1,50 @@
+//! Conversions for [`actix-web`](https://docs.rs/actix-web) requests and responses
+//!
+//! `actix-web` is built on the 0.2 release of the `http` crate, so its types are converted into
+//! the tuples that implement [`RequestLike`][crate::RequestLike] and
+//! [`ResponseLike`][crate::ResponseLike]. Middleware can reach both halves of an outgoing
+//! response through [`ServiceResponse::request()`] and [`ServiceResponse::response()`].
+//!
+//! ```
+//! use actix_web::{test::TestRequest, HttpResponse};
+//! use http_cache_policy::{actix_web as convert, CachePolicy};
+//!
+//! let req = TestRequest::get().uri("/a").to_http_request();
+//! let res = HttpResponse::Ok()
+//!     .insert_header(("cache-control", "public, max-age=60"))
+//!     .finish();
+//! let policy = CachePolicy::new(&convert::request(&req).unwrap(), &convert::response(&res).unwrap());
+//! assert!(policy.is_storable());
+//! ```
+//!
+//! [`ServiceResponse::request()`]: actix_web::dev::ServiceResponse::request
+//! [`ServiceResponse::response()`]: actix_web::dev::ServiceResponse::response
+
+use actix_web::{http::header as actix_header, HttpRequest, HttpResponse};
+use http::{HeaderMap, Method, StatusCode, Uri};
+
+use crate::{builder::BuildError, interop::header_map};
+
+/// Converts a request into the `(Uri, Method, HeaderMap)` tuple that implements `RequestLike`
+///
+/// The URI is the absolute [`HttpRequest::full_url()`] so that it's keyed like client requests
+pub fn request(req: &HttpRequest) -> Result<(Uri, Method, HeaderMap), BuildError> {
+    let uri = req.full_url().as_str().parse()?;
+    let method = Method::from_bytes(req.method().as_str().as_bytes())?;
+    Ok((uri, method, headers(req.headers())?))
+}
+
+/// Converts a response into the `(StatusCode, HeaderMap)` tuple that implements `ResponseLike`
+pub fn response<B>(res: &HttpResponse<B>) -> Result<(StatusCode, HeaderMap), BuildError> {
+    let status = StatusCode::from_u16(res.status().as_u16())?;
+    Ok((status, headers(res.headers())?))
+}
+
+/// Converts a header map e.g. from a [`ServiceRequest`][actix_web::dev::ServiceRequest]
+pub fn headers(headers: &actix_header::HeaderMap) -> Result<HeaderMap, BuildError> {
+    header_map(
+        headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes())),
+    )
+}
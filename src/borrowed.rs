@@ -0,0 +1,288 @@
+//! Borrowed views of requests and responses
+//!
+//! Building a [`http::response::Parts`] for a cache hit clones the whole stored header map. A
+//! proxy that writes the headers straight to a connection can use
+//! [`CachePolicy::cached_response_ref()`] instead, which borrows the stored headers and only
+//! creates the few that change between hits.
+//!
+//! Likewise [`CachePolicyRef`] evaluates a response that's still owned elsewhere e.g. by a server
+//! deciding whether an outgoing response is worth caching, and only copies it once it's turned
+//! into a [`CachePolicy`].
+//!
+//! ```
+//! use http::{header, Request, Response};
+//! use http_cache_policy::CachePolicy;
+//!
+//! let req = Request::get("https://example.com/a").body(()).unwrap();
+//! let res = Response::builder()
+//!     .header(header::CACHE_CONTROL, "max-age=60")
+//!     .header(header::CONTENT_TYPE, "text/plain")
+//!     .body(())
+//!     .unwrap();
+//! let now = http_cache_policy::now();
+//! let policy = CachePolicy::new_at(&req, &res, now);
+//!
+//! if policy.satisfies_without_revalidation(&req, now) {
+//!     let cached = policy.cached_response_ref(now);
+//!     assert_eq!(cached.status(), 200);
+//!     for (name, value) in cached.headers() {
+//!         // Write the header out
+//!     }
+//!     assert_eq!(cached.get(&header::AGE).unwrap(), "0");
+//! }
+//! ```
+
+use std::{
+    borrow::Cow,
+    time::{Duration, SystemTime},
+};
+
+use http::{
+    header::{AGE, CACHE_CONTROL, CONNECTION, DATE, WARNING},
+    HeaderMap, HeaderName, HeaderValue, Method, Response, StatusCode, Uri,
+};
+
+use crate::{
+    date_value, get_all_comma, join, parse_cache_control, prepare_response, received_date,
+    text_value, warning, CachePolicy, Config, Dates, DirectiveSet, RequestLike, ResponseLike,
+    Storability, View, HOP_BY_HOP_HEADERS,
+};
+
+impl CachePolicy {
+    /// A borrowed view of the stored response to serve at `now`
+    ///
+    /// This has the same status and headers as the response from
+    /// [`before_request()`][Self::before_request] when the stored response is fresh, without
+    /// cloning the stored headers. Check that the stored response can be used e.g. with
+    /// [`satisfies_without_revalidation()`][Self::satisfies_without_revalidation] first.
+    pub fn cached_response_ref(&self, now: SystemTime) -> CachedResponseRef<'_> {
+        let age = self.age(now);
+        // The stored warnings are only rewritten when some of them have to be dropped
+        let rewrites_warnings = warning::members(self.res.get_all(WARNING)).any(warning::is_1xx);
+        let kept_warnings = if rewrites_warnings {
+            let warnings =
+                join(warning::members(self.res.get_all(WARNING)).filter(|w| !warning::is_1xx(w)));
+            (!warnings.is_empty()).then(|| (WARNING, text_value(warnings)))
+        } else {
+            None
+        };
+        let heuristic_warning = self.needs_heuristic_warning(age).then(|| {
+            (
+                WARNING,
+                HeaderValue::from_static(r#"113 - "rfc7234 5.5.4""#),
+            )
+        });
+
+        CachedResponseRef {
+            status: self.status,
+            stored: &self.res,
+            rewrites_warnings,
+            kept_warnings,
+            heuristic_warning,
+            age: (AGE, HeaderValue::from(age.as_secs())),
+            date: (DATE, date_value(now)),
+        }
+    }
+}
+
+/// A stored response as it's served from the cache, borrowing the stored headers
+///
+/// See [`CachePolicy::cached_response_ref()`]
+#[derive(Debug, Clone)]
+pub struct CachedResponseRef<'a> {
+    status: StatusCode,
+    stored: &'a HeaderMap,
+    rewrites_warnings: bool,
+    kept_warnings: Option<(HeaderName, HeaderValue)>,
+    heuristic_warning: Option<(HeaderName, HeaderValue)>,
+    age: (HeaderName, HeaderValue),
+    date: (HeaderName, HeaderValue),
+}
+
+impl<'a> CachedResponseRef<'a> {
+    /// The stored response's status
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// If a stored header is served as-is
+    fn passes_through(&self, name: &HeaderName) -> bool {
+        if *name == AGE || (*name == WARNING && self.rewrites_warnings) {
+            return false;
+        }
+        !HOP_BY_HOP_HEADERS.contains(&name.as_str())
+            && !get_all_comma(self.stored.get_all(CONNECTION))
+                .any(|option| option.eq_ignore_ascii_case(name.as_str()))
+    }
+
+    /// Every header of the response to serve
+    ///
+    /// The stored headers come first without the hop-by-hop ones, followed by any updated
+    /// `Warning`s and then the `Age` and `Date`
+    pub fn headers(&self) -> impl Iterator<Item = (&HeaderName, &HeaderValue)> {
+        let stored = self
+            .stored
+            .iter()
+            .filter(move |(name, _)| self.passes_through(name));
+        let updated = self
+            .kept_warnings
+            .iter()
+            .chain(&self.heuristic_warning)
+            .chain([&self.age, &self.date])
+            .map(|(name, value)| (name, value));
+        stored.chain(updated)
+    }
+
+    /// The first value of a header
+    pub fn get(&self, name: &HeaderName) -> Option<&HeaderValue> {
+        self.headers()
+            .find(|(other, _)| *other == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Copies the response into owned parts e.g. for handing it to a client library
+    pub fn to_parts(&self) -> http::response::Parts {
+        let mut parts = Response::new(()).into_parts().0;
+        parts.status = self.status;
+        parts.headers.reserve(self.stored.len() + 3);
+        for (name, value) in self.headers() {
+            parts.headers.append(name.clone(), value.clone());
+        }
+        parts
+    }
+}
+
+/// Evaluates a request and response without taking a copy of their headers
+///
+/// This follows the same rules as [`CachePolicy`] and is meant for deciding whether a response is
+/// worth caching before paying for an owned policy. Headers are only copied up front when they
+/// change e.g. with [`Config::strip_response_headers`] or when the response lacks a `Date`. Unlike
+/// [`CachePolicy`] nothing is reported to the [`Config::observer`] until the policy is created
+/// with [`into_policy()`][Self::into_policy].
+///
+/// ```
+/// use http::{header, Request, Response};
+/// use http_cache_policy::{borrowed::CachePolicyRef, Config};
+///
+/// let req = Request::get("https://example.com/a").body(()).unwrap();
+/// let res = Response::builder()
+///     .header(header::CACHE_CONTROL, "max-age=60")
+///     .body(())
+///     .unwrap();
+/// let config = Config::default();
+/// let now = http_cache_policy::now();
+///
+/// let view = CachePolicyRef::with_config(&req, &res, now, &config);
+/// if view.is_storable() {
+///     let policy = view.into_policy();
+///     assert_eq!(policy.time_to_live(now).as_secs(), 60);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CachePolicyRef<'a> {
+    uri: Uri,
+    method: &'a Method,
+    status: StatusCode,
+    req: &'a HeaderMap,
+    res: Cow<'a, HeaderMap>,
+    config: Cow<'a, Config>,
+    res_cc: DirectiveSet,
+    req_cc: DirectiveSet,
+    body_digest: Option<&'a [u8]>,
+    response_time: SystemTime,
+    dates: Dates,
+}
+
+impl<'a> CachePolicyRef<'a> {
+    /// Evaluates `res` to `req`, received at `response_time`
+    ///
+    /// See [`CachePolicy::with_config()`]
+    pub fn with_config<Req: RequestLike, Res: ResponseLike>(
+        req: &'a Req,
+        res: &'a Res,
+        response_time: SystemTime,
+        config: &'a Config,
+    ) -> Self {
+        let mut res_headers = Cow::Borrowed(res.headers());
+        if let Some(date) = received_date(&res_headers, response_time) {
+            res_headers.to_mut().insert(DATE, date);
+        }
+        let uri = req.uri();
+        let config = config
+            .for_uri(&uri)
+            .map_or(Cow::Borrowed(config), Cow::Owned);
+        let (res_headers, res_cc) = prepare_response(res_headers, &config);
+        let req_headers = req.headers();
+        Self {
+            uri,
+            method: req.method(),
+            status: res.status(),
+            req: req_headers,
+            req_cc: parse_cache_control(req_headers.get_all(CACHE_CONTROL)),
+            dates: Dates::parse(&res_headers),
+            res: res_headers,
+            config,
+            res_cc,
+            body_digest: req.body_digest(),
+            response_time,
+        }
+    }
+
+    fn view(&self) -> View<'_> {
+        View {
+            req: self.req,
+            res: &self.res,
+            status: self.status,
+            method: self.method,
+            config: &self.config,
+            res_cc: &self.res_cc,
+            req_cc: &self.req_cc,
+            body_digest: self.body_digest,
+            response_time: self.response_time,
+            dates: self.dates,
+        }
+    }
+
+    /// See [`CachePolicy::is_storable()`]
+    pub fn is_storable(&self) -> bool {
+        self.storability().is_storable()
+    }
+
+    /// See [`CachePolicy::storability()`]
+    pub fn storability(&self) -> Storability {
+        self.view().storability(self.config.mode)
+    }
+
+    /// See [`CachePolicy::freshness_lifetime()`]
+    pub fn freshness_lifetime(&self) -> Duration {
+        self.view().max_age_with_source(self.config.mode).0
+    }
+
+    /// See [`CachePolicy::time_to_live()`]
+    pub fn time_to_live(&self, now: SystemTime) -> Duration {
+        let mut age = self.view().age_header_value();
+        if let Ok(resident_time) = now.duration_since(self.response_time) {
+            age += resident_time;
+        }
+        self.freshness_lifetime()
+            .checked_sub(age)
+            .unwrap_or_default()
+    }
+
+    /// Copies the request and response into an owned [`CachePolicy`] e.g. to store it
+    ///
+    /// Headers that were already copied by the [`Config`] are moved instead
+    pub fn into_policy(self) -> CachePolicy {
+        CachePolicy::from_details(
+            self.uri,
+            self.method.clone(),
+            self.status,
+            self.req.clone(),
+            self.res.into_owned(),
+            self.response_time,
+            self.config.into_owned(),
+        )
+        .with_body_digest(self.body_digest.map(Box::from))
+        .evaluated()
+    }
+}
@@ -0,0 +1,262 @@
+//! Building a [`CachePolicy`] from plain strings
+
+use std::{error::Error, fmt, time::SystemTime};
+
+use http::{
+    header::{InvalidHeaderName, InvalidHeaderValue, DATE},
+    method::InvalidMethod,
+    status::InvalidStatusCode,
+    uri::InvalidUri,
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri,
+};
+
+use crate::{received_date, CachePolicy, Config};
+
+/// Builds a [`CachePolicy`] from plain strings e.g. ones loaded from a database
+///
+/// Every value is validated when it's set, but the first error is only reported by
+/// [`build()`][Self::build] so that calls can be chained
+///
+/// ```
+/// use std::time::SystemTime;
+///
+/// use http_cache_policy::CachePolicyBuilder;
+///
+/// let policy = CachePolicyBuilder::new()
+///     .method("GET")
+///     .uri("https://example.com/a")
+///     .request_header("accept", "text/html")
+///     .status("200")
+///     .response_header("cache-control", "max-age=60")
+///     .response_time(SystemTime::now())
+///     .build()
+///     .unwrap();
+/// assert!(policy.is_storable());
+///
+/// let err = CachePolicyBuilder::new().status("2000").build().unwrap_err();
+/// assert_eq!(err.to_string(), "invalid response status code");
+/// ```
+#[derive(Debug)]
+pub struct CachePolicyBuilder {
+    inner: Result<Parts, BuildError>,
+}
+
+#[derive(Debug)]
+struct Parts {
+    method: Method,
+    uri: Uri,
+    request_headers: HeaderMap,
+    status: StatusCode,
+    response_headers: HeaderMap,
+    response_time: Option<SystemTime>,
+    body_digest: Option<Box<[u8]>>,
+    config: Config,
+}
+
+impl Default for CachePolicyBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CachePolicyBuilder {
+    /// A builder for a `GET /` request with a `200 OK` response and no headers
+    pub fn new() -> Self {
+        Self {
+            inner: Ok(Parts {
+                method: Method::GET,
+                uri: Uri::from_static("/"),
+                request_headers: HeaderMap::new(),
+                status: StatusCode::OK,
+                response_headers: HeaderMap::new(),
+                response_time: None,
+                body_digest: None,
+                config: Config::default(),
+            }),
+        }
+    }
+
+    fn and_then(self, f: impl FnOnce(&mut Parts) -> Result<(), BuildError>) -> Self {
+        let inner = self
+            .inner
+            .and_then(|mut parts| f(&mut parts).map(|()| parts));
+        Self { inner }
+    }
+
+    /// The request method
+    #[must_use]
+    pub fn method(self, method: &str) -> Self {
+        self.and_then(|parts| {
+            parts.method = Method::from_bytes(method.as_bytes())?;
+            Ok(())
+        })
+    }
+
+    /// The request URI
+    #[must_use]
+    pub fn uri(self, uri: &str) -> Self {
+        self.and_then(|parts| {
+            parts.uri = uri.parse()?;
+            Ok(())
+        })
+    }
+
+    /// Appends a request header
+    #[must_use]
+    pub fn request_header(self, name: &str, value: &str) -> Self {
+        self.and_then(|parts| append_header(&mut parts.request_headers, name, value))
+    }
+
+    /// The response status code e.g. `"200"`
+    #[must_use]
+    pub fn status(self, status: &str) -> Self {
+        self.and_then(|parts| {
+            parts.status = StatusCode::from_bytes(status.trim().as_bytes())?;
+            Ok(())
+        })
+    }
+
+    /// Appends a response header
+    #[must_use]
+    pub fn response_header(self, name: &str, value: &str) -> Self {
+        self.and_then(|parts| append_header(&mut parts.response_headers, name, value))
+    }
+
+    /// When the response was received
+    ///
+    /// Defaults to the time of [`build()`][Self::build], which reads the clock
+    #[must_use]
+    pub fn response_time(self, response_time: SystemTime) -> Self {
+        self.and_then(|parts| {
+            parts.response_time = Some(response_time);
+            Ok(())
+        })
+    }
+
+    /// A digest of the request content
+    ///
+    /// See [`RequestLike::body_digest()`][crate::RequestLike::body_digest]
+    #[must_use]
+    pub fn body_digest(self, digest: &[u8]) -> Self {
+        self.and_then(|parts| {
+            parts.body_digest = Some(digest.into());
+            Ok(())
+        })
+    }
+
+    /// The [`Config`] to evaluate the policy with
+    #[must_use]
+    pub fn config(self, config: Config) -> Self {
+        self.and_then(|parts| {
+            parts.config = config;
+            Ok(())
+        })
+    }
+
+    /// Builds the policy or returns the first invalid value
+    pub fn build(self) -> Result<CachePolicy, BuildError> {
+        let Parts {
+            method,
+            uri,
+            request_headers,
+            status,
+            response_headers,
+            response_time,
+            body_digest,
+            config,
+        } = self.inner?;
+        let response_time = response_time.unwrap_or_else(SystemTime::now);
+        let mut response_headers = response_headers;
+        if let Some(date) = received_date(&response_headers, response_time) {
+            response_headers.insert(DATE, date);
+        }
+        Ok(CachePolicy::from_details(
+            uri,
+            method,
+            status,
+            request_headers,
+            response_headers,
+            response_time,
+            config,
+        )
+        .with_body_digest(body_digest)
+        .evaluated())
+    }
+}
+
+fn append_header(headers: &mut HeaderMap, name: &str, value: &str) -> Result<(), BuildError> {
+    let name = HeaderName::from_bytes(name.trim().as_bytes())?;
+    let value = HeaderValue::from_str(value.trim())?;
+    headers.append(name, value);
+    Ok(())
+}
+
+/// An invalid value passed to a [`CachePolicyBuilder`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// An invalid request method
+    Method(InvalidMethod),
+    /// An invalid request URI
+    Uri(InvalidUri),
+    /// An invalid response status code
+    Status(InvalidStatusCode),
+    /// An invalid header name
+    HeaderName(InvalidHeaderName),
+    /// An invalid header value
+    HeaderValue(InvalidHeaderValue),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Method(_) => "invalid request method",
+            Self::Uri(_) => "invalid request URI",
+            Self::Status(_) => "invalid response status code",
+            Self::HeaderName(_) => "invalid header name",
+            Self::HeaderValue(_) => "invalid header value",
+        })
+    }
+}
+
+impl Error for BuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Method(err) => Some(err),
+            Self::Uri(err) => Some(err),
+            Self::Status(err) => Some(err),
+            Self::HeaderName(err) => Some(err),
+            Self::HeaderValue(err) => Some(err),
+        }
+    }
+}
+
+impl From<InvalidMethod> for BuildError {
+    fn from(err: InvalidMethod) -> Self {
+        Self::Method(err)
+    }
+}
+
+impl From<InvalidUri> for BuildError {
+    fn from(err: InvalidUri) -> Self {
+        Self::Uri(err)
+    }
+}
+
+impl From<InvalidStatusCode> for BuildError {
+    fn from(err: InvalidStatusCode) -> Self {
+        Self::Status(err)
+    }
+}
+
+impl From<InvalidHeaderName> for BuildError {
+    fn from(err: InvalidHeaderName) -> Self {
+        Self::HeaderName(err)
+    }
+}
+
+impl From<InvalidHeaderValue> for BuildError {
+    fn from(err: InvalidHeaderValue) -> Self {
+        Self::HeaderValue(err)
+    }
+}
@@ -0,0 +1,457 @@
+//! Parsing and formatting for the `Cache-Control` header
+//!
+//! Directives follow the `token [ "=" ( token / quoted-string ) ]` grammar (rfc9111 5.2). Parsing
+//! is lenient in the same spirit as browsers: whitespace around `=` is tolerated, malformed
+//! members are skipped instead of failing the whole header, and directive names are lowercased
+//! since they're case-insensitive.
+
+use std::{borrow::Cow, fmt};
+
+use http::HeaderValue;
+
+/// `tchar` from rfc9110 5.6.2
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(is_tchar)
+}
+
+/// Visible characters, spaces, and tabs are the only things allowed in a quoted-string
+fn is_quotable(s: &str) -> bool {
+    s.bytes()
+        .all(|b| b == b'\t' || b == b' ' || (b.is_ascii_graphic() || !b.is_ascii()))
+}
+
+fn is_valid_directive(name: &str, value: Option<&str>) -> bool {
+    is_token(name) && value.map_or(true, is_quotable)
+}
+
+/// A single `Cache-Control` directive e.g. `max-age=60`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Directive {
+    name: Box<str>,
+    value: Option<Box<str>>,
+}
+
+impl Directive {
+    /// Constructs a directive with an optional (unquoted) value
+    ///
+    /// The name is lowercased. Returns `None` if the name isn't a valid token or the value
+    /// contains characters that can't be represented in a header
+    pub fn new(name: &str, value: Option<&str>) -> Option<Self> {
+        if !is_valid_directive(name, value) {
+            return None;
+        }
+        Some(Self {
+            name: name.to_ascii_lowercase().into(),
+            value: value.map(From::from),
+        })
+    }
+
+    /// The lowercased directive name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The unquoted directive value if there is one
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+impl fmt::Display for Directive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_directive(f, &self.name, self.value.as_deref())
+    }
+}
+
+/// Writes `name` or `name=value`, quoting the value when it isn't a valid token
+pub(crate) fn fmt_directive(
+    f: &mut impl fmt::Write,
+    name: &str,
+    value: Option<&str>,
+) -> fmt::Result {
+    f.write_str(name)?;
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(()),
+    };
+    f.write_char('=')?;
+    if is_token(value) {
+        return f.write_str(value);
+    }
+    f.write_char('"')?;
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    f.write_char('"')
+}
+
+/// A parsed `Cache-Control` header e.g. `max-age=60, private="set-cookie"`
+///
+/// ```
+/// use http_cache_policy::cache_control::CacheControl;
+///
+/// let cc = CacheControl::parse(r#"Max-Age=60, private="set-cookie, x-token", no-store"#);
+/// assert_eq!(cc.get("max-age").unwrap().value(), Some("60"));
+/// assert_eq!(cc.get("private").unwrap().value(), Some("set-cookie, x-token"));
+/// assert!(cc.contains("no-store"));
+/// assert_eq!(
+///     cc.to_string(),
+///     r#"max-age=60, private="set-cookie, x-token", no-store"#,
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CacheControl {
+    directives: Vec<Directive>,
+}
+
+impl CacheControl {
+    /// Constructs a `Cache-Control` from its directives
+    pub fn new(directives: Vec<Directive>) -> Self {
+        Self { directives }
+    }
+
+    /// Parses a `Cache-Control` header value
+    ///
+    /// This never fails. Malformed members are skipped while the rest are kept
+    pub fn parse(s: &str) -> Self {
+        let mut directives = Vec::new();
+        parse_directives(s, |name, value| {
+            directives.push(Directive {
+                name: name.into(),
+                value: value.map(From::from),
+            })
+        });
+        Self { directives }
+    }
+
+    /// Parses a `Cache-Control` header value
+    ///
+    /// Values that aren't visible ASCII are treated as empty
+    pub fn from_header(value: &HeaderValue) -> Self {
+        value.to_str().map(Self::parse).unwrap_or_default()
+    }
+
+    /// Parses every `Cache-Control` header value as a single list
+    pub fn from_headers<'a>(values: impl IntoIterator<Item = &'a HeaderValue>) -> Self {
+        values.into_iter().map(Self::from_header).collect()
+    }
+
+    /// The directives in the order they appeared
+    pub fn directives(&self) -> &[Directive] {
+        &self.directives
+    }
+
+    /// The first directive with the given (lowercase) name
+    pub fn get(&self, name: &str) -> Option<&Directive> {
+        self.directives.iter().find(|d| &*d.name == name)
+    }
+
+    /// If a directive with the given (lowercase) name is present
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Appends a directive
+    pub fn push(&mut self, directive: Directive) {
+        self.directives.push(directive);
+    }
+
+    /// Formats the directives as a header value
+    pub fn to_header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.to_string()).expect("Directives are always valid")
+    }
+}
+
+impl fmt::Display for CacheControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, directive) in self.directives.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{directive}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<Directive> for CacheControl {
+    fn from_iter<I: IntoIterator<Item = Directive>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl FromIterator<CacheControl> for CacheControl {
+    fn from_iter<I: IntoIterator<Item = CacheControl>>(iter: I) -> Self {
+        iter.into_iter().flat_map(|cc| cc.directives).collect()
+    }
+}
+
+impl IntoIterator for CacheControl {
+    type Item = Directive;
+    type IntoIter = std::vec::IntoIter<Directive>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.directives.into_iter()
+    }
+}
+
+/// Builds a `Cache-Control` header for a response
+///
+/// Setting a directive again replaces its earlier value while keeping its position
+///
+/// ```
+/// use http_cache_policy::cache_control::CacheControlBuilder;
+///
+/// let cc = CacheControlBuilder::new()
+///     .public()
+///     .max_age(3600)
+///     .s_maxage(60)
+///     .stale_while_revalidate(30)
+///     .build();
+/// assert_eq!(cc, "public, max-age=3600, s-maxage=60, stale-while-revalidate=30");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CacheControlBuilder {
+    cc: CacheControl,
+}
+
+impl CacheControlBuilder {
+    /// An empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an arbitrary directive e.g. an extension directive
+    #[must_use]
+    pub fn directive(mut self, directive: Directive) -> Self {
+        match self
+            .cc
+            .directives
+            .iter_mut()
+            .find(|d| d.name == directive.name)
+        {
+            Some(existing) => *existing = directive,
+            None => self.cc.push(directive),
+        }
+        self
+    }
+
+    fn flag(self, name: &'static str) -> Self {
+        self.directive(Directive {
+            name: name.into(),
+            value: None,
+        })
+    }
+
+    fn seconds(self, name: &'static str, secs: u64) -> Self {
+        self.directive(Directive {
+            name: name.into(),
+            value: Some(secs.to_string().into()),
+        })
+    }
+
+    /// `public`
+    #[must_use]
+    pub fn public(self) -> Self {
+        self.flag("public")
+    }
+
+    /// `private`
+    #[must_use]
+    pub fn private(self) -> Self {
+        self.flag("private")
+    }
+
+    /// `no-cache`
+    #[must_use]
+    pub fn no_cache(self) -> Self {
+        self.flag("no-cache")
+    }
+
+    /// `no-store`
+    #[must_use]
+    pub fn no_store(self) -> Self {
+        self.flag("no-store")
+    }
+
+    /// `no-transform`
+    #[must_use]
+    pub fn no_transform(self) -> Self {
+        self.flag("no-transform")
+    }
+
+    /// `must-revalidate`
+    #[must_use]
+    pub fn must_revalidate(self) -> Self {
+        self.flag("must-revalidate")
+    }
+
+    /// `proxy-revalidate`
+    #[must_use]
+    pub fn proxy_revalidate(self) -> Self {
+        self.flag("proxy-revalidate")
+    }
+
+    /// `must-understand`
+    #[must_use]
+    pub fn must_understand(self) -> Self {
+        self.flag("must-understand")
+    }
+
+    /// `immutable` (rfc8246)
+    #[must_use]
+    pub fn immutable(self) -> Self {
+        self.flag("immutable")
+    }
+
+    /// `max-age=secs`
+    #[must_use]
+    pub fn max_age(self, secs: u64) -> Self {
+        self.seconds("max-age", secs)
+    }
+
+    /// `s-maxage=secs`
+    #[must_use]
+    pub fn s_maxage(self, secs: u64) -> Self {
+        self.seconds("s-maxage", secs)
+    }
+
+    /// `stale-while-revalidate=secs` (rfc5861 3)
+    #[must_use]
+    pub fn stale_while_revalidate(self, secs: u64) -> Self {
+        self.seconds("stale-while-revalidate", secs)
+    }
+
+    /// `stale-if-error=secs` (rfc5861 4)
+    #[must_use]
+    pub fn stale_if_error(self, secs: u64) -> Self {
+        self.seconds("stale-if-error", secs)
+    }
+
+    /// The directives built so far
+    pub fn build_cache_control(self) -> CacheControl {
+        self.cc
+    }
+
+    /// Formats the directives as a header value
+    pub fn build(self) -> HeaderValue {
+        self.cc.to_header_value()
+    }
+}
+
+/// Calls `f` with the lowercased name and unquoted value of every valid directive in `s`
+///
+/// This is [`CacheControl::parse()`] without allocating for each directive
+pub(crate) fn parse_directives(s: &str, mut f: impl FnMut(&str, Option<&str>)) {
+    let mut rest = s;
+    while !rest.is_empty() {
+        let (name, value, remaining) = split_member(rest);
+        if is_valid_directive(name, value.as_deref()) {
+            if name.bytes().any(|b| b.is_ascii_uppercase()) {
+                f(&name.to_ascii_lowercase(), value.as_deref());
+            } else {
+                f(name, value.as_deref());
+            }
+        }
+        rest = remaining;
+    }
+}
+
+/// Splits off a single list member returning its name and unquoted value, which may not be valid,
+/// along with the remaining input
+fn split_member(s: &str) -> (&str, Option<Cow<'_, str>>, &str) {
+    let name_end = s.find(['=', ',']).unwrap_or(s.len());
+    let name = s[..name_end].trim();
+    let rest = &s[name_end..];
+
+    let (value, rest) = match rest.strip_prefix('=') {
+        Some(rest) => {
+            let rest = rest.trim_start();
+            let (value, rest) = match rest.strip_prefix('"') {
+                Some(quoted) => {
+                    let (value, rest) = parse_quoted_string(quoted);
+                    (Cow::Owned(value), rest)
+                }
+                None => {
+                    let end = rest.find(',').unwrap_or(rest.len());
+                    (Cow::Borrowed(rest[..end].trim()), &rest[end..])
+                }
+            };
+            (Some(value), rest)
+        }
+        None => (None, rest),
+    };
+
+    // Anything trailing the member is junk
+    let rest = match rest.find(',') {
+        Some(comma) => &rest[comma + 1..],
+        None => "",
+    };
+
+    (name, value, rest)
+}
+
+/// Removes the members whose names `remove` matches, keeping everything else exactly as written
+pub(crate) fn remove_members(s: &str, mut remove: impl FnMut(&str) -> bool) -> String {
+    let mut kept = String::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let (name, _, remaining) = split_member(rest);
+        let member = &rest[..rest.len() - remaining.len()];
+        let member = member.strip_suffix(',').unwrap_or(member).trim();
+        if !member.is_empty() && !remove(name) {
+            if !kept.is_empty() {
+                kept.push_str(", ");
+            }
+            kept.push_str(member);
+        }
+        rest = remaining;
+    }
+    kept
+}
+
+/// The members of `s` that [`CacheControl::parse()`] skips since they aren't valid directives
+///
+/// Empty members e.g. from `a,,b` are allowed by the list syntax, so they aren't included
+pub(crate) fn malformed_members(s: &str) -> Vec<&str> {
+    let mut malformed = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let (name, value, remaining) = split_member(rest);
+        let member = rest[..rest.len() - remaining.len()]
+            .trim_end_matches(',')
+            .trim();
+        if !is_valid_directive(name, value.as_deref()) && !member.is_empty() {
+            malformed.push(member);
+        }
+        rest = remaining;
+    }
+    malformed
+}
+
+/// Unquotes a quoted-string (after the opening quote) returning the remaining input
+///
+/// An unterminated quoted-string runs to the end of the input
+fn parse_quoted_string(s: &str) -> (String, &str) {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return (value, &s[i + 1..]),
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    value.push(escaped);
+                }
+            }
+            c => value.push(c),
+        }
+    }
+    (value, "")
+}
@@ -0,0 +1,206 @@
+//! A compact binary encoding of policies
+//!
+//! Serializing policies with serde is convenient, but formats like JSON are bulky when storing
+//! millions of entries, and the serde representation follows the crate's internals. The encoding
+//! written by [`CachePolicy::to_bytes()`] only holds the stored request and response, and starts
+//! with a format version byte. A version is never changed once released, so bytes written by any
+//! release can be read by all later ones.
+//!
+//! ```
+//! use http::{Request, Response};
+//! use http_cache_policy::{CachePolicy, Config};
+//!
+//! let req = Request::get("https://example.com/a").body(()).unwrap();
+//! let res = Response::builder().header("cache-control", "max-age=60").body(()).unwrap();
+//! let policy = CachePolicy::new(&req, &res);
+//!
+//! let bytes = policy.to_bytes();
+//! let restored = CachePolicy::from_bytes(&bytes, Config::default()).unwrap();
+//! assert_eq!(restored.identity(), policy.identity());
+//! ```
+//!
+//! The [`Config`] isn't part of the encoding, so it's passed in when decoding.
+//!
+//! Version `1` is made of these fields in order, where integers are big-endian and strings and
+//! byte strings are prefixed with their length as a `u32`:
+//!
+//! | field | encoding |
+//! | :--- | :--- |
+//! | version | `u8` `1` |
+//! | request method | string |
+//! | request URI | string |
+//! | response status | `u16` |
+//! | response time | `u64` seconds and `u32` nanoseconds since the Unix epoch |
+//! | request headers | `u32` count, then each name as a string and value as a byte string |
+//! | response headers | like the request headers |
+//! | body digest | `u8` `0` for none, or `1` followed by a byte string |
+
+use std::{
+    error::Error,
+    fmt,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri};
+
+use crate::{CachePolicy, Config};
+
+/// The version of the encoding that's written
+const VERSION: u8 = 1;
+
+impl CachePolicy {
+    /// Encodes the policy in the compact binary format
+    ///
+    /// See the [module docs][crate::compact] for the layout
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let response_time = self
+            .response_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let mut out = vec![VERSION];
+        put_bytes(&mut out, self.method.as_str().as_bytes());
+        put_bytes(&mut out, self.uri.to_string().as_bytes());
+        out.extend_from_slice(&self.status.as_u16().to_be_bytes());
+        out.extend_from_slice(&response_time.as_secs().to_be_bytes());
+        out.extend_from_slice(&response_time.subsec_nanos().to_be_bytes());
+        put_headers(&mut out, &self.req);
+        put_headers(&mut out, &self.res);
+        match &self.body_digest {
+            Some(digest) => {
+                out.push(1);
+                put_bytes(&mut out, digest);
+            }
+            None => out.push(0),
+        }
+        out
+    }
+
+    /// Decodes a policy written by [`to_bytes()`][Self::to_bytes], evaluating it with `config`
+    pub fn from_bytes(bytes: &[u8], config: Config) -> Result<Self, DecodeError> {
+        let mut reader = Reader(bytes);
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        let method = Method::from_bytes(reader.bytes()?).map_err(|_| DecodeError::Invalid)?;
+        let uri = Uri::try_from(reader.bytes()?).map_err(|_| DecodeError::Invalid)?;
+        let status = StatusCode::from_u16(reader.u16()?).map_err(|_| DecodeError::Invalid)?;
+        let secs = reader.u64()?;
+        let nanos = reader.u32()?;
+        if nanos >= 1_000_000_000 {
+            return Err(DecodeError::Invalid);
+        }
+        let response_time = UNIX_EPOCH
+            .checked_add(Duration::new(secs, nanos))
+            .ok_or(DecodeError::Invalid)?;
+        let req = reader.headers()?;
+        let res = reader.headers()?;
+        let body_digest = match reader.u8()? {
+            0 => None,
+            1 => Some(reader.bytes()?.into()),
+            _ => return Err(DecodeError::Invalid),
+        };
+        if !reader.0.is_empty() {
+            return Err(DecodeError::Invalid);
+        }
+
+        Ok(
+            Self::from_details(uri, method, status, req, res, response_time, config)
+                .with_body_digest(body_digest),
+        )
+    }
+}
+
+fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    let len = u32::try_from(bytes.len()).expect("Header and URI lengths fit in a `u32`");
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(bytes);
+}
+
+fn put_headers(out: &mut Vec<u8>, headers: &HeaderMap) {
+    let len = u32::try_from(headers.len()).expect("Header counts fit in a `u32`");
+    out.extend_from_slice(&len.to_be_bytes());
+    for (name, value) in headers {
+        put_bytes(out, name.as_str().as_bytes());
+        put_bytes(out, value.as_bytes());
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.0.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        self.array().map(u8::from_be_bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, DecodeError> {
+        self.array().map(u16::from_be_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        self.array().map(u32::from_be_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        self.array().map(u64::from_be_bytes)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], DecodeError> {
+        let len = self.u32()?;
+        self.take(len as usize)
+    }
+
+    fn headers(&mut self) -> Result<HeaderMap, DecodeError> {
+        let len = self.u32()?;
+        let mut headers = HeaderMap::new();
+        for _ in 0..len {
+            let name = HeaderName::from_bytes(self.bytes()?).map_err(|_| DecodeError::Invalid)?;
+            let value = HeaderValue::from_bytes(self.bytes()?).map_err(|_| DecodeError::Invalid)?;
+            headers.append(name, value);
+        }
+        Ok(headers)
+    }
+}
+
+/// Bytes that couldn't be decoded by [`CachePolicy::from_bytes()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The bytes were written in a version of the format that isn't supported
+    UnsupportedVersion(u8),
+    /// The bytes ended early
+    UnexpectedEnd,
+    /// A field has an invalid value, or there are trailing bytes
+    Invalid,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {}", version)
+            }
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::Invalid => f.write_str("invalid policy encoding"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
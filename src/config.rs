@@ -1,6 +1,16 @@
+use std::{borrow::Cow, fmt, sync::Arc, time::Duration};
+
+use http::{uri::PathAndQuery, HeaderName, Method, Uri};
+
+use crate::{
+    observer::DecisionObserver,
+    overrides::{ConfigOverrides, Overrides},
+};
+
 /// TODO
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Config {
     /// TODO
     pub mode: Mode,
@@ -8,29 +18,145 @@ pub struct Config {
     pub last_modified: LastModifiedHeuristic,
     /// TODO
     pub ignore_cargo_cult: bool,
-}
-
-impl Config {
-    /// The default cache config
+    /// The response status codes that the cache understands
     ///
-    /// See the various fields' docs for more details.
+    /// Responses with any other status are never storable. The defaults leave out `206` since the
+    /// policy doesn't store partial responses itself, but a cache that does its own range handling
+    /// can opt in to it (or other codes like `226`) here.
+    pub understood_statuses: StatusCodes,
+    /// The response status codes that are defined as heuristically cacheable
     ///
-    /// | field | value |
-    /// | :---: | :--- |
-    /// | [`mode`][Self::mode] | [`Mode::Shared`] |
-    /// | [`last_modified`][Self::last_modified] | 10% of the time since last modified |
-    /// | [`ignore_cargo_cult`][Self::ignore_cargo_cult] | [`false`] |
-    pub const fn default() -> Self {
-        Self {
-            mode: Mode::default(),
-            last_modified: LastModifiedHeuristic::default(), // 10% matches IE
-            ignore_cargo_cult: false,
-        }
-    }
+    /// Responses with one of these statuses are storable (and can be given a heuristic freshness
+    /// lifetime) even without explicit freshness information. This has no effect on responses
+    /// that do have explicit freshness information e.g. `max-age` or `Expires`.
+    pub heuristic_statuses: StatusCodes,
+    /// Opts in to caching responses to the `QUERY` method
+    ///
+    /// `QUERY` responses are keyed on the request content, so they're only storable when the
+    /// request provides a [`body_digest`][crate::RequestLike::body_digest], and they only match
+    /// later requests with the same digest.
+    pub cache_query: bool,
+    /// The request methods that are defined as cacheable
+    ///
+    /// Responses to `GET` and `HEAD` follow the usual storability rules while responses to any
+    /// other method listed here are only storable when they have explicit freshness information
+    /// e.g. `max-age` or `Expires`. `QUERY` is controlled separately by
+    /// [`cache_query`][Self::cache_query].
+    pub cacheable_methods: Methods,
+    /// Overrides for the freshness lifetime of responses with particular status codes
+    ///
+    /// These are applied after the normal freshness lifetime is computed, with the first entry
+    /// that matches a response's status winning. Responses that would never be fresh e.g. due to
+    /// `no-cache` aren't affected.
+    pub status_ttls: Arc<[StatusTtl]>,
+    /// Response headers that are removed before the policy stores the response
+    ///
+    /// This keeps things like `Set-Cookie`, `Server-Timing`, or huge `Content-Security-Policy`
+    /// headers out of stored (and persisted) policies. The headers are removed before the response
+    /// is evaluated, so stripping a header that affects caching e.g. `Cache-Control` changes the
+    /// policy's decisions too.
+    pub strip_response_headers: HeaderNames,
+    /// The largest response header field (name and value) that the policy will store
+    ///
+    /// This protects memory-constrained caches from pathological responses e.g. multi-kilobyte
+    /// cookies or enormous `Link` headers. What happens to larger fields is controlled by
+    /// [`oversized_headers`][Self::oversized_headers].
+    pub max_stored_header_bytes: Option<usize>,
+    /// What to do with response header fields over
+    /// [`max_stored_header_bytes`][Self::max_stored_header_bytes]
+    pub oversized_headers: OversizedHeaders,
+    /// Query parameters that are ignored when matching requests and generating keys
+    ///
+    /// This keeps analytics parameters like `utm_source`, `fbclid`, or `gclid` from fragmenting
+    /// the cache. A name ending in `*` matches any parameter starting with the rest e.g. `utm_*`.
+    /// The listed parameters are removed from the stored request URI, so they're also left out
+    /// of revalidation requests.
+    pub ignored_query_params: Arc<[Cow<'static, str>]>,
+    /// Which request headers the policy stores alongside the response
+    ///
+    /// By default the whole request header map is kept, which means that things like cookies
+    /// and credentials end up in persisted policies. See [`StoredRequestHeaders::Vary`] for only
+    /// keeping the headers that matching requests depends on.
+    pub stored_request_headers: StoredRequestHeaders,
+    /// Only store the headers that future decisions depend on
+    ///
+    /// This applies [`CachePolicy::minimize()`][crate::CachePolicy::minimize] to every policy
+    /// when it's created, which can cut the memory used by each entry severalfold. Responses
+    /// built by the policy then lack most headers, so keep the full response headers in your own
+    /// store and serve them with
+    /// [`CachePolicy::write_cached_headers()`][crate::CachePolicy::write_cached_headers].
+    pub minimize_headers: bool,
+    /// Compare the request URI and `Host` header separately when matching requests
+    ///
+    /// By default requests are matched on their effective authority, which is the URI's authority
+    /// falling back to the `Host` header, so a stored request in origin-form e.g. `/a` with
+    /// `Host: example.com` matches a later one in absolute-form e.g. `https://example.com/a`.
+    /// With this set the URIs must be identical and the `Host` headers must be too.
+    pub strict_host_matching: bool,
+    /// The request methods whose responses are keyed on the request content e.g. `POST`
+    ///
+    /// Responses to these methods are only storable when the request provides a
+    /// [`body_digest`][crate::RequestLike::body_digest], so two requests with different content
+    /// never share a response. `QUERY` always works this way when
+    /// [`cache_query`][Self::cache_query] is set.
+    pub body_digest_methods: Methods,
+    /// The freshness lifetime of storable responses that don't have any freshness information
+    ///
+    /// This applies when a response has neither explicit freshness information e.g. `max-age` or
+    /// `Expires` nor a `Last-Modified` for the [`last_modified`][Self::last_modified] heuristic,
+    /// which would otherwise need revalidation every time.
+    pub default_ttl: Option<Duration>,
+    /// The longest freshness lifetime a response can have
+    ///
+    /// Longer lifetimes from `max-age`, `Expires`, heuristics or a
+    /// [`status_ttls`][Self::status_ttls] override are cut down to this, for when an origin can't be
+    /// trusted with long lifetimes.
+    pub max_ttl: Option<Duration>,
+    /// The shortest freshness lifetime a response can have, to protect origins from the load of
+    /// very short lifetimes e.g. `max-age=1`
+    ///
+    /// This lifts whatever the response describes, including `max-age=0` and a default or missing
+    /// lifetime, but not responses that must always be revalidated e.g. because of `no-cache`.
+    /// [`status_ttls`][Self::status_ttls] and [`max_ttl`][Self::max_ttl] still apply afterwards.
+    pub min_ttl: Option<Duration>,
+    /// The freshness lifetime of `404` and `410` responses without explicit freshness information
+    ///
+    /// This negative caching keeps repeated requests for missing resources from reaching the
+    /// origin. It takes the place of the [`last_modified`][Self::last_modified] heuristic and
+    /// [`default_ttl`][Self::default_ttl] for these responses.
+    pub client_error_ttl: Option<Duration>,
+    /// The freshness lifetime of `5xx` responses without explicit freshness information
+    ///
+    /// Setting this makes every `5xx` response understood and storable, so a failing origin is
+    /// only asked again once the lifetime runs out.
+    pub server_error_ttl: Option<Duration>,
+    /// Never store responses to requests with `Authorization` in a shared cache
+    ///
+    /// Shared caches can normally store these responses when they have `must-revalidate`,
+    /// `public`, or `s-maxage` (rfc9111 3.5), but some refuse to ever risk handing one user's
+    /// response to another.
+    pub never_cache_authorized: bool,
+    /// Picks overrides for the rest of the config from the request URI
+    ///
+    /// See the [`overrides`][crate::overrides] module. The overrides are applied when a policy is
+    /// created and stay applied, but this isn't serialized, so set it again with
+    /// [`CachePolicy::with_options()`][crate::CachePolicy::with_options] after deserializing a
+    /// policy to pick up new overrides.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub overrides: Option<Arc<dyn Overrides>>,
+    /// Called with the outcome of every decision that the policy makes
+    ///
+    /// See the [`observer`][crate::observer] module. Observers aren't serialized, so set this
+    /// again with [`CachePolicy::with_options()`][crate::CachePolicy::with_options] after
+    /// deserializing a policy.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub observer: Option<Arc<dyn DecisionObserver>>,
+}
 
+impl Config {
     /// Set the mode that the cache operates in
     #[must_use]
-    pub const fn mode(self, mode: Mode) -> Self {
+    pub fn mode(self, mode: Mode) -> Self {
         Self { mode, ..self }
     }
 
@@ -38,7 +164,7 @@ impl Config {
     ///
     /// See [`last_modified`][Self::last_modified] for more details.
     #[must_use]
-    pub const fn last_modified_heuristic(self, last_modified: LastModifiedHeuristic) -> Self {
+    pub fn last_modified_heuristic(self, last_modified: LastModifiedHeuristic) -> Self {
         Self {
             last_modified,
             ..self
@@ -49,20 +175,447 @@ impl Config {
     ///
     /// See [`ignore_cargo_cult`][Self::ignore_cargo_cult] for more details.
     #[must_use]
-    pub const fn ignore_cargo_cult(self, ignore: bool) -> Self {
+    pub fn ignore_cargo_cult(self, ignore: bool) -> Self {
         Self {
             ignore_cargo_cult: ignore,
             ..self
         }
     }
+
+    /// Sets the response status codes that the cache understands
+    ///
+    /// See [`understood_statuses`][Self::understood_statuses] for more details.
+    #[must_use]
+    pub fn understood_statuses(self, statuses: StatusCodes) -> Self {
+        Self {
+            understood_statuses: statuses,
+            ..self
+        }
+    }
+
+    /// Sets the response status codes that are defined as heuristically cacheable
+    ///
+    /// See [`heuristic_statuses`][Self::heuristic_statuses] for more details.
+    #[must_use]
+    pub fn heuristic_statuses(self, statuses: StatusCodes) -> Self {
+        Self {
+            heuristic_statuses: statuses,
+            ..self
+        }
+    }
+
+    /// Opts in to caching responses to the `QUERY` method
+    ///
+    /// See [`cache_query`][Self::cache_query] for more details.
+    #[must_use]
+    pub fn cache_query(self, cache: bool) -> Self {
+        Self {
+            cache_query: cache,
+            ..self
+        }
+    }
+
+    /// Sets the request methods that are defined as cacheable
+    ///
+    /// See [`cacheable_methods`][Self::cacheable_methods] for more details.
+    #[must_use]
+    pub fn cacheable_methods(self, methods: Methods) -> Self {
+        Self {
+            cacheable_methods: methods,
+            ..self
+        }
+    }
+
+    /// Sets the request methods whose responses are keyed on the request content
+    ///
+    /// See [`body_digest_methods`][Self::body_digest_methods] for more details.
+    #[must_use]
+    pub fn body_digest_methods(self, methods: Methods) -> Self {
+        Self {
+            body_digest_methods: methods,
+            ..self
+        }
+    }
+
+    /// Adds an override for the freshness lifetime of responses with any of the `statuses`
+    ///
+    /// See [`status_ttls`][Self::status_ttls] for more details.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use http_cache_policy::{config::{StatusCodes, TtlOverride}, Config};
+    ///
+    /// let config = Config::default()
+    ///     // cap redirects at an hour
+    ///     .status_ttl(StatusCodes::range(300, 399), TtlOverride::Max(Duration::from_secs(3600)))
+    ///     // and always give 404s 30 seconds
+    ///     .status_ttl(StatusCodes::empty().with(404), TtlOverride::Replace(Duration::from_secs(30)));
+    /// ```
+    #[must_use]
+    pub fn status_ttl(self, statuses: StatusCodes, ttl: TtlOverride) -> Self {
+        let mut status_ttls = self.status_ttls.to_vec();
+        status_ttls.push(StatusTtl { statuses, ttl });
+        Self {
+            status_ttls: status_ttls.into(),
+            ..self
+        }
+    }
+
+    /// Sets the response headers that are removed before the policy stores the response
+    ///
+    /// See [`strip_response_headers`][Self::strip_response_headers] for more details.
+    ///
+    /// ```
+    /// use http::header::SET_COOKIE;
+    /// use http_cache_policy::{config::HeaderNames, Config};
+    ///
+    /// let config = Config::default().strip_response_headers(
+    ///     HeaderNames::from_static(&[SET_COOKIE]).with("server-timing".parse().unwrap()),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn strip_response_headers(self, names: HeaderNames) -> Self {
+        Self {
+            strip_response_headers: names,
+            ..self
+        }
+    }
+
+    /// Sets the largest response header field that the policy will store
+    ///
+    /// See [`max_stored_header_bytes`][Self::max_stored_header_bytes] for more details.
+    #[must_use]
+    pub fn max_stored_header_bytes(self, max: usize) -> Self {
+        Self {
+            max_stored_header_bytes: Some(max),
+            ..self
+        }
+    }
+
+    /// Sets what to do with oversized response header fields
+    ///
+    /// See [`oversized_headers`][Self::oversized_headers] for more details.
+    #[must_use]
+    pub fn oversized_headers(self, oversized: OversizedHeaders) -> Self {
+        Self {
+            oversized_headers: oversized,
+            ..self
+        }
+    }
+
+    /// Sets the query parameters that are ignored when matching requests and generating keys
+    ///
+    /// See [`ignored_query_params`][Self::ignored_query_params] for more details.
+    ///
+    /// ```
+    /// use http_cache_policy::Config;
+    ///
+    /// let config = Config::default().ignored_query_params(["utm_*", "fbclid", "gclid"]);
+    /// ```
+    #[must_use]
+    pub fn ignored_query_params<I, S>(self, params: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Cow<'static, str>>,
+    {
+        Self {
+            ignored_query_params: params.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    /// Sets which request headers the policy stores alongside the response
+    ///
+    /// See [`stored_request_headers`][Self::stored_request_headers] for more details.
+    #[must_use]
+    pub fn stored_request_headers(self, stored: StoredRequestHeaders) -> Self {
+        Self {
+            stored_request_headers: stored,
+            ..self
+        }
+    }
+
+    /// Sets whether policies only store the headers that future decisions depend on
+    ///
+    /// See [`minimize_headers`][Self::minimize_headers] for more details.
+    #[must_use]
+    pub fn minimize_headers(self, minimize_headers: bool) -> Self {
+        Self {
+            minimize_headers,
+            ..self
+        }
+    }
+
+    /// Sets whether the request URI and `Host` header are compared separately
+    ///
+    /// See [`strict_host_matching`][Self::strict_host_matching] for more details.
+    #[must_use]
+    pub fn strict_host_matching(self, strict: bool) -> Self {
+        Self {
+            strict_host_matching: strict,
+            ..self
+        }
+    }
+
+    /// Sets the freshness lifetime of responses without any freshness information
+    ///
+    /// See [`default_ttl`][Self::default_ttl] for more details.
+    #[must_use]
+    pub fn default_ttl(self, ttl: Duration) -> Self {
+        Self {
+            default_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Sets the longest freshness lifetime a response can have
+    ///
+    /// See [`max_ttl`][Self::max_ttl] for more details.
+    #[must_use]
+    pub fn max_ttl(self, ttl: Duration) -> Self {
+        Self {
+            max_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Sets the shortest freshness lifetime a response can have
+    ///
+    /// See [`min_ttl`][Self::min_ttl] for more details.
+    #[must_use]
+    pub fn min_ttl(self, ttl: Duration) -> Self {
+        Self {
+            min_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Sets the freshness lifetime of `404` and `410` responses without explicit freshness
+    /// information
+    ///
+    /// See [`client_error_ttl`][Self::client_error_ttl] for more details.
+    #[must_use]
+    pub fn client_error_ttl(self, ttl: Duration) -> Self {
+        Self {
+            client_error_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Sets the freshness lifetime of `5xx` responses without explicit freshness information
+    ///
+    /// See [`server_error_ttl`][Self::server_error_ttl] for more details.
+    #[must_use]
+    pub fn server_error_ttl(self, ttl: Duration) -> Self {
+        Self {
+            server_error_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Sets whether responses to requests with `Authorization` are never stored in a shared cache
+    ///
+    /// See [`never_cache_authorized`][Self::never_cache_authorized] for more details.
+    #[must_use]
+    pub fn never_cache_authorized(self, never_cache_authorized: bool) -> Self {
+        Self {
+            never_cache_authorized,
+            ..self
+        }
+    }
+
+    /// Sets what picks overrides for the rest of the config from the request URI
+    ///
+    /// See [`overrides`][Self::overrides] for more details.
+    #[must_use]
+    pub fn overrides<O: Overrides + 'static>(self, overrides: O) -> Self {
+        Self {
+            overrides: Some(Arc::new(overrides)),
+            ..self
+        }
+    }
+
+    /// Sets the observer that's called with the outcome of every decision
+    ///
+    /// See [`observer`][Self::observer] for more details.
+    #[must_use]
+    pub fn observer<O: DecisionObserver + 'static>(self, observer: O) -> Self {
+        Self {
+            observer: Some(Arc::new(observer)),
+            ..self
+        }
+    }
+
+    fn is_ignored_query_param(&self, name: &str) -> bool {
+        self.ignored_query_params
+            .iter()
+            .any(|param| match param.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == param,
+            })
+    }
+
+    /// The effective request URI with the ignored query parameters removed
+    pub(crate) fn strip_ignored_query_params(&self, uri: Uri) -> Uri {
+        if self.ignored_query_params.is_empty() {
+            return uri;
+        }
+        let query = match uri.query() {
+            Some(query) => query,
+            None => return uri,
+        };
+        let params = query.split('&');
+        let kept = params
+            .clone()
+            .filter(|param| {
+                let name = param.split('=').next().unwrap_or_default();
+                !self.is_ignored_query_param(name)
+            })
+            .collect::<Vec<_>>();
+        if kept.len() == params.count() {
+            return uri;
+        }
+
+        let mut path_and_query = uri.path().to_owned();
+        if !kept.is_empty() {
+            path_and_query.push('?');
+            path_and_query.push_str(&kept.join("&"));
+        }
+        let path_and_query = PathAndQuery::try_from(path_and_query)
+            .expect("Removing query parameters keeps the URI valid");
+        let mut parts = uri.into_parts();
+        parts.path_and_query = Some(path_and_query);
+        Uri::from_parts(parts).expect("Removing query parameters keeps the URI valid")
+    }
+
+    pub(crate) fn is_oversized(&self, name: &HeaderName, value: &http::HeaderValue) -> bool {
+        self.max_stored_header_bytes
+            .map_or(false, |max| name.as_str().len() + value.len() > max)
+    }
+
+    pub(crate) fn status_ttl_override(&self, status: http::StatusCode) -> Option<TtlOverride> {
+        self.status_ttls
+            .iter()
+            .find(|status_ttl| status_ttl.statuses.contains(status.as_u16()))
+            .map(|status_ttl| status_ttl.ttl)
+    }
+
+    /// The config with the [`overrides`][Self::overrides] for `uri` applied, or `None` without
+    /// any overrides
+    pub(crate) fn for_uri(&self, uri: &Uri) -> Option<Self> {
+        let overrides: ConfigOverrides = self.overrides.as_ref()?.overrides(uri);
+        let mut config = self.clone();
+        overrides.apply(&mut config);
+        Some(config)
+    }
+
+    pub(crate) fn negative_ttl(&self, status: http::StatusCode) -> Option<Duration> {
+        match status.as_u16() {
+            404 | 410 => self.client_error_ttl,
+            500..=599 => self.server_error_ttl,
+            _ => None,
+        }
+    }
 }
 
 impl Default for Config {
+    /// The default cache config
+    ///
+    /// See the various fields' docs for more details.
+    ///
+    /// | field | value |
+    /// | :---: | :--- |
+    /// | [`mode`][Self::mode] | [`Mode::Shared`] |
+    /// | [`last_modified`][Self::last_modified] | 10% of the time since last modified |
+    /// | [`ignore_cargo_cult`][Self::ignore_cargo_cult] | [`false`] |
+    /// | [`understood_statuses`][Self::understood_statuses] | [`StatusCodes::UNDERSTOOD`] |
+    /// | [`heuristic_statuses`][Self::heuristic_statuses] | [`StatusCodes::HEURISTICALLY_CACHEABLE`] |
+    /// | [`cache_query`][Self::cache_query] | [`false`] |
+    /// | [`cacheable_methods`][Self::cacheable_methods] | [`Methods::CACHEABLE`] |
+    /// | [`status_ttls`][Self::status_ttls] | none |
+    /// | [`strip_response_headers`][Self::strip_response_headers] | none |
+    /// | [`max_stored_header_bytes`][Self::max_stored_header_bytes] | [`None`] |
+    /// | [`oversized_headers`][Self::oversized_headers] | [`OversizedHeaders::Drop`] |
+    /// | [`ignored_query_params`][Self::ignored_query_params] | none |
+    /// | [`stored_request_headers`][Self::stored_request_headers] | [`StoredRequestHeaders::All`] |
+    /// | [`minimize_headers`][Self::minimize_headers] | `false` |
+    /// | [`strict_host_matching`][Self::strict_host_matching] | `false` |
+    /// | [`body_digest_methods`][Self::body_digest_methods] | none |
+    /// | [`default_ttl`][Self::default_ttl] | [`None`] |
+    /// | [`max_ttl`][Self::max_ttl] | [`None`] |
+    /// | [`min_ttl`][Self::min_ttl] | [`None`] |
+    /// | [`client_error_ttl`][Self::client_error_ttl] | [`None`] |
+    /// | [`server_error_ttl`][Self::server_error_ttl] | [`None`] |
+    /// | [`never_cache_authorized`][Self::never_cache_authorized] | `false` |
+    /// | [`overrides`][Self::overrides] | [`None`] |
+    /// | [`observer`][Self::observer] | [`None`] |
     fn default() -> Self {
-        Self::default()
+        Self {
+            mode: Mode::default(),
+            last_modified: LastModifiedHeuristic::default(), // 10% matches IE
+            ignore_cargo_cult: false,
+            understood_statuses: StatusCodes::UNDERSTOOD,
+            heuristic_statuses: StatusCodes::HEURISTICALLY_CACHEABLE,
+            cache_query: false,
+            cacheable_methods: Methods::CACHEABLE,
+            status_ttls: Arc::new([]),
+            strip_response_headers: HeaderNames::from_static(&[]),
+            max_stored_header_bytes: None,
+            oversized_headers: OversizedHeaders::Drop,
+            ignored_query_params: Arc::new([]),
+            stored_request_headers: StoredRequestHeaders::All,
+            minimize_headers: false,
+            strict_host_matching: false,
+            body_digest_methods: Methods::empty(),
+            default_ttl: None,
+            max_ttl: None,
+            min_ttl: None,
+            client_error_ttl: None,
+            server_error_ttl: None,
+            never_cache_authorized: false,
+            overrides: None,
+            observer: None,
+        }
     }
 }
 
+/// What to do with response header fields over [`Config::max_stored_header_bytes`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OversizedHeaders {
+    /// Drop the oversized fields (default) while storing the rest of the response
+    ///
+    /// Dropped fields are gone before the response is evaluated, so an oversized `Cache-Control`
+    /// affects the policy's decisions too
+    #[default]
+    Drop,
+    /// Make the whole response unstorable
+    ///
+    /// See [`Storability::HeadersTooLarge`][crate::Storability::HeadersTooLarge]
+    NotStorable,
+}
+
+/// Which request headers are kept by [`Config::stored_request_headers`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StoredRequestHeaders {
+    /// Keep every request header (default)
+    #[default]
+    All,
+    /// Only keep `Host`, the request headers nominated by the response's `Vary` header, and a
+    /// placeholder in place of `Authorization`
+    ///
+    /// This shrinks stored policies and keeps cookies and credentials from being persisted. The
+    /// placeholder only records that there was an `Authorization` header, so storability still
+    /// accounts for it. Since the credential can't be compared, a shared cache then revalidates
+    /// non-`public` responses for every request, even one from the same user, and
+    /// `Vary: Authorization` never matches again. The request's `Cache-Control`
+    /// is evaluated before its headers are dropped, but it's gone from the stored policy so
+    /// re-evaluating it with [`CachePolicy::with_options()`][crate::CachePolicy::with_options]
+    /// won't see it.
+    Vary,
+}
+
 /// Indicates the mode the cache is operating in
 ///
 /// This influences the impact of things like the `private` or `s-maxage` directives or the
@@ -126,3 +679,391 @@ impl From<LastModifiedHeuristic> for f32 {
         l_m.0
     }
 }
+
+/// A set of HTTP status codes
+///
+/// ```
+/// use http_cache_policy::config::StatusCodes;
+///
+/// let statuses = StatusCodes::UNDERSTOOD.with(206).with(226);
+/// assert!(statuses.contains(206));
+/// assert!(!StatusCodes::UNDERSTOOD.contains(206));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatusCodes([u64; 16]);
+
+impl StatusCodes {
+    /// The status codes understood by default
+    ///
+    /// This implementation does not understand partial responses (206)
+    pub const UNDERSTOOD: Self = Self::from_slice(&[
+        200, 203, 204, 300, 301, 302, 303, 307, 308, 404, 405, 410, 414, 501,
+    ]);
+
+    /// The status codes defined as heuristically cacheable (rfc9110 15.1)
+    pub const HEURISTICALLY_CACHEABLE: Self =
+        Self::from_slice(&[200, 203, 204, 206, 300, 301, 308, 404, 405, 410, 414, 501]);
+
+    /// An empty set
+    pub const fn empty() -> Self {
+        Self([0; 16])
+    }
+
+    /// A set containing all of the `codes`
+    ///
+    /// # Panics
+    ///
+    /// If any of the codes are not a valid status code (`100..=999`)
+    pub const fn from_slice(codes: &[u16]) -> Self {
+        let mut set = Self::empty();
+        let mut i = 0;
+        while i < codes.len() {
+            set = set.with(codes[i]);
+            i += 1;
+        }
+        set
+    }
+
+    /// Adds `code` to the set
+    ///
+    /// # Panics
+    ///
+    /// If `code` is not a valid status code (`100..=999`)
+    #[must_use]
+    pub const fn with(mut self, code: u16) -> Self {
+        assert!(100 <= code && code <= 999, "Invalid status code");
+        self.0[code as usize / 64] |= 1 << (code % 64);
+        self
+    }
+
+    /// A set containing every code from `first` to `last` (inclusive) e.g. `300, 399` for all
+    /// redirects
+    ///
+    /// # Panics
+    ///
+    /// If the range includes invalid status codes (outside of `100..=999`)
+    pub const fn range(first: u16, last: u16) -> Self {
+        let mut set = Self::empty();
+        let mut code = first;
+        while code <= last {
+            set = set.with(code);
+            code += 1;
+        }
+        set
+    }
+
+    /// Removes `code` from the set
+    #[must_use]
+    pub const fn without(mut self, code: u16) -> Self {
+        if code <= 999 {
+            self.0[code as usize / 64] &= !(1 << (code % 64));
+        }
+        self
+    }
+
+    /// If the set contains `code`
+    pub const fn contains(&self, code: u16) -> bool {
+        code <= 999 && self.0[code as usize / 64] & (1 << (code % 64)) != 0
+    }
+
+    /// Iterates over the codes in the set in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        (100..=999).filter(move |&code| self.contains(code))
+    }
+}
+
+impl fmt::Debug for StatusCodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// A set of HTTP request methods
+///
+/// ```
+/// use http::Method;
+/// use http_cache_policy::config::Methods;
+///
+/// let purge = Method::from_bytes(b"PURGE").unwrap();
+/// let methods = Methods::CACHEABLE.with(purge.clone());
+/// assert!(methods.contains(&purge));
+/// assert!(methods.contains(&Method::GET));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Methods {
+    /// A bit for each of the [`STANDARD_METHODS`] in the set
+    standard: u16,
+    /// Every other method in the set
+    extensions: Cow<'static, [Method]>,
+}
+
+/// The methods defined in rfc9110 and rfc5789, in the order of their bits in [`Methods`]
+static STANDARD_METHODS: [Method; 9] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::DELETE,
+    Method::CONNECT,
+    Method::OPTIONS,
+    Method::TRACE,
+    Method::PATCH,
+];
+
+fn standard_bit(method: &Method) -> Option<u16> {
+    STANDARD_METHODS
+        .iter()
+        .position(|standard| standard == method)
+        .map(|i| 1 << i)
+}
+
+impl Methods {
+    /// The methods defined as cacheable by default: `GET`, `HEAD`, and `POST`
+    pub const CACHEABLE: Self = Self {
+        // The first three standard methods
+        standard: 0b111,
+        extensions: Cow::Borrowed(&[]),
+    };
+
+    /// An empty set
+    pub const fn empty() -> Self {
+        Self {
+            standard: 0,
+            extensions: Cow::Borrowed(&[]),
+        }
+    }
+
+    /// A set containing all of the `methods`
+    ///
+    /// This only allocates when `methods` mixes standard methods with extension methods.
+    pub fn from_static(methods: &'static [Method]) -> Self {
+        let mut standard = 0;
+        let mut extension_count = 0;
+        for method in methods {
+            match standard_bit(method) {
+                Some(bit) => standard |= bit,
+                None => extension_count += 1,
+            }
+        }
+        let extensions: Cow<'static, [Method]> = if extension_count == methods.len() {
+            Cow::Borrowed(methods)
+        } else if extension_count == 0 {
+            Cow::Borrowed(&[])
+        } else {
+            methods
+                .iter()
+                .filter(|method| standard_bit(method).is_none())
+                .cloned()
+                .collect()
+        };
+        Self {
+            standard,
+            extensions,
+        }
+    }
+
+    /// Adds `method` to the set
+    #[must_use]
+    pub fn with(mut self, method: Method) -> Self {
+        match standard_bit(&method) {
+            Some(bit) => self.standard |= bit,
+            None if self.extensions.contains(&method) => {}
+            None => self.extensions.to_mut().push(method),
+        }
+        self
+    }
+
+    /// Removes `method` from the set
+    #[must_use]
+    pub fn without(mut self, method: &Method) -> Self {
+        match standard_bit(method) {
+            Some(bit) => self.standard &= !bit,
+            None if !self.extensions.contains(method) => {}
+            None => self.extensions.to_mut().retain(|m| m != method),
+        }
+        self
+    }
+
+    /// If the set contains `method`
+    pub fn contains(&self, method: &Method) -> bool {
+        match standard_bit(method) {
+            Some(bit) => self.standard & bit != 0,
+            None => self.extensions.contains(method),
+        }
+    }
+
+    /// Iterates over the methods in the set
+    pub fn iter(&self) -> impl Iterator<Item = &Method> {
+        let standard = self.standard;
+        STANDARD_METHODS
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| standard & (1 << i) != 0)
+            .map(|(_, method)| method)
+            .chain(self.extensions.iter())
+    }
+}
+
+impl FromIterator<Method> for Methods {
+    fn from_iter<I: IntoIterator<Item = Method>>(iter: I) -> Self {
+        let mut standard = 0;
+        let mut extensions = Vec::new();
+        for method in iter {
+            match standard_bit(&method) {
+                Some(bit) => standard |= bit,
+                None if extensions.contains(&method) => {}
+                None => extensions.push(method),
+            }
+        }
+        Self {
+            standard,
+            extensions: Cow::Owned(extensions),
+        }
+    }
+}
+
+impl fmt::Debug for Methods {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Methods {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(Method::as_str))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Methods {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let methods = <Vec<Cow<'de, str>>>::deserialize(deserializer)?;
+        methods
+            .iter()
+            .map(|method| Method::from_bytes(method.as_bytes()).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+/// A set of HTTP header names
+///
+/// ```
+/// use http::header::{SERVER, SET_COOKIE};
+/// use http_cache_policy::config::HeaderNames;
+///
+/// let names = HeaderNames::from_static(&[SET_COOKIE]).with(SERVER);
+/// assert!(names.contains(&SERVER));
+/// assert!(names.contains(&SET_COOKIE));
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct HeaderNames(Cow<'static, [HeaderName]>);
+
+impl HeaderNames {
+    /// A set using the `names` without allocating
+    pub const fn from_static(names: &'static [HeaderName]) -> Self {
+        Self(Cow::Borrowed(names))
+    }
+
+    /// Adds `name` to the set
+    #[must_use]
+    pub fn with(mut self, name: HeaderName) -> Self {
+        if !self.contains(&name) {
+            self.0.to_mut().push(name);
+        }
+        self
+    }
+
+    /// Removes `name` from the set
+    #[must_use]
+    pub fn without(mut self, name: &HeaderName) -> Self {
+        if self.contains(name) {
+            self.0.to_mut().retain(|n| n != name);
+        }
+        self
+    }
+
+    /// If the set contains `name`
+    pub fn contains(&self, name: &HeaderName) -> bool {
+        self.0.contains(name)
+    }
+
+    /// If the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the names in the set
+    pub fn iter(&self) -> impl Iterator<Item = &HeaderName> {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<HeaderName> for HeaderNames {
+    fn from_iter<I: IntoIterator<Item = HeaderName>>(iter: I) -> Self {
+        let mut names = Vec::new();
+        for name in iter {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        Self(Cow::Owned(names))
+    }
+}
+
+impl fmt::Debug for HeaderNames {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for HeaderNames {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(HeaderName::as_str))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for HeaderNames {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = <Vec<Cow<'de, str>>>::deserialize(deserializer)?;
+        names
+            .iter()
+            .map(|name| HeaderName::from_bytes(name.as_bytes()).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+/// An override for the freshness lifetime of responses with some status codes
+///
+/// See [`Config::status_ttl()`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatusTtl {
+    /// The statuses that the override applies to
+    pub statuses: StatusCodes,
+    /// The override itself
+    pub ttl: TtlOverride,
+}
+
+/// How a [`StatusTtl`] changes the computed freshness lifetime
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TtlOverride {
+    /// Caps the freshness lifetime at this value
+    Max(Duration),
+    /// Replaces the freshness lifetime with this value
+    Replace(Duration),
+}
+
+impl TtlOverride {
+    /// Applies the override to a computed freshness lifetime
+    pub fn apply(self, ttl: Duration) -> Duration {
+        match self {
+            Self::Max(max) => ttl.min(max),
+            Self::Replace(ttl) => ttl,
+        }
+    }
+}
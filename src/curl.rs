@@ -0,0 +1,90 @@
+//! Building policies from [`curl`](https://docs.rs/curl) transfers
+//!
+//! libcurl hands response headers to a callback one line at a time, so [`HeaderLines`] collects
+//! them and [`builder()`] combines them with an [`Easy`] handle's effective URL and response code
+//! into a [`CachePolicyBuilder`].
+//!
+//! ```no_run
+//! use curl::easy::Easy;
+//! use http_cache_policy::curl::{builder, HeaderLines};
+//!
+//! let mut easy = Easy::new();
+//! easy.url("https://example.com/").unwrap();
+//! let mut lines = HeaderLines::new();
+//! let mut body = Vec::new();
+//! {
+//!     let mut transfer = easy.transfer();
+//!     transfer.header_function(|line| lines.push(line)).unwrap();
+//!     transfer
+//!         .write_function(|data| {
+//!             body.extend_from_slice(data);
+//!             Ok(data.len())
+//!         })
+//!         .unwrap();
+//!     transfer.perform().unwrap();
+//! }
+//!
+//! let policy = builder(&mut easy, &lines).unwrap().build().unwrap();
+//! ```
+
+use std::str;
+
+use curl::easy::Easy;
+use http::{HeaderMap, HeaderName};
+
+use crate::builder::{BuildError, CachePolicyBuilder};
+
+/// The response header lines passed to an [`Easy`] handle's header callback
+///
+/// Only the last response's headers are kept, so interim `1xx` responses and redirects that were
+/// followed don't leak into the final response's headers
+#[derive(Debug, Clone, Default)]
+pub struct HeaderLines {
+    lines: Vec<(String, String)>,
+}
+
+impl HeaderLines {
+    /// No header lines
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a line passed to the header callback
+    ///
+    /// This always returns `true` so that it can be returned from the callback. Status lines start
+    /// a new response, and lines that aren't UTF-8 or aren't `name: value` fields are skipped.
+    pub fn push(&mut self, line: &[u8]) -> bool {
+        if line.starts_with(b"HTTP/") {
+            self.lines.clear();
+        } else if let Some((name, value)) = str::from_utf8(line)
+            .ok()
+            .and_then(|line| line.split_once(':'))
+        {
+            self.lines.push((name.trim().into(), value.trim().into()));
+        }
+        true
+    }
+
+    /// The recorded headers
+    pub fn headers(&self) -> Result<HeaderMap, BuildError> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.lines {
+            headers.append(HeaderName::from_bytes(name.as_bytes())?, value.parse()?);
+        }
+        Ok(headers)
+    }
+}
+
+/// A builder with the effective URL, response code, and response headers of a finished transfer
+///
+/// The builder defaults to a `GET` request without headers, so the method and any request headers
+/// that the response could vary on should be set before building the policy
+pub fn builder(easy: &mut Easy, lines: &HeaderLines) -> Result<CachePolicyBuilder, curl::Error> {
+    let mut builder = CachePolicyBuilder::new().status(&easy.response_code()?.to_string());
+    if let Some(url) = easy.effective_url()? {
+        builder = builder.uri(url);
+    }
+    Ok(lines.lines.iter().fold(builder, |builder, (name, value)| {
+        builder.response_header(name, value)
+    }))
+}
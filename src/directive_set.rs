@@ -0,0 +1,233 @@
+//! A compact set of `Cache-Control` directives
+//!
+//! Policies hold on to the directives of the stored request and response for as long as they're
+//! cached, so a map that allocates per directive adds up in large caches. [`DirectiveSet`] keeps
+//! the well-known directives as bits along with their `delta-seconds` values, and only allocates
+//! for extension directives and values that don't fit that shape. Even then the names of
+//! well-known directives are static strings.
+
+use std::{borrow::Cow, fmt};
+
+/// The directives that are stored as bits, where the first [`SECONDS`] can hold `delta-seconds`
+const KNOWN: [&str; 18] = [
+    "max-age",
+    "s-maxage",
+    "stale-while-revalidate",
+    "stale-if-error",
+    "min-fresh",
+    "max-stale",
+    "pre-check",
+    "post-check",
+    "no-cache",
+    "no-store",
+    "private",
+    "public",
+    "must-revalidate",
+    "proxy-revalidate",
+    "must-understand",
+    "immutable",
+    "no-transform",
+    "only-if-cached",
+];
+
+/// How many of the [`KNOWN`] directives take `delta-seconds`
+const SECONDS: usize = 8;
+
+fn known_index(name: &str) -> Option<usize> {
+    KNOWN.iter().position(|known| *known == name)
+}
+
+/// If the policy acts on the directive, as opposed to it being an extension
+pub(crate) fn is_known(name: &str) -> bool {
+    known_index(name).is_some()
+}
+
+/// Seconds are only stored as a number when they format back to the same text e.g. not `060`
+fn canonical_seconds(value: &str) -> Option<u32> {
+    let is_canonical =
+        value.bytes().all(|b| b.is_ascii_digit()) && (value == "0" || !value.starts_with('0'));
+    if !is_canonical {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// The value of a directive in a [`DirectiveSet`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DirectiveValue<'a> {
+    /// The directive doesn't have a value
+    None,
+    /// A `delta-seconds` value
+    Seconds(u32),
+    /// Any other value
+    Text(&'a str),
+}
+
+impl<'a> DirectiveValue<'a> {
+    fn from_text(value: &'a Option<Box<str>>) -> Self {
+        value.as_deref().map_or(Self::None, Self::Text)
+    }
+
+    /// The value as it appears in a header
+    pub(crate) fn to_text(self) -> Option<Cow<'a, str>> {
+        match self {
+            Self::None => None,
+            Self::Seconds(secs) => Some(secs.to_string().into()),
+            Self::Text(value) => Some(value.into()),
+        }
+    }
+}
+
+/// The `Cache-Control` directives that a policy acts on, without any duplicate names
+#[derive(Clone, Default)]
+pub(crate) struct DirectiveSet {
+    /// Bits for the [`KNOWN`] directives that are present
+    present: u32,
+    /// Bits for the present directives whose value is in `seconds`, the rest don't have one
+    with_seconds: u32,
+    seconds: [u32; SECONDS],
+    /// Extension directives, and known directives with values that don't fit the bits
+    ///
+    /// The names of known directives still point to [`KNOWN`] here instead of being allocated
+    other: Vec<(Cow<'static, str>, Option<Box<str>>)>,
+}
+
+impl DirectiveSet {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn known_value(&self, i: usize) -> DirectiveValue<'_> {
+        if self.with_seconds & (1 << i) != 0 {
+            DirectiveValue::Seconds(self.seconds[i])
+        } else {
+            DirectiveValue::None
+        }
+    }
+
+    /// The value of the directive with the given (lowercase) name if it's present
+    pub(crate) fn get(&self, name: &str) -> Option<DirectiveValue<'_>> {
+        if let Some(i) = known_index(name) {
+            if self.present & (1 << i) != 0 {
+                return Some(self.known_value(i));
+            }
+        }
+        self.other
+            .iter()
+            .find(|(other, _)| &**other == name)
+            .map(|(_, value)| DirectiveValue::from_text(value))
+    }
+
+    pub(crate) fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Sets the directive with the given (lowercase) name, replacing any earlier value
+    pub(crate) fn insert(&mut self, name: &str, value: Option<&str>) {
+        self.remove(name);
+        let known = known_index(name);
+        if let Some(i) = known {
+            let bit = 1 << i;
+            match value {
+                None => {
+                    self.present |= bit;
+                    return;
+                }
+                Some(value) => {
+                    if let Some(secs) = canonical_seconds(value).filter(|_| i < SECONDS) {
+                        self.present |= bit;
+                        self.with_seconds |= bit;
+                        self.seconds[i] = secs;
+                        return;
+                    }
+                }
+            }
+        }
+        let name = match known {
+            Some(i) => Cow::Borrowed(KNOWN[i]),
+            None => Cow::Owned(name.into()),
+        };
+        self.other.push((name, value.map(From::from)));
+    }
+
+    pub(crate) fn remove(&mut self, name: &str) {
+        if let Some(i) = known_index(name) {
+            self.present &= !(1 << i);
+            self.with_seconds &= !(1 << i);
+        }
+        self.other.retain(|(other, _)| &**other != name);
+    }
+
+    /// Roughly how many bytes the directives that don't fit the bits use on the heap
+    pub(crate) fn heap_size(&self) -> usize {
+        let entries = self.other.capacity() * std::mem::size_of::<(Cow<str>, Option<Box<str>>)>();
+        let text = self.other.iter().map(|(name, value)| {
+            let name = match name {
+                Cow::Borrowed(_) => 0,
+                Cow::Owned(name) => name.capacity(),
+            };
+            name + value.as_ref().map_or(0, |value| value.len())
+        });
+        entries + text.sum::<usize>()
+    }
+
+    /// Every directive and its value, starting with the well-known ones
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, DirectiveValue<'_>)> {
+        let known = KNOWN
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| self.present & (1 << i) != 0)
+            .map(move |(i, name)| (*name, self.known_value(i)));
+        let other = self
+            .other
+            .iter()
+            .map(|(name, value)| (&**name, DirectiveValue::from_text(value)));
+        known.chain(other)
+    }
+}
+
+impl fmt::Debug for DirectiveSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|(name, value)| (name, value.to_text())))
+            .finish()
+    }
+}
+
+// Serialized as a map from names to optional values, which is how directives were always stored
+#[cfg(feature = "serde")]
+impl serde::Serialize for DirectiveSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter().map(|(name, value)| (name, value.to_text())))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DirectiveSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = DirectiveSet;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a map of `Cache-Control` directives")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut set = DirectiveSet::new();
+                while let Some((name, value)) =
+                    map.next_entry::<Cow<'de, str>, Option<Cow<'de, str>>>()?
+                {
+                    set.insert(&name, value.as_deref());
+                }
+                Ok(set)
+            }
+        }
+
+        deserializer.deserialize_map(Visitor)
+    }
+}
@@ -0,0 +1,208 @@
+//! Typed views of `Cache-Control` directives
+
+use std::{borrow::Cow, time::Duration};
+
+use http::{
+    header::{CACHE_CONTROL, PRAGMA},
+    HeaderMap,
+};
+
+use crate::{
+    directive_set::{is_known, DirectiveSet, DirectiveValue},
+    parse_cache_control,
+};
+
+/// Parses a `delta-seconds` directive value
+///
+/// Directives without a value are ignored while an invalid value counts as `0` which errs on the
+/// side of treating things as stale
+fn delta_seconds(cc: &DirectiveSet, name: &str) -> Option<Duration> {
+    let secs = match cc.get(name)? {
+        DirectiveValue::None => return None,
+        DirectiveValue::Seconds(secs) => secs.into(),
+        DirectiveValue::Text(value) => value.trim().parse().unwrap_or(0),
+    };
+    Some(Duration::from_secs(secs))
+}
+
+/// The `Cache-Control` directives of a stored response
+///
+/// These are the directives that the policy acts on, so they can differ from the raw header e.g.
+/// when [`Config::ignore_cargo_cult`][crate::Config::ignore_cargo_cult] dropped some, or a
+/// `Pragma: no-cache` was folded into `no-cache`.
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseDirectives<'a> {
+    cc: &'a DirectiveSet,
+}
+
+impl<'a> ResponseDirectives<'a> {
+    pub(crate) fn new(cc: &'a DirectiveSet) -> Self {
+        Self { cc }
+    }
+
+    /// If the directive is present
+    pub fn contains(&self, name: &str) -> bool {
+        self.cc.contains_key(name)
+    }
+
+    /// The value of a directive e.g. for extension directives
+    ///
+    /// Returns `Some(None)` for a directive without a value and `None` when it's absent
+    pub fn get(&self, name: &str) -> Option<Option<Cow<'a, str>>> {
+        self.cc.get(name).map(DirectiveValue::to_text)
+    }
+
+    /// Every directive and its value
+    ///
+    /// The well-known directives come first, followed by extension directives in the order they
+    /// appeared
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, Option<Cow<'a, str>>)> {
+        self.cc.iter().map(|(k, v)| (k, v.to_text()))
+    }
+
+    /// The directives that the policy doesn't act on itself, in the order they appeared
+    ///
+    /// These are e.g. CDN-specific extensions, which callers can layer their own meaning on top of
+    pub fn extensions(&self) -> impl Iterator<Item = (&'a str, Option<Cow<'a, str>>)> {
+        self.iter().filter(|(name, _)| !is_known(name))
+    }
+
+    /// `max-age`
+    pub fn max_age(&self) -> Option<Duration> {
+        delta_seconds(self.cc, "max-age")
+    }
+
+    /// `s-maxage` which only applies to shared caches
+    pub fn s_maxage(&self) -> Option<Duration> {
+        delta_seconds(self.cc, "s-maxage")
+    }
+
+    /// `stale-while-revalidate` (rfc5861 3)
+    pub fn stale_while_revalidate(&self) -> Option<Duration> {
+        delta_seconds(self.cc, "stale-while-revalidate")
+    }
+
+    /// `stale-if-error` (rfc5861 4)
+    pub fn stale_if_error(&self) -> Option<Duration> {
+        delta_seconds(self.cc, "stale-if-error")
+    }
+
+    /// `public`
+    pub fn is_public(&self) -> bool {
+        self.contains("public")
+    }
+
+    /// `private`
+    pub fn is_private(&self) -> bool {
+        self.contains("private")
+    }
+
+    /// `no-cache`
+    pub fn no_cache(&self) -> bool {
+        self.contains("no-cache")
+    }
+
+    /// `no-store`
+    pub fn no_store(&self) -> bool {
+        self.contains("no-store")
+    }
+
+    /// `no-transform`
+    pub fn no_transform(&self) -> bool {
+        self.contains("no-transform")
+    }
+
+    /// `must-revalidate`
+    pub fn must_revalidate(&self) -> bool {
+        self.contains("must-revalidate")
+    }
+
+    /// `proxy-revalidate` which only applies to shared caches
+    pub fn proxy_revalidate(&self) -> bool {
+        self.contains("proxy-revalidate")
+    }
+
+    /// `must-understand`
+    pub fn must_understand(&self) -> bool {
+        self.contains("must-understand")
+    }
+
+    /// `immutable` (rfc8246)
+    pub fn immutable(&self) -> bool {
+        self.contains("immutable")
+    }
+}
+
+/// The `Cache-Control` directives of a request
+///
+/// This can be used standalone e.g. to skip looking up entries for `no-store` requests or to
+/// answer `only-if-cached` requests without going to the origin.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use http::{header, HeaderMap, HeaderValue};
+/// use http_cache_policy::directives::RequestDirectives;
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert(
+///     header::CACHE_CONTROL,
+///     HeaderValue::from_static("max-age=60, max-stale, only-if-cached"),
+/// );
+/// let directives = RequestDirectives::from_headers(&headers);
+/// assert_eq!(directives.max_age, Some(Duration::from_secs(60)));
+/// assert_eq!(directives.max_stale, Some(None));
+/// assert!(directives.only_if_cached);
+/// assert!(!directives.no_store);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct RequestDirectives {
+    /// `no-cache` or the `Pragma: no-cache` equivalent
+    pub no_cache: bool,
+    /// `no-store`
+    pub no_store: bool,
+    /// `max-age`
+    pub max_age: Option<Duration>,
+    /// `max-stale` where `Some(None)` accepts a stale response of any age
+    pub max_stale: Option<Option<Duration>>,
+    /// `min-fresh`
+    pub min_fresh: Option<Duration>,
+    /// `no-transform`
+    pub no_transform: bool,
+    /// `only-if-cached`
+    pub only_if_cached: bool,
+}
+
+impl RequestDirectives {
+    /// Parses the directives from a request's `Cache-Control` and `Pragma` headers
+    ///
+    /// Directives with an invalid value are ignored, except for `max-stale` which falls back to
+    /// accepting any staleness
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let cc = parse_cache_control(headers.get_all(CACHE_CONTROL));
+        let seconds = |name: &str| {
+            let secs = match cc.get(name)? {
+                DirectiveValue::None => return None,
+                DirectiveValue::Seconds(secs) => secs.into(),
+                DirectiveValue::Text(value) => value.trim().parse().ok()?,
+            };
+            Some(Duration::from_secs(secs))
+        };
+        let pragma_no_cache = headers
+            .get_all(PRAGMA)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| v.contains("no-cache"));
+
+        Self {
+            no_cache: cc.contains_key("no-cache") || pragma_no_cache,
+            no_store: cc.contains_key("no-store"),
+            max_age: seconds("max-age"),
+            max_stale: cc.contains_key("max-stale").then(|| seconds("max-stale")),
+            min_fresh: seconds("min-fresh"),
+            no_transform: cc.contains_key("no-transform"),
+            only_if_cached: cc.contains_key("only-if-cached"),
+        }
+    }
+}
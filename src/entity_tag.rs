@@ -0,0 +1,127 @@
+//! Parsing for entity-tags and the lists of them in `If-Match` and `If-None-Match`
+//!
+//! An entity-tag is an optional `W/` weak prefix followed by a quoted opaque-tag (rfc9110 8.8.3),
+//! and the opaque-tag may contain commas, so lists can't just be split on them. Parsing is
+//! lenient: unquoted tags sent by non-conforming servers are kept up to the next comma, and any
+//! junk after a closing quote is skipped.
+
+use http::HeaderValue;
+
+/// A single entity-tag, or `*` in a list that matches any tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EntityTag<'a> {
+    /// The tag as it appeared in the header, including the weak prefix
+    text: &'a str,
+    weak: bool,
+}
+
+impl<'a> EntityTag<'a> {
+    /// The tag as it appeared in the header
+    pub(crate) fn as_str(self) -> &'a str {
+        self.text
+    }
+
+    pub(crate) fn is_weak(self) -> bool {
+        self.weak
+    }
+
+    /// `*` which matches any current representation
+    pub(crate) fn is_any(self) -> bool {
+        self.text == "*"
+    }
+
+    /// The tag without its weak prefix
+    fn opaque(self) -> &'a str {
+        if self.weak {
+            &self.text[2..]
+        } else {
+            self.text
+        }
+    }
+
+    /// Both tags are strong and identical (rfc9110 8.8.3.2)
+    pub(crate) fn strong_eq(self, other: Self) -> bool {
+        !self.weak && !other.weak && self.opaque() == other.opaque()
+    }
+
+    /// The tags are identical when ignoring weakness (rfc9110 8.8.3.2)
+    pub(crate) fn weak_eq(self, other: Self) -> bool {
+        self.opaque() == other.opaque()
+    }
+}
+
+/// Every entity-tag in a comma-separated list
+pub(crate) struct EntityTags<'a> {
+    rest: &'a str,
+}
+
+impl<'a> EntityTags<'a> {
+    pub(crate) fn new(list: &'a str) -> Self {
+        Self { rest: list }
+    }
+}
+
+impl<'a> Iterator for EntityTags<'a> {
+    type Item = EntityTag<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest.trim_start_matches([',', ' ', '\t']);
+        if rest.is_empty() {
+            self.rest = rest;
+            return None;
+        }
+
+        let weak = rest.starts_with("W/");
+        let prefix = if weak { 2 } else { 0 };
+        let tag = &rest[prefix..];
+        let (len, after) = if let Some(quoted) = tag.strip_prefix('"') {
+            match quoted.find('"') {
+                Some(end) => {
+                    let len = prefix + end + 2;
+                    // Anything between the closing quote and the next comma isn't part of a tag
+                    let after = rest[len..].find(',').map_or(rest.len(), |i| len + i);
+                    (len, after)
+                }
+                None => (rest.len(), rest.len()),
+            }
+        } else {
+            let len = rest.find(',').unwrap_or(rest.len());
+            (len, len)
+        };
+        self.rest = &rest[after..];
+
+        Some(EntityTag {
+            text: rest[..len].trim_end(),
+            weak,
+        })
+    }
+}
+
+/// The entity-tags across every value of a header
+pub(crate) fn entity_tags<'a>(
+    all: impl IntoIterator<Item = &'a HeaderValue>,
+) -> impl Iterator<Item = EntityTag<'a>> {
+    all.into_iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(EntityTags::new)
+}
+
+/// Drops tags that are equivalent under the weak comparison function, which is what
+/// `If-None-Match` uses
+///
+/// The first occurrence keeps its place, but a strong tag wins over an equivalent weak one since
+/// it can still be sent where weak validators aren't allowed.
+pub(crate) fn dedup<'a>(tags: impl Iterator<Item = EntityTag<'a>>) -> Vec<EntityTag<'a>> {
+    let mut unique: Vec<EntityTag<'a>> = Vec::new();
+    for tag in tags {
+        match unique.iter_mut().find(|seen| seen.weak_eq(tag)) {
+            Some(seen) => {
+                if seen.weak && !tag.weak {
+                    *seen = tag;
+                }
+            }
+            None => unique.push(tag),
+        }
+    }
+    unique
+}
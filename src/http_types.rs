@@ -0,0 +1,65 @@
+//! Conversions for [`http-types`](https://docs.rs/http-types) requests and responses
+//!
+//! `http-types`, which `surf` and `async-h1` are built on, keeps its own header representation,
+//! so its types are converted into the tuples that implement [`RequestLike`][crate::RequestLike]
+//! and [`ResponseLike`][crate::ResponseLike].
+//!
+//! ```
+//! use http_cache_policy::{http_types as convert, CachePolicy};
+//! use http_types::{Method, Request, Response, StatusCode};
+//!
+//! let req = Request::new(Method::Get, "https://example.com/a");
+//! let mut res = Response::new(StatusCode::Ok);
+//! res.insert_header("cache-control", "max-age=60");
+//! let policy = CachePolicy::new(&convert::request(&req).unwrap(), &convert::response(&res).unwrap());
+//! assert!(policy.is_storable());
+//! ```
+
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri};
+use http_types::{headers::Headers, Request, Response};
+
+use crate::{builder::BuildError, interop::validators};
+
+/// Converts a request into the `(Uri, Method, HeaderMap)` tuple that implements `RequestLike`
+pub fn request(req: &Request) -> Result<(Uri, Method, HeaderMap), BuildError> {
+    let uri = req.url().as_str().parse()?;
+    let method = Method::from_bytes(req.method().as_ref().as_bytes())?;
+    Ok((uri, method, headers(req.as_ref())?))
+}
+
+/// Converts a response into the `(StatusCode, HeaderMap)` tuple that implements `ResponseLike`
+pub fn response(res: &Response) -> Result<(StatusCode, HeaderMap), BuildError> {
+    let status = StatusCode::from_u16(res.status().into())?;
+    Ok((status, headers(res.as_ref())?))
+}
+
+/// Converts a header map, keeping every value of the names with several values
+pub fn headers(headers: &Headers) -> Result<HeaderMap, BuildError> {
+    let mut converted = HeaderMap::new();
+    for (name, values) in headers {
+        let name = HeaderName::from_bytes(name.as_str().as_bytes())?;
+        for value in values {
+            converted.append(&name, HeaderValue::from_str(value.as_str())?);
+        }
+    }
+    Ok(converted)
+}
+
+/// Brings the conditional headers of `req` in line with
+/// [`BeforeRequest::Stale`][crate::BeforeRequest::Stale]'s request `headers`
+///
+/// The validators that the policy left out e.g. a client's `If-Range` are removed from `req` too,
+/// since `http-types` requests are usually sent on as they are. Values that aren't UTF-8 can't
+/// be set, so the header is removed instead.
+pub fn apply_revalidation_headers(req: &mut Request, headers: &HeaderMap) {
+    for (name, value) in validators(headers) {
+        match value.and_then(|value| value.to_str().ok()) {
+            Some(value) => {
+                req.insert_header(name.as_str(), value);
+            }
+            None => {
+                req.remove_header(name.as_str());
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Client-side caching for [`hyper`](https://docs.rs/hyper) clients
+//!
+//! [`CachingClient`] wraps a [`hyper_util`](https://docs.rs/hyper-util) client with a
+//! [`CacheService`], so requests are answered from a [`CacheStorage`] when the stored policies
+//! allow it, stale responses are revalidated with conditional requests, and `304`s are answered
+//! with the stored bodies. Response bodies are buffered into [`Bytes`] so that they can be stored.
+//!
+//! [`BufferBody`] does the buffering for any other service with streaming response bodies.
+
+use std::{
+    error::Error,
+    fmt,
+    future::{poll_fn, Future},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http::{Request, Response};
+use http_body::Body;
+use http_body_util::BodyExt;
+use hyper_util::client::legacy::{connect::Connect, Client};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{
+    storage::CacheStorage,
+    tower::{CacheLayer, CacheService},
+    Config,
+};
+
+/// The error type for [`CachingClient`] and [`BufferBody`]
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+/// A service that buffers the response bodies of an inner service into [`Bytes`]
+#[derive(Debug, Clone)]
+pub struct BufferBody<S> {
+    inner: S,
+}
+
+impl<S> BufferBody<S> {
+    /// Buffers the response bodies of `inner`
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    /// The inner service
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for BufferBody<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+    ResBody: Body + Send + 'static,
+    ResBody::Data: Send,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<Bytes>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let res = self.inner.call(req);
+        Box::pin(async move {
+            let (parts, body) = res.await.map_err(Into::into)?.into_parts();
+            let body = body.collect().await.map_err(Into::into)?.to_bytes();
+            Ok(Response::from_parts(parts, body))
+        })
+    }
+}
+
+/// A [`hyper_util`](https://docs.rs/hyper-util) client that caches responses in a
+/// [`CacheStorage`]
+pub struct CachingClient<C, B, St> {
+    service: CacheService<BufferBody<Client<C, B>>, St>,
+}
+
+impl<C, B, St> CachingClient<C, B, St> {
+    /// Caches the responses of `client` in `store` with the default [`Config`]
+    pub fn new(client: Client<C, B>, store: Arc<St>) -> Self {
+        Self::with_config(client, store, Config::default())
+    }
+
+    /// Caches the responses of `client` in `store` evaluating them with `config`
+    pub fn with_config(client: Client<C, B>, store: Arc<St>, config: Config) -> Self {
+        let service = CacheLayer::new(store)
+            .config(config)
+            .layer(BufferBody::new(client));
+        Self { service }
+    }
+}
+
+impl<C, B, St> CachingClient<C, B, St>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+    B: Body + Default + Send + Unpin + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    St: CacheStorage<Bytes> + Send + Sync + 'static,
+{
+    /// Sends `req` unless it can be answered from the store
+    pub async fn request(&self, req: Request<B>) -> Result<Response<Bytes>, BoxError> {
+        let mut service = self.service.clone();
+        poll_fn(|cx| service.poll_ready(cx)).await?;
+        service.call(req).await
+    }
+}
+
+impl<C: Clone, B, St> Clone for CachingClient<C, B, St> {
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+        }
+    }
+}
+
+impl<C, B, St> fmt::Debug for CachingClient<C, B, St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachingClient").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,39 @@
+//! Helpers shared by the conversions for other HTTP crates
+
+#[cfg(any(feature = "http-types", feature = "isahc"))]
+use http::header::{IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE};
+use http::{HeaderMap, HeaderName, HeaderValue};
+
+#[cfg(any(feature = "actix-web", feature = "isahc"))]
+use crate::builder::BuildError;
+
+/// Converts the names and values of header fields from a crate with its own header types
+#[cfg(any(feature = "actix-web", feature = "isahc"))]
+pub(crate) fn header_map<'a>(
+    fields: impl Iterator<Item = (&'a str, &'a [u8])>,
+) -> Result<HeaderMap, BuildError> {
+    let mut converted = HeaderMap::with_capacity(fields.size_hint().0);
+    for (name, value) in fields {
+        converted.append(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_bytes(value)?,
+        );
+    }
+    Ok(converted)
+}
+
+/// The conditional headers of a revalidation request, with `None` for the ones it goes without
+///
+/// The policy drops validators that don't apply to the stored response e.g. a client's
+/// `If-Range`, so conversions that patch the original request have to remove those.
+#[cfg(any(feature = "http-types", feature = "isahc"))]
+pub(crate) fn validators(
+    headers: &HeaderMap,
+) -> impl Iterator<Item = (HeaderName, Option<&HeaderValue>)> {
+    [IF_NONE_MATCH, IF_MODIFIED_SINCE, IF_RANGE]
+        .into_iter()
+        .map(move |name| {
+            let value = headers.get(&name);
+            (name, value)
+        })
+}
@@ -0,0 +1,76 @@
+//! Conversions for [`isahc`](https://docs.rs/isahc) requests and responses
+//!
+//! `isahc` is built on the 0.2 release of the `http` crate, so its types are converted into the
+//! tuples that implement [`RequestLike`][crate::RequestLike] and
+//! [`ResponseLike`][crate::ResponseLike].
+//!
+//! ```no_run
+//! use http_cache_policy::{isahc as convert, BeforeRequest, CachePolicy};
+//!
+//! let get = || isahc::Request::get("https://example.com/").body(()).unwrap();
+//! let req = get();
+//! let res = isahc::send(get()).unwrap();
+//! let policy = CachePolicy::new(&convert::request(&req).unwrap(), &convert::response(&res).unwrap());
+//!
+//! let mut req = get();
+//! match policy.before_request(&convert::request(&req).unwrap(), http_cache_policy::now()) {
+//!     BeforeRequest::Fresh(_) => { /* serve the stored body */ }
+//!     BeforeRequest::Stale { request, .. } => {
+//!         convert::apply_revalidation_headers(&mut req, &request.headers);
+//!         let res = isahc::send(req);
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use http::{HeaderMap, Method, StatusCode, Uri};
+use isahc::http as http02;
+
+use crate::{
+    builder::BuildError,
+    interop::{header_map, validators},
+};
+
+/// Converts a request into the `(Uri, Method, HeaderMap)` tuple that implements `RequestLike`
+pub fn request<T>(req: &isahc::Request<T>) -> Result<(Uri, Method, HeaderMap), BuildError> {
+    let uri = req.uri().to_string().parse()?;
+    let method = Method::from_bytes(req.method().as_str().as_bytes())?;
+    Ok((uri, method, headers(req.headers())?))
+}
+
+/// Converts a response into the `(StatusCode, HeaderMap)` tuple that implements `ResponseLike`
+pub fn response<T>(res: &isahc::Response<T>) -> Result<(StatusCode, HeaderMap), BuildError> {
+    let status = StatusCode::from_u16(res.status().as_u16())?;
+    Ok((status, headers(res.headers())?))
+}
+
+/// Converts a header map e.g. from a request's or response's parts
+pub fn headers(headers: &http02::HeaderMap) -> Result<HeaderMap, BuildError> {
+    header_map(
+        headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes())),
+    )
+}
+
+/// Makes the conditional headers of `req` match
+/// [`BeforeRequest::Stale`][crate::BeforeRequest::Stale]'s request `headers`
+///
+/// Besides adding the stored response's validators this removes the ones that the policy left
+/// out, like a client's `If-Range` for its own copy, so the rest of `req` can be sent as-is.
+pub fn apply_revalidation_headers<T>(req: &mut isahc::Request<T>, headers: &HeaderMap) {
+    for (name, value) in validators(headers) {
+        let name = http02::header::HeaderName::from_bytes(name.as_str().as_bytes())
+            .expect("Standard header names are valid");
+        match value {
+            Some(value) => {
+                let value = http02::HeaderValue::from_bytes(value.as_bytes())
+                    .expect("Header values are valid in both versions of http");
+                req.headers_mut().insert(name, value);
+            }
+            None => {
+                req.headers_mut().remove(name);
+            }
+        }
+    }
+}
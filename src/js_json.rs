@@ -0,0 +1,251 @@
+//! Interop with the JSON format of the [`http-cache-semantics`](https://www.npmjs.com/package/http-cache-semantics)
+//! npm package
+//!
+//! The package persists policies through `toObject()`/`fromObject()` with abbreviated field names
+//! (`reqh`, `resh`, `t`, `sh`, ...). [`CachePolicy::to_js_json()`] and
+//! [`CachePolicy::from_js_json()`] map between that format and [`CachePolicy`], so entries can be
+//! shared or migrated between the two implementations.
+//!
+//! ```
+//! use http_cache_policy::CachePolicy;
+//! use serde_json::json;
+//!
+//! let policy = CachePolicy::from_js_json(&json!({
+//!     "v": 1,
+//!     "t": 1700000000000u64,
+//!     "sh": true,
+//!     "ch": 0.1,
+//!     "imm": 86400000,
+//!     "st": 200,
+//!     "resh": { "cache-control": "max-age=300", "etag": "\"a\"" },
+//!     "rescc": { "max-age": "300" },
+//!     "m": "GET",
+//!     "u": "/a",
+//!     "h": "example.com",
+//!     "a": true,
+//!     "reqh": null,
+//!     "reqcc": {}
+//! }))
+//! .unwrap();
+//! assert!(policy.is_storable());
+//! assert_eq!(policy.to_js_json()["resh"]["etag"], "\"a\"");
+//! ```
+//!
+//! The package only keeps the request URL and `Host` header, so a policy for an origin-form URL
+//! like `/a` keeps that as its URI. The package's `immutableMinTimeToLive` (`imm`) has no
+//! equivalent here, so it's written with the package's default and ignored when reading, and the
+//! parsed `Cache-Control` fields (`rescc`, `reqcc`) are recomputed from the headers.
+
+use std::{
+    error::Error,
+    fmt,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use http::{
+    header::{AUTHORIZATION, HOST, SET_COOKIE},
+    HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri,
+};
+use serde_json::{Map, Value};
+
+use crate::{
+    config::{LastModifiedHeuristic, Mode},
+    CachePolicy, Config, DirectiveSet,
+};
+
+/// The version of the format that's written and understood
+const VERSION: u64 = 1;
+
+/// The package's default `immutableMinTimeToLive` of a day, in milliseconds
+const IMMUTABLE_MIN_TTL_MS: u64 = 24 * 3600 * 1000;
+
+impl CachePolicy {
+    /// Converts the policy into the format of `http-cache-semantics`' `toObject()`
+    ///
+    /// Headers with several values are joined with `, ` except for `Set-Cookie` which is written
+    /// as an array, like Node.js does
+    pub fn to_js_json(&self) -> Value {
+        let response_time = self
+            .response_time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let host = self
+            .req
+            .get(HOST)
+            .and_then(|host| host.to_str().ok())
+            .map(str::to_owned)
+            .or_else(|| self.uri.authority().map(|authority| authority.to_string()));
+        // Going through the shortest representation keeps e.g. `0.1` from becoming
+        // `0.10000000149011612`
+        let ratio = f32::from(self.config.last_modified)
+            .to_string()
+            .parse::<f64>()
+            .unwrap_or_default();
+
+        let mut object = Map::new();
+        object.insert("v".into(), VERSION.into());
+        object.insert("t".into(), (response_time.as_millis() as u64).into());
+        object.insert("sh".into(), self.config.mode.is_shared().into());
+        object.insert("ch".into(), ratio.into());
+        object.insert("imm".into(), IMMUTABLE_MIN_TTL_MS.into());
+        object.insert("st".into(), self.status.as_u16().into());
+        object.insert("resh".into(), headers_to_js(&self.res));
+        object.insert("rescc".into(), directives_to_js(&self.res_cc));
+        object.insert("m".into(), self.method.as_str().into());
+        object.insert("u".into(), self.uri.to_string().into());
+        object.insert("h".into(), host.into());
+        object.insert("a".into(), (!self.req.contains_key(AUTHORIZATION)).into());
+        object.insert("reqh".into(), headers_to_js(&self.req));
+        object.insert("reqcc".into(), directives_to_js(&self.req_cc));
+        Value::Object(object)
+    }
+
+    /// Reads a policy in the format of `http-cache-semantics`' `toObject()`
+    ///
+    /// The policy uses the default [`Config`] besides the mode (`sh`) and the last-modified
+    /// heuristic (`ch`)
+    pub fn from_js_json(value: &Value) -> Result<Self, JsJsonError> {
+        let field = |name: &'static str| value.get(name).ok_or(JsJsonError::MissingField(name));
+
+        let version = value.get("v").and_then(Value::as_u64);
+        if version != Some(VERSION) {
+            return Err(JsJsonError::UnsupportedVersion(version));
+        }
+
+        let millis = field("t")?
+            .as_f64()
+            .filter(|millis| *millis >= 0.0)
+            .ok_or(JsJsonError::InvalidField("t"))?;
+        let response_time = UNIX_EPOCH + Duration::from_millis(millis as u64);
+        let mode = match field("sh")?.as_bool() {
+            Some(true) => Mode::Shared,
+            Some(false) => Mode::Private,
+            None => return Err(JsJsonError::InvalidField("sh")),
+        };
+        let last_modified = match value.get("ch").and_then(Value::as_f64) {
+            Some(ratio) => {
+                LastModifiedHeuristic::new(ratio as f32).ok_or(JsJsonError::InvalidField("ch"))?
+            }
+            None => LastModifiedHeuristic::default(),
+        };
+        let status = field("st")?
+            .as_u64()
+            .and_then(|status| u16::try_from(status).ok())
+            .ok_or(JsJsonError::InvalidField("st"))?;
+        let status = StatusCode::from_u16(status).map_err(invalid("st"))?;
+        let method = field("m")?.as_str().ok_or(JsJsonError::InvalidField("m"))?;
+        let method = Method::from_bytes(method.as_bytes()).map_err(invalid("m"))?;
+        let uri = field("u")?.as_str().ok_or(JsJsonError::InvalidField("u"))?;
+        let uri: Uri = uri.parse().map_err(invalid("u"))?;
+
+        let res = headers_from_js(field("resh")?).ok_or(JsJsonError::InvalidField("resh"))?;
+        let mut req = match value.get("reqh") {
+            None | Some(Value::Null) => HeaderMap::new(),
+            Some(headers) => headers_from_js(headers).ok_or(JsJsonError::InvalidField("reqh"))?,
+        };
+        if let Some(host) = value.get("h").and_then(Value::as_str) {
+            if !req.contains_key(HOST) {
+                req.insert(HOST, HeaderValue::from_str(host).map_err(invalid("h"))?);
+            }
+        }
+
+        let config = Config {
+            mode,
+            last_modified,
+            ..Config::default()
+        };
+        Ok(Self::from_details(
+            uri,
+            method,
+            status,
+            req,
+            res,
+            response_time,
+            config,
+        ))
+    }
+}
+
+fn invalid<E>(name: &'static str) -> impl FnOnce(E) -> JsJsonError {
+    move |_| JsJsonError::InvalidField(name)
+}
+
+pub(crate) fn headers_to_js(headers: &HeaderMap) -> Value {
+    let mut object = Map::new();
+    for name in headers.keys() {
+        let values = headers
+            .get_all(name)
+            .iter()
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+        let value = if name == SET_COOKIE {
+            Value::Array(values.map(Value::String).collect())
+        } else {
+            Value::String(values.collect::<Vec<_>>().join(", "))
+        };
+        object.insert(name.as_str().to_owned(), value);
+    }
+    Value::Object(object)
+}
+
+fn headers_from_js(value: &Value) -> Option<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in value.as_object()? {
+        let name = HeaderName::from_bytes(name.as_bytes()).ok()?;
+        match value {
+            Value::String(value) => {
+                headers.append(&name, HeaderValue::from_str(value).ok()?);
+            }
+            Value::Array(values) => {
+                for value in values {
+                    headers.append(&name, HeaderValue::from_str(value.as_str()?).ok()?);
+                }
+            }
+            Value::Number(value) => {
+                headers.append(&name, HeaderValue::from_str(&value.to_string()).ok()?);
+            }
+            _ => return None,
+        }
+    }
+    Some(headers)
+}
+
+fn directives_to_js(directives: &DirectiveSet) -> Value {
+    let object = directives
+        .iter()
+        .map(|(name, value)| {
+            let value = match value.to_text() {
+                Some(value) => Value::String(value.to_string()),
+                None => Value::Bool(true),
+            };
+            (name.to_string(), value)
+        })
+        .collect();
+    Value::Object(object)
+}
+
+/// A policy in the `http-cache-semantics` format that couldn't be read
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JsJsonError {
+    /// The format version (`v`) isn't supported
+    UnsupportedVersion(Option<u64>),
+    /// A required field is missing
+    MissingField(&'static str),
+    /// A field has an invalid value
+    InvalidField(&'static str),
+}
+
+impl fmt::Display for JsJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(Some(version)) => {
+                write!(f, "unsupported format version {}", version)
+            }
+            Self::UnsupportedVersion(None) => f.write_str("missing format version"),
+            Self::MissingField(name) => write!(f, "missing field `{}`", name),
+            Self::InvalidField(name) => write!(f, "invalid field `{}`", name),
+        }
+    }
+}
+
+impl Error for JsJsonError {}
@@ -5,36 +5,93 @@
 
 use http::{
     header::{
-        ACCEPT_RANGES, AGE, AUTHORIZATION, CACHE_CONTROL, CONNECTION, DATE, ETAG, EXPIRES, HOST,
-        IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, IF_UNMODIFIED_SINCE, LAST_MODIFIED,
-        PRAGMA, SET_COOKIE, VARY, WARNING,
+        ACCEPT_RANGES, AGE, AUTHORIZATION, CACHE_CONTROL, CONNECTION, CONTENT_LENGTH,
+        CONTENT_LOCATION, DATE, ETAG, EXPIRES, HOST, IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        IF_RANGE, IF_UNMODIFIED_SINCE, LAST_MODIFIED, LOCATION, PRAGMA, RANGE, SET_COOKIE, VARY,
+        WARNING,
     },
     HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode, Uri,
 };
 use std::{
-    collections::{hash_map::Entry, HashMap},
-    time::{Duration, SystemTime},
+    borrow::Cow,
+    error, fmt,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+#[cfg(feature = "actix-web")]
+#[cfg_attr(docsrs, doc(cfg(feature = "actix-web")))]
+pub mod actix_web;
+pub mod borrowed;
+pub mod builder;
+pub mod cache_control;
+pub mod compact;
 /// TODO
 pub mod config;
-
+#[cfg(feature = "curl")]
+#[cfg_attr(docsrs, doc(cfg(feature = "curl")))]
+pub mod curl;
+mod directive_set;
+pub mod directives;
+mod entity_tag;
+#[cfg(feature = "http-types")]
+#[cfg_attr(docsrs, doc(cfg(feature = "http-types")))]
+pub mod http_types;
+#[cfg(feature = "hyper")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hyper")))]
+pub mod hyper;
+#[cfg(any(feature = "actix-web", feature = "http-types", feature = "isahc"))]
+mod interop;
+#[cfg(feature = "isahc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "isahc")))]
+pub mod isahc;
+#[cfg(feature = "js-json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "js-json")))]
+pub mod js_json;
+pub mod observer;
+pub mod overrides;
+pub mod range;
+#[cfg(feature = "reqwest-middleware")]
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest-middleware")))]
+pub mod reqwest_middleware;
+#[cfg(feature = "serde")]
+mod serde_parts;
+#[cfg(feature = "serde")]
+mod serde_policy;
+#[cfg(feature = "server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "server")))]
+pub mod server;
+pub mod storage;
+pub mod strict;
+#[cfg(feature = "tower")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+pub mod tower;
+#[cfg(feature = "ureq")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ureq")))]
+pub mod ureq;
+#[cfg(feature = "url")]
+#[cfg_attr(docsrs, doc(cfg(feature = "url")))]
+pub mod url;
+pub mod warning;
+#[cfg(feature = "wasm-bindgen")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm-bindgen")))]
+pub mod wasm;
+
+pub use builder::CachePolicyBuilder;
 pub use config::Config;
+use config::{Mode, OversizedHeaders, StoredRequestHeaders};
+use directive_set::DirectiveSet;
+use directives::{RequestDirectives, ResponseDirectives};
+use entity_tag::{dedup, entity_tags, EntityTag, EntityTags};
+use observer::Decision;
 
 /// Simply a convenience function for `SystemTime::now()`
+///
+/// This panics on `wasm32-unknown-unknown` where there's no clock
 pub fn now() -> SystemTime {
     SystemTime::now()
 }
 
-// rfc7231 6.1
-const STATUS_CODE_CACHEABLE_BY_DEFAULT: &[u16] =
-    &[200, 203, 204, 206, 300, 301, 308, 404, 405, 410, 414, 501];
-
-// This implementation does not understand partial responses (206)
-const UNDERSTOOD_STATUSES: &[u16] = &[
-    200, 203, 204, 300, 301, 302, 303, 307, 308, 404, 405, 410, 414, 501,
-];
-
 const HOP_BY_HOP_HEADERS: &[&str] = &[
     "date", // included, because we add Age update Date
     "connection",
@@ -55,92 +112,491 @@ const EXCLUDED_FROM_REVALIDATION_UPDATE: &[&str] = &[
     "content-range",
 ];
 
-type CacheControl = HashMap<Box<str>, Option<Box<str>>>;
+/// Stands in for `Authorization` values that aren't stored, see [`StoredRequestHeaders::Vary`]
+const REDACTED_AUTHORIZATION: HeaderValue = HeaderValue::from_static("[redacted]");
+
+/// Request headers that decisions depend on regardless of `Vary`
+const MINIMIZED_REQUEST_HEADERS: &[&str] = &["host", "authorization", "cache-control", "pragma"];
+
+/// Response headers that decisions depend on
+const MINIMIZED_RESPONSE_HEADERS: &[&str] = &[
+    "cache-control",
+    "pragma",
+    "vary",
+    "etag",
+    "last-modified",
+    "date",
+    "expires",
+    "age",
+    "set-cookie",
+    "accept-ranges",
+    "content-length",
+    "cache-tag",
+    "surrogate-key",
+    "xkey",
+];
 
-fn parse_cache_control<'a>(headers: impl IntoIterator<Item = &'a HeaderValue>) -> CacheControl {
-    let mut cc = CacheControl::new();
-    let mut is_valid = true;
+/// Keeps the header values that pass `keep`
+///
+/// Headers that are shared with other policies are only copied when some are dropped
+fn retain_headers(headers: &mut Arc<HeaderMap>, keep: impl Fn(&HeaderName, &HeaderValue) -> bool) {
+    if headers.iter().all(|(name, value)| keep(name, value)) {
+        return;
+    }
+    retain_headers_in(Arc::make_mut(headers), keep);
+}
 
-    for h in headers.into_iter().filter_map(|v| v.to_str().ok()) {
-        for part in h.split(',') {
-            // TODO: lame parsing
-            if part.trim().is_empty() {
-                continue;
-            }
-            let mut kv = part.splitn(2, '=');
-            let k = kv.next().unwrap().trim();
-            if k.is_empty() {
-                continue;
-            }
-            let v = kv.next().map(str::trim);
-            match cc.entry(k.into()) {
-                Entry::Occupied(e) => {
-                    // When there is more than one value present for a given directive (e.g., two Expires header fields, multiple Cache-Control: max-age directives),
-                    // the directive's value is considered invalid. Caches are encouraged to consider responses that have invalid freshness information to be stale
-                    if e.get().as_deref() != v {
-                        is_valid = false;
-                    }
-                }
-                Entry::Vacant(e) => {
-                    e.insert(v.map(|v| v.trim_matches('"')).map(From::from)); // TODO: bad unquoting
-                }
-            }
+/// Keeps the header values that pass `keep`, in place
+fn retain_headers_in(headers: &mut HeaderMap, keep: impl Fn(&HeaderName, &HeaderValue) -> bool) {
+    let mut name = None;
+    for (next_name, value) in std::mem::take(headers) {
+        if next_name.is_some() {
+            name = next_name;
+        }
+        let name = name.as_ref().expect("The first entry always has a name");
+        if keep(name, &value) {
+            headers.append(name.clone(), value);
         }
     }
-    if !is_valid {
-        cc.insert("must-revalidate".into(), None);
+}
+
+/// The deduplicated request header names nominated by `Vary`
+fn parse_vary(headers: &HeaderMap) -> Arc<[HeaderName]> {
+    let mut names = Vec::new();
+    for name in get_all_comma(headers.get_all(VARY)) {
+        if let Ok(name) = HeaderName::from_bytes(name.trim().as_bytes()) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
     }
-    cc
+    names.into()
 }
 
-fn format_cache_control(cc: &CacheControl) -> String {
-    let mut out = String::new();
-    for (k, v) in cc {
-        if !out.is_empty() {
-            out.push_str(", ");
+/// Applies the config's rules to the stored response headers, which are only copied when
+/// something changes
+///
+/// Returns the headers along with the directives that the policy acts on
+fn prepare_response<'h>(
+    mut res: Cow<'h, HeaderMap>,
+    config: &Config,
+) -> (Cow<'h, HeaderMap>, DirectiveSet) {
+    for name in config.strip_response_headers.iter() {
+        if res.contains_key(name) {
+            res.to_mut().remove(name);
         }
-        out.push_str(k);
-        if let Some(v) = v {
-            out.push('=');
-            let needs_quote =
-                v.is_empty() || v.as_bytes().iter().any(|b| !b.is_ascii_alphanumeric());
-            if needs_quote {
-                out.push('"');
-            }
-            out.push_str(v);
-            if needs_quote {
-                out.push('"');
-            }
+    }
+    if config.oversized_headers == OversizedHeaders::Drop
+        && res
+            .iter()
+            .any(|(name, value)| config.is_oversized(name, value))
+    {
+        retain_headers_in(res.to_mut(), |name, value| {
+            !config.is_oversized(name, value)
+        });
+    }
+
+    let mut res_cc = parse_cache_control(res.get_all(CACHE_CONTROL));
+
+    // Assume that if someone uses legacy, non-standard uncecessary options they don't understand caching,
+    // so there's no point stricly adhering to the blindly copy&pasted directives.
+    if config.ignore_cargo_cult
+        && res_cc.contains_key("pre-check")
+        && res_cc.contains_key("post-check")
+    {
+        const CARGO_CULT: [&str; 5] = [
+            "pre-check",
+            "post-check",
+            "no-cache",
+            "no-store",
+            "must-revalidate",
+        ];
+        for name in CARGO_CULT {
+            res_cc.remove(name);
+        }
+        // Only the offending directives are cut out of the original text, so everything else
+        // e.g. extension directives survives as it was sent
+        let is_cargo_cult = |name: &str| CARGO_CULT.iter().any(|cc| name.eq_ignore_ascii_case(cc));
+        let values: Vec<_> = res
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| match value.to_str() {
+                Ok(value) => {
+                    let kept = cache_control::remove_members(value, is_cargo_cult);
+                    (!kept.is_empty()).then(|| text_value(kept))
+                }
+                Err(_) => Some(value.clone()),
+            })
+            .collect();
+        let res = res.to_mut();
+        res.remove(CACHE_CONTROL);
+        if values.is_empty() {
+            res.insert(CACHE_CONTROL, HeaderValue::from_static(""));
+        }
+        for value in values {
+            res.append(CACHE_CONTROL, value);
         }
+        res.remove(EXPIRES);
+        res.remove(PRAGMA);
     }
-    out
+
+    // When the Cache-Control header field is not present in a request, caches MUST consider the no-cache request pragma-directive
+    // as having the same effect as if "Cache-Control: no-cache" were present (see Section 5.2.1).
+    if !res.contains_key(CACHE_CONTROL)
+        && res
+            .get_str(&PRAGMA)
+            .map_or(false, |p| p.contains("no-cache"))
+    {
+        res_cc.insert("no-cache", None);
+    }
+
+    (res, res_cc)
 }
 
-/// TODO
+fn parse_cache_control<'a>(headers: impl IntoIterator<Item = &'a HeaderValue>) -> DirectiveSet {
+    let mut cc = DirectiveSet::new();
+    let mut is_valid = true;
+
+    for value in headers {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        cache_control::parse_directives(value, |k, v| match cc.get(k) {
+            Some(existing) => {
+                // When there is more than one value present for a given directive (e.g., two Expires header fields, multiple Cache-Control: max-age directives),
+                // the directive's value is considered invalid. Caches are encouraged to consider responses that have invalid freshness information to be stale
+                if existing.to_text().as_deref() != v {
+                    is_valid = false;
+                }
+            }
+            None => cc.insert(k, v),
+        });
+    }
+    if !is_valid {
+        cc.insert("must-revalidate", None);
+    }
+    cc
+}
+
+/// The caching policy for a stored response
+///
+/// Cloning a policy is cheap since clones share the stored request and response headers, which
+/// are only copied once a clone drops some e.g. with [`minimize()`][Self::minimize]. This also
+/// goes for the policies from [`with_options()`][Self::with_options].
+///
+/// # Serialization
+///
+/// With the `serde` feature a policy can be persisted in any self-describing serde format.
+/// Binary formats like CBOR and MessagePack (both with and without field names) keep header
+/// values as byte strings, so every value round-trips. Human-readable formats like JSON keep them
+/// as strings, which drops any values that aren't valid UTF-8. See [`compact`] for a smaller
+/// encoding with a stable layout, and [`Config::stored_request_headers`] for keeping cookies and
+/// credentials out of persisted policies.
+///
+/// Serialized policies carry a format version, and policies written by older releases (including
+/// ones from before the version was added) can still be deserialized. Policies written by a newer
+/// release with a format version that isn't known yet fail to deserialize instead of being
+/// misread.
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CachePolicy {
-    #[cfg_attr(feature = "serde", serde(with = "http_serde::header_map"))]
-    req: HeaderMap,
-    #[cfg_attr(feature = "serde", serde(with = "http_serde::header_map"))]
-    res: HeaderMap,
-    #[cfg_attr(feature = "serde", serde(with = "http_serde::uri"))]
+    req: Arc<HeaderMap>,
+    res: Arc<HeaderMap>,
     uri: Uri,
-    #[cfg_attr(feature = "serde", serde(with = "http_serde::status_code"))]
     status: StatusCode,
-    #[cfg_attr(feature = "serde", serde(with = "http_serde::method"))]
     method: Method,
     config: Config,
-    res_cc: CacheControl,
-    req_cc: CacheControl,
+    res_cc: DirectiveSet,
+    req_cc: DirectiveSet,
+    /// Parsed from the response's `Vary` so that matching requests doesn't allocate
+    vary: Arc<[HeaderName]>,
     response_time: SystemTime,
+    body_digest: Option<Box<[u8]>>,
+    derived: Derived,
+}
+
+/// The state that only depends on the stored request and response
+///
+/// This is computed once when the policy is created, so evaluating requests against the policy
+/// comes down to some arithmetic
+#[derive(Debug, Clone, Copy)]
+struct Derived {
+    storability: Storability,
+    max_age: Duration,
+    freshness_source: FreshnessSource,
+    age_header: Duration,
+    dates: Dates,
+}
+
+impl Derived {
+    /// A placeholder until [`CachePolicy::with_derived()`] fills it in
+    const PENDING: Self = Self {
+        storability: Storability::Storable,
+        max_age: Duration::ZERO,
+        freshness_source: FreshnessSource::Unknown,
+        age_header: Duration::ZERO,
+        dates: Dates::NONE,
+    };
+}
+
+/// The stored response's dates, which are parsed once
+#[derive(Debug, Clone, Copy)]
+struct Dates {
+    /// `Date` if it's valid
+    date: Option<SystemTime>,
+    /// `Expires`, where `Some(None)` is an invalid date
+    expires: Option<Option<SystemTime>>,
+    /// `Last-Modified` if it's valid
+    last_modified: Option<SystemTime>,
+}
+
+impl Dates {
+    const NONE: Self = Self {
+        date: None,
+        expires: None,
+        last_modified: None,
+    };
+
+    fn parse(res: &HeaderMap) -> Self {
+        Self {
+            date: date_header(res, &DATE).flatten(),
+            expires: date_header(res, &EXPIRES),
+            last_modified: date_header(res, &LAST_MODIFIED).flatten(),
+        }
+    }
+}
+
+/// What storability and freshness are computed from
+///
+/// This borrows from either a [`CachePolicy`] or a [`borrowed::CachePolicyRef`], so both follow
+/// the same rules
+#[derive(Clone, Copy)]
+struct View<'a> {
+    req: &'a HeaderMap,
+    res: &'a HeaderMap,
+    status: StatusCode,
+    method: &'a Method,
+    config: &'a Config,
+    res_cc: &'a DirectiveSet,
+    req_cc: &'a DirectiveSet,
+    body_digest: Option<&'a [u8]>,
+    response_time: SystemTime,
+    dates: Dates,
+}
+
+impl View<'_> {
+    fn storability(&self, mode: Mode) -> Storability {
+        // The "no-store" request directive indicates that a cache MUST NOT store any part of either this request or any response to it.
+        if self.req_cc.contains_key("no-store") {
+            return Storability::RequestNoStore;
+        }
+        // A cache MUST NOT store a response to any request, unless:
+        // The request method is understood by the cache and defined as being cacheable, and
+        let is_query = *self.method == "QUERY";
+        if is_query && self.config.cache_query {
+            // QUERY responses are keyed on the request content, so the body digest is required
+            if self.body_digest.is_none() {
+                return Storability::MissingBodyDigest;
+            }
+        } else if !self.config.cacheable_methods.contains(self.method) {
+            return Storability::UncacheableMethod;
+        } else if self.config.body_digest_methods.contains(self.method)
+            && self.body_digest.is_none()
+        {
+            return Storability::MissingBodyDigest;
+        } else if Method::GET != *self.method
+            && Method::HEAD != *self.method
+            && !self.has_explicit_expiration(mode)
+        {
+            return Storability::NoExplicitExpiration;
+        }
+        // the response status code is understood by the cache, and
+        if !self
+            .config
+            .understood_statuses
+            .contains(self.status.as_u16())
+            && self.config.negative_ttl(self.status).is_none()
+        {
+            return Storability::StatusNotUnderstood;
+        }
+        // the "no-store" cache directive does not appear in request or response header fields, and
+        if self.res_cc.contains_key("no-store") {
+            return Storability::ResponseNoStore;
+        }
+        // the "private" response directive does not appear in the response, if the cache is shared, and
+        if mode.is_shared() && self.res_cc.contains_key("private") {
+            return Storability::Private;
+        }
+        // the Authorization header field does not appear in the request, if the cache is shared,
+        if mode.is_shared()
+            && self.req.contains_key(AUTHORIZATION)
+            && !self.allows_storing_authenticated()
+        {
+            return Storability::Authorization;
+        }
+        // Fields over the size limit are only left behind when they make the response unstorable
+        if self
+            .res
+            .iter()
+            .any(|(name, value)| self.config.is_oversized(name, value))
+        {
+            return Storability::HeadersTooLarge;
+        }
+        // the response either:
+        // contains an Expires header field, or
+        let allows_storing = self.res.contains_key(EXPIRES) ||
+            // contains a max-age response directive, or
+            // contains a s-maxage response directive and the cache is shared, or
+            // contains a public response directive.
+            self.res_cc.contains_key("max-age") ||
+            (mode.is_shared() && self.res_cc.contains_key("s-maxage")) ||
+            self.res_cc.contains_key("public") ||
+            // has a status code that is defined as cacheable by default
+            self.config.heuristic_statuses.contains(self.status.as_u16()) ||
+            // or has a negative caching lifetime
+            self.config.negative_ttl(self.status).is_some();
+        if !allows_storing {
+            return Storability::NoExplicitExpiration;
+        }
+
+        Storability::Storable
+    }
+
+    fn has_explicit_expiration(&self, mode: Mode) -> bool {
+        // 4.2.1 Calculating Freshness Lifetime
+        (mode.is_shared() && self.res_cc.contains_key("s-maxage"))
+            || self.res_cc.contains_key("max-age")
+            || self.res.contains_key(EXPIRES)
+    }
+
+    fn allows_storing_authenticated(&self) -> bool {
+        if self.config.never_cache_authorized {
+            return false;
+        }
+        //  following Cache-Control response directives (Section 5.2.2) have such an effect: must-revalidate, public, and s-maxage.
+        self.res_cc.contains_key("must-revalidate")
+            || self.res_cc.contains_key("public")
+            || self.res_cc.contains_key("s-maxage")
+    }
+
+    fn age_header_value(&self) -> Duration {
+        Duration::from_secs(
+            self.res
+                .get_str(&AGE)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+        )
+    }
+
+    fn server_date(&self) -> SystemTime {
+        self.dates.date.unwrap_or(self.response_time)
+    }
+
+    fn max_age_with_source(&self, mode: Mode) -> (Duration, FreshnessSource) {
+        let zero = Duration::from_secs(0);
+        if !self.storability(mode).is_storable() {
+            return (zero, FreshnessSource::NotStorable);
+        }
+        if self.res_cc.contains_key("no-cache") {
+            return (zero, FreshnessSource::NoCache);
+        }
+
+        // Shared responses with cookies are cacheable according to the RFC, but IMHO it'd be unwise to do so by default
+        // so this implementation requires explicit opt-in via public header
+        if mode.is_shared()
+            && self.res.contains_key(SET_COOKIE)
+            && !self.res_cc.contains_key("public")
+            && !self.res_cc.contains_key("immutable")
+        {
+            return (zero, FreshnessSource::SetCookie);
+        }
+
+        if self.res.get_str(&VARY).map(str::trim) == Some("*") {
+            return (zero, FreshnessSource::VaryStar);
+        }
+
+        if mode.is_shared() && self.res_cc.contains_key("proxy-revalidate") {
+            return (zero, FreshnessSource::ProxyRevalidate);
+        }
+
+        let (max_age, source) = self.response_max_age(mode);
+        let max_age = self.config.min_ttl.map_or(max_age, |min| max_age.max(min));
+        let max_age = self
+            .config
+            .status_ttl_override(self.status)
+            .map_or(max_age, |ttl| ttl.apply(max_age));
+        let max_age = self.config.max_ttl.map_or(max_age, |max| max_age.min(max));
+        (max_age, source)
+    }
+
+    /// The freshness lifetime as described by the response itself
+    fn response_max_age(&self, mode: Mode) -> (Duration, FreshnessSource) {
+        if mode.is_shared() {
+            // if a response includes the s-maxage directive, a shared cache recipient MUST ignore the Expires field.
+            if let Some(s_max) = ResponseDirectives::new(self.res_cc).s_maxage() {
+                return (s_max, FreshnessSource::SMaxAge);
+            }
+        }
+
+        // If a response includes a Cache-Control field with the max-age directive, a recipient MUST ignore the Expires field.
+        if let Some(max_age) = ResponseDirectives::new(self.res_cc).max_age() {
+            return (max_age, FreshnessSource::MaxAge);
+        }
+
+        let default_min_ttl = Duration::from_secs(0);
+
+        let server_date = self.server_date();
+        if let Some(expires) = self.dates.expires {
+            let expires = match expires {
+                // A cache recipient MUST interpret invalid date formats, especially the value "0", as representing a time in the past (i.e., "already expired").
+                None => Duration::from_secs(0),
+                Some(expires) => {
+                    default_min_ttl.max(expires.duration_since(server_date).unwrap_or_default())
+                }
+            };
+            return (expires, FreshnessSource::Expires);
+        }
+
+        if let Some(ttl) = self.config.negative_ttl(self.status) {
+            return (ttl, FreshnessSource::NegativeTtl);
+        }
+
+        if let Some(last_modified) = self.dates.last_modified {
+            if let Ok(diff) = server_date.duration_since(last_modified) {
+                let secs_left =
+                    diff.as_secs() as f64 * f64::from(f32::from(self.config.last_modified));
+                let heuristic = default_min_ttl.max(Duration::from_secs(secs_left as _));
+                return (heuristic, FreshnessSource::LastModifiedHeuristic);
+            }
+        }
+
+        match self.config.default_ttl {
+            Some(ttl) => (ttl, FreshnessSource::DefaultTtl),
+            None => (default_min_ttl, FreshnessSource::Unknown),
+        }
+    }
 }
 
 impl CachePolicy {
-    /// TODO
+    /// Evaluates `res` to `req`, received just now
+    ///
+    /// This reads the clock, see [`new_at()`][Self::new_at] for platforms without one
     #[inline]
     pub fn new<Req: RequestLike, Res: ResponseLike>(req: &Req, res: &Res) -> Self {
-        Self::with_config(req, res, SystemTime::now(), Default::default())
+        Self::new_at(req, res, SystemTime::now())
+    }
+
+    /// Like [`new()`][Self::new], but with an explicit time for when the response was received
+    ///
+    /// This avoids reading the clock which isn't available on every platform (e.g.
+    /// `wasm32-unknown-unknown`) and makes tests deterministic
+    #[inline]
+    pub fn new_at<Req: RequestLike, Res: ResponseLike>(
+        req: &Req,
+        res: &Res,
+        response_time: SystemTime,
+    ) -> Self {
+        Self::with_config(req, res, response_time, Default::default())
     }
 
     /// TODO
@@ -150,57 +606,143 @@ impl CachePolicy {
         res: &Res,
         response_time: SystemTime,
         config: Config,
+    ) -> Self {
+        Self::unobserved(req, res, response_time, config).evaluated()
+    }
+
+    /// Like [`with_config()`][Self::with_config], but takes ownership of the request and response
+    /// so that their headers are moved instead of copied
+    pub fn from_owned_parts(
+        req: http::request::Parts,
+        res: http::response::Parts,
+        response_time: SystemTime,
+        config: Config,
+    ) -> Self {
+        let mut res = res;
+        if let Some(date) = received_date(&res.headers, response_time) {
+            res.headers.insert(DATE, date);
+        }
+        Self::from_details(
+            req.uri,
+            req.method,
+            res.status,
+            req.headers,
+            res.headers,
+            response_time,
+            config,
+        )
+        .evaluated()
+    }
+
+    /// Like [`with_config()`][Self::with_config] without reporting to the observer
+    fn unobserved<Req: RequestLike, Res: ResponseLike>(
+        req: &Req,
+        res: &Res,
+        response_time: SystemTime,
+        config: Config,
     ) -> Self {
         let uri = req.uri();
         let status = res.status();
         let method = req.method().clone();
-        let res = res.headers().clone();
+        let body_digest = req.body_digest().map(Box::from);
+        let mut res = res.headers().clone();
+        if let Some(date) = received_date(&res, response_time) {
+            res.insert(DATE, date);
+        }
         let req = req.headers().clone();
         Self::from_details(uri, method, status, req, res, response_time, config)
+            .with_body_digest(body_digest)
+    }
+
+    /// Reports the storability of a newly created policy to the observer
+    fn evaluated(self) -> Self {
+        self.observe(Decision::Evaluated(self.storability()));
+        self
+    }
+
+    fn observe(&self, decision: Decision) {
+        if let Some(observer) = &self.config.observer {
+            observer.observe(self, decision);
+        }
+    }
+
+    fn with_body_digest(self, body_digest: Option<Box<[u8]>>) -> Self {
+        // Storability depends on the digest, so the derived state is only stale when there is one
+        let is_stale = body_digest.is_some();
+        let policy = Self {
+            body_digest,
+            ..self
+        };
+        if is_stale {
+            policy.with_derived()
+        } else {
+            policy
+        }
+    }
+
+    /// Computes the [`Derived`] state from the stored request and response
+    fn with_derived(mut self) -> Self {
+        let mode = self.config.mode;
+        // The dates go first since the freshness depends on them
+        self.derived.dates = Dates::parse(&self.res);
+        let view = self.view();
+        let (max_age, freshness_source) = view.max_age_with_source(mode);
+        self.derived = Derived {
+            storability: view.storability(mode),
+            max_age,
+            freshness_source,
+            age_header: view.age_header_value(),
+            dates: self.derived.dates,
+        };
+        self
+    }
+
+    fn view(&self) -> View<'_> {
+        View {
+            req: &self.req,
+            res: &self.res,
+            status: self.status,
+            method: &self.method,
+            config: &self.config,
+            res_cc: &self.res_cc,
+            req_cc: &self.req_cc,
+            body_digest: self.body_digest.as_deref(),
+            response_time: self.response_time,
+            dates: self.derived.dates,
+        }
     }
 
     fn from_details(
         uri: Uri,
         method: Method,
         status: StatusCode,
-        req: HeaderMap,
-        mut res: HeaderMap,
+        req: impl Into<Arc<HeaderMap>>,
+        res: impl Into<Arc<HeaderMap>>,
         response_time: SystemTime,
         config: Config,
     ) -> Self {
-        let mut res_cc = parse_cache_control(res.get_all("cache-control"));
-        let req_cc = parse_cache_control(req.get_all("cache-control"));
-
-        // Assume that if someone uses legacy, non-standard uncecessary options they don't understand caching,
-        // so there's no point stricly adhering to the blindly copy&pasted directives.
-        if config.ignore_cargo_cult
-            && res_cc.contains_key("pre-check")
-            && res_cc.contains_key("post-check")
-        {
-            res_cc.remove("pre-check");
-            res_cc.remove("post-check");
-            res_cc.remove("no-cache");
-            res_cc.remove("no-store");
-            res_cc.remove("must-revalidate");
-            res.insert(
-                CACHE_CONTROL,
-                HeaderValue::from_str(&format_cache_control(&res_cc)).unwrap(),
-            );
-            res.remove(EXPIRES);
-            res.remove(PRAGMA);
-        }
-
-        // When the Cache-Control header field is not present in a request, caches MUST consider the no-cache request pragma-directive
-        // as having the same effect as if "Cache-Control: no-cache" were present (see Section 5.2.1).
-        if !res.contains_key(CACHE_CONTROL)
-            && res
-                .get_str(&PRAGMA)
-                .map_or(false, |p| p.contains("no-cache"))
-        {
-            res_cc.insert("no-cache".into(), None);
-        }
+        let (req, res) = (req.into(), res.into());
+        let config = config.for_uri(&uri).unwrap_or(config);
+        let uri = config.strip_ignored_query_params(uri);
+        let (res, res_cc) = match Arc::try_unwrap(res) {
+            // Headers that aren't shared yet are updated in place
+            Ok(res) => {
+                let (res, res_cc) = prepare_response(Cow::Owned(res), &config);
+                (Arc::new(res.into_owned()), res_cc)
+            }
+            Err(res) => {
+                let (prepared, res_cc) = prepare_response(Cow::Borrowed(&res), &config);
+                let prepared = match prepared {
+                    Cow::Owned(prepared) => Some(prepared),
+                    Cow::Borrowed(_) => None,
+                };
+                (prepared.map_or(res, Arc::new), res_cc)
+            }
+        };
+        let req_cc = parse_cache_control(req.get_all(CACHE_CONTROL));
 
-        Self {
+        let vary = parse_vary(&res);
+        let mut policy = Self {
             req,
             res,
             uri,
@@ -209,108 +751,587 @@ impl CachePolicy {
             config,
             res_cc,
             req_cc,
+            vary,
             response_time,
+            body_digest: None,
+            derived: Derived::PENDING,
+        };
+        if policy.config.stored_request_headers == StoredRequestHeaders::Vary {
+            let vary = Arc::clone(&policy.vary);
+            retain_headers(&mut policy.req, |name, _| {
+                name == HOST || name == AUTHORIZATION || vary.contains(name)
+            });
+            // Whether there was a credential still matters e.g. after switching to a shared cache
+            // with `with_options()`, but the credential itself isn't kept
+            let redacted = policy
+                .req
+                .get_all(AUTHORIZATION)
+                .iter()
+                .all(|value| value == REDACTED_AUTHORIZATION);
+            if !redacted {
+                Arc::make_mut(&mut policy.req).insert(AUTHORIZATION, REDACTED_AUTHORIZATION);
+            }
+        }
+        if policy.config.minimize_headers {
+            policy.minimize();
         }
+        policy.with_derived()
     }
 
     /// Returns a default [`Config`] struct
     ///
     /// [`Config`] may be used to customize non-default caching behavior
-    pub const fn config() -> Config {
+    pub fn config() -> Config {
         Config::default()
     }
 
+    /// The [`Config`] that the policy was built with
+    ///
+    /// Unlike [`config()`][Self::config] this reflects the actual options in use e.g. after
+    /// deserializing a stored policy
+    pub fn options(&self) -> &Config {
+        &self.config
+    }
+
+    /// Re-evaluates the stored request and response under a different [`Config`]
+    ///
+    /// This makes it possible for a single stored entry to serve caches in different modes e.g.
+    /// both shared and private. Note that any headers that were already dropped due to
+    /// [`Config::ignore_cargo_cult`] stay dropped.
+    pub fn with_options(&self, config: Config) -> Self {
+        Self::from_details(
+            self.uri.clone(),
+            self.method.clone(),
+            self.status,
+            Arc::clone(&self.req),
+            Arc::clone(&self.res),
+            self.response_time,
+            config,
+        )
+        .with_body_digest(self.body_digest.clone())
+    }
+
+    /// Drops the stored headers that don't affect any future decisions
+    ///
+    /// Only the request headers nominated by `Vary` (along with `Host`, `Authorization`,
+    /// `Cache-Control`, and `Pragma`) are kept, and the response keeps its validators, dates,
+    /// caching directives, `Vary`, and the few other headers that the policy consults. This can
+    /// shrink stored policies considerably when requests and responses carry lots of headers.
+    ///
+    /// The dropped response headers are also missing from the responses that the policy builds
+    /// e.g. [`stale_response()`][Self::stale_response], so store the full response headers
+    /// separately and use [`write_cached_headers()`][Self::write_cached_headers] on them instead.
+    /// See [`Config::minimize_headers`] for doing this to every new policy.
+    pub fn minimize(&mut self) {
+        let vary = Arc::clone(&self.vary);
+        retain_headers(&mut self.req, |name, _| {
+            MINIMIZED_REQUEST_HEADERS.contains(&name.as_str()) || vary.contains(name)
+        });
+        retain_headers(&mut self.res, |name, _| {
+            MINIMIZED_RESPONSE_HEADERS.contains(&name.as_str())
+        });
+    }
+
+    /// Estimates how many bytes the policy takes up, including what it points to on the heap
+    ///
+    /// This covers the stored headers, URI, and parsed directives so that storage layers can
+    /// account policies against a memory budget alongside their bodies. The headers that clones
+    /// share are counted for each of them, and the [`Config`] isn't counted at all.
+    pub fn approx_size(&self) -> usize {
+        fn headers_size(headers: &HeaderMap) -> usize {
+            // Each entry takes roughly a name and value along with the map's links and indices
+            let entries =
+                headers.capacity() * (std::mem::size_of::<(HeaderName, HeaderValue)>() + 16);
+            let text = headers
+                .iter()
+                .map(|(name, value)| name.as_str().len() + value.len());
+            std::mem::size_of::<HeaderMap>() + entries + text.sum::<usize>()
+        }
+
+        let uri = self.uri.scheme_str().map_or(0, str::len)
+            + self
+                .uri
+                .authority()
+                .map_or(0, |authority| authority.as_str().len())
+            + self
+                .uri
+                .path_and_query()
+                .map_or(0, |path| path.as_str().len());
+        std::mem::size_of::<Self>()
+            + headers_size(&self.req)
+            + headers_size(&self.res)
+            + uri
+            + self.res_cc.heap_size()
+            + self.req_cc.heap_size()
+            + self.vary.len() * std::mem::size_of::<HeaderName>()
+            + self.body_digest.as_ref().map_or(0, |digest| digest.len())
+    }
+
+    /// The URI of the stored request
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// The method of the stored request
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The status of the stored response
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// When the stored response was received
+    ///
+    /// This is the `now` that the policy was created (or last updated) with on the local clock
+    pub fn response_time(&self) -> SystemTime {
+        self.response_time
+    }
+
+    /// The stored response's `Date` header
+    ///
+    /// A missing or invalid `Date` is replaced with the [response time][Self::response_time], like
+    /// it is when calculating the response's age. Otherwise this is on the origin server's clock, so
+    /// it may not line up with [`response_time()`][Self::response_time]
+    pub fn date(&self) -> SystemTime {
+        self.derived.dates.date.unwrap_or(self.response_time)
+    }
+
+    /// The headers of the stored request
+    pub fn request_headers(&self) -> &HeaderMap {
+        &self.req
+    }
+
+    /// The headers of the stored response
+    ///
+    /// These are the headers as stored, so they may differ from what was originally received e.g.
+    /// when [`Config::ignore_cargo_cult`] rewrote them, or a `Date` was added for the response
+    /// time since the origin didn't send one. Use
+    /// [`before_request()`][Self::before_request] for the headers to send to a client.
+    pub fn response_headers(&self) -> &HeaderMap {
+        &self.res
+    }
+
+    /// The stored response's `Cache-Control` directives
+    pub fn response_directives(&self) -> ResponseDirectives<'_> {
+        ResponseDirectives::new(&self.res_cc)
+    }
+
+    /// The value of one of the stored response's `Cache-Control` directives
+    ///
+    /// The name is case-insensitive. Returns `Some(None)` for a directive without a value and
+    /// `None` when it's absent. See [`ResponseDirectives::get()`].
+    pub fn response_directive(&self, name: &str) -> Option<Option<Cow<'_, str>>> {
+        if name.bytes().any(|b| b.is_ascii_uppercase()) {
+            return self.response_directives().get(&name.to_ascii_lowercase());
+        }
+        self.response_directives().get(name)
+    }
+
+    /// The stored response's extension directives i.e. the ones the policy doesn't act on
+    ///
+    /// See [`ResponseDirectives::extensions()`]
+    pub fn extension_directives(&self) -> impl Iterator<Item = (&str, Option<Cow<'_, str>>)> {
+        self.response_directives().extensions()
+    }
+
+    /// The request headers nominated by the stored response's `Vary` header
+    ///
+    /// The names are lowercased and deduplicated in the order they first appear. These are the
+    /// request headers that need to be kept alongside the entry to match future requests against
+    /// it. `Vary: *` is reported as a `*` name which never matches.
+    pub fn vary_headers(&self) -> Vec<HeaderName> {
+        self.vary.to_vec()
+    }
+
+    /// The keys that a storage layer can index this entry by
+    ///
+    /// See [`CacheKey`] for details
+    pub fn cache_key(&self) -> CacheKey {
+        CacheKey {
+            primary: primary_key(
+                &self.method,
+                &self.uri,
+                self.req.get(HOST),
+                self.body_digest.as_deref(),
+            ),
+            secondary: self.secondary_key_from(&self.req),
+        }
+    }
+
+    /// The primary key for looking up entries that may satisfy `req`
+    ///
+    /// This matches [`CacheKey::primary()`] for the entries that `req` may be able to use
+    pub fn primary_key<Req: RequestLike>(req: &Req) -> String {
+        Self::primary_key_with_config(req, &Config::default())
+    }
+
+    /// Like [`primary_key()`][Self::primary_key], but for entries stored with `config`
+    ///
+    /// The [`Config::ignored_query_params`] are left out of the key
+    pub fn primary_key_with_config<Req: RequestLike>(req: &Req, config: &Config) -> String {
+        primary_key(
+            req.method(),
+            &config.strip_ignored_query_params(req.uri()),
+            req.headers().get(HOST),
+            req.body_digest(),
+        )
+    }
+
+    /// The secondary key of `req` according to this entry's `Vary` header
+    ///
+    /// This matches this entry's [`CacheKey::secondary()`] when the headers nominated by `Vary`
+    /// match, so a storage layer can find the right variant among entries sharing a primary key
+    pub fn secondary_key<Req: RequestLike>(&self, req: &Req) -> String {
+        self.secondary_key_from(req.headers())
+    }
+
+    /// A one-line summary of the key facts about the policy at `now` for logs and admin UIs
+    ///
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use http::{header, Request, Response};
+    /// use http_cache_policy::CachePolicy;
+    ///
+    /// let req = Request::get("https://example.com/a").body(()).unwrap();
+    /// let res = Response::builder()
+    ///     .header(header::CACHE_CONTROL, "max-age=60")
+    ///     .header(header::ETAG, "\"v1\"")
+    ///     .body(())
+    ///     .unwrap();
+    /// let now = SystemTime::now();
+    /// let policy = CachePolicy::with_config(&req, &res, now, Default::default());
+    /// assert_eq!(
+    ///     policy.summary(now + Duration::from_secs(10)).to_string(),
+    ///     r#"GET https://example.com/a 200 OK storable ttl=50s etag="v1""#,
+    /// );
+    /// ```
+    pub fn summary(&self, now: SystemTime) -> PolicySummary<'_> {
+        PolicySummary { policy: self, now }
+    }
+
+    /// A value identifying the stored entry for deduplication
+    ///
+    /// Two policies have the same identity when they share a [`CacheKey`], status, validators, and
+    /// response directives. Header order, the order of directives, and when the responses were
+    /// received don't matter.
+    pub fn identity(&self) -> PolicyIdentity {
+        let validator = |name| {
+            self.res
+                .get_str(name)
+                .map(|value| value.trim().to_owned().into_boxed_str())
+        };
+        let mut directives = self
+            .res_cc
+            .iter()
+            .map(|(k, v)| (k.into(), v.to_text().map(From::from)))
+            .collect::<Vec<_>>();
+        directives.sort();
+
+        PolicyIdentity {
+            key: self.cache_key(),
+            status: self.status.as_u16(),
+            etag: validator(&ETAG),
+            last_modified: validator(&LAST_MODIFIED),
+            directives,
+        }
+    }
+
+    fn secondary_key_from(&self, headers: &HeaderMap) -> String {
+        if self.vary.iter().any(|name| name == "*") {
+            return "*".to_owned();
+        }
+        let mut names = self.vary.iter().collect::<Vec<_>>();
+        names.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        let mut key = String::new();
+        for name in names {
+            if !key.is_empty() {
+                key.push('\n');
+            }
+            key.push_str(name.as_str());
+            if let Some(value) = headers.get(name) {
+                key.push('=');
+                key.push_str(&String::from_utf8_lossy(value.as_bytes()));
+            }
+        }
+        key
+    }
+
     /// TODO
+    ///
+    /// See [`storability()`][Self::storability] for why a response isn't storable
     pub fn is_storable(&self) -> bool {
-        // The "no-store" request directive indicates that a cache MUST NOT store any part of either this request or any response to it.
-        !self.req_cc.contains_key("no-store") &&
-            // A cache MUST NOT store a response to any request, unless:
-            // The request method is understood by the cache and defined as being cacheable, and
-            (Method::GET == self.method ||
-                Method::HEAD == self.method ||
-                (Method::POST == self.method && self.has_explicit_expiration())) &&
-            // the response status code is understood by the cache, and
-            UNDERSTOOD_STATUSES.contains(&self.status.as_u16()) &&
-            // the "no-store" cache directive does not appear in request or response header fields, and
-            !self.res_cc.contains_key("no-store") &&
-            // the "private" response directive does not appear in the response, if the cache is shared, and
-            (self.config.mode.is_private() || !self.res_cc.contains_key("private")) &&
-            // the Authorization header field does not appear in the request, if the cache is shared,
-            (self.config.mode.is_private() ||
-                !self.req.contains_key(AUTHORIZATION) ||
-                self.allows_storing_authenticated()) &&
-            // the response either:
-            // contains an Expires header field, or
-            (self.res.contains_key(EXPIRES) ||
-                // contains a max-age response directive, or
-                // contains a s-maxage response directive and the cache is shared, or
-                // contains a public response directive.
-                self.res_cc.contains_key("max-age") ||
-                (self.config.mode.is_shared() && self.res_cc.contains_key("s-maxage")) ||
-                self.res_cc.contains_key("public") ||
-                // has a status code that is defined as cacheable by default
-                STATUS_CODE_CACHEABLE_BY_DEFAULT.contains(&self.status.as_u16()))
-    }
-
-    fn has_explicit_expiration(&self) -> bool {
-        // 4.2.1 Calculating Freshness Lifetime
-        (self.config.mode.is_shared() && self.res_cc.contains_key("s-maxage"))
-            || self.res_cc.contains_key("max-age")
-            || self.res.contains_key(EXPIRES)
+        self.storability().is_storable()
+    }
+
+    /// If the response can be stored by a cache in `mode`
+    ///
+    /// This answers for a different audience than the policy's own [`Config::mode`] without
+    /// reconstructing the policy e.g. for a proxy that fronts both shared and private caches
+    pub fn is_storable_for(&self, mode: Mode) -> bool {
+        self.storability_for(mode).is_storable()
+    }
+
+    /// Determines if the response can be stored, and if not then why
+    pub fn storability(&self) -> Storability {
+        self.derived.storability
+    }
+
+    fn storability_for(&self, mode: Mode) -> Storability {
+        if mode == self.config.mode {
+            self.derived.storability
+        } else {
+            self.view().storability(mode)
+        }
     }
 
     /// TODO
     pub fn before_request<Req: RequestLike>(&self, req: &Req, now: SystemTime) -> BeforeRequest {
+        lossy(self.before_request_with(req, None, now, Rewrite::Lossy))
+    }
+
+    /// Like [`before_request()`][Self::before_request], but fails instead of dropping anything
+    /// that can't be written into the returned headers
+    ///
+    /// See [`Error`] for what can't be written
+    pub fn try_before_request<Req: RequestLike>(
+        &self,
+        req: &Req,
+        now: SystemTime,
+    ) -> Result<BeforeRequest, Error> {
+        self.before_request_with(req, None, now, Rewrite::Strict)
+    }
+
+    /// Like [`before_request()`][Self::before_request], but with the request's already parsed
+    /// `Cache-Control` directives
+    ///
+    /// This saves parsing the same request's directives again when evaluating it against several
+    /// candidate entries. `directives` has to come from `req`'s headers
+    pub fn before_request_with_directives<Req: RequestLike>(
+        &self,
+        req: &Req,
+        directives: &RequestDirectives,
+        now: SystemTime,
+    ) -> BeforeRequest {
+        lossy(self.before_request_with(req, Some(directives), now, Rewrite::Lossy))
+    }
+
+    fn before_request_with<Req: RequestLike>(
+        &self,
+        req: &Req,
+        directives: Option<&RequestDirectives>,
+        now: SystemTime,
+        rewrite: Rewrite,
+    ) -> Result<BeforeRequest, Error> {
         let req_headers = req.headers();
 
         // revalidation allowed via HEAD
         let (matches, may_revalidate) = self.request_matches(req);
 
-        if matches && self.satisfies_without_revalidation(req_headers, now) {
-            BeforeRequest::Fresh(self.cached_response(now))
-        } else if may_revalidate {
-            BeforeRequest::Stale {
-                request: self.revalidation_request(req),
-                matches,
+        let reason = match self.stale_reason(req, directives, now) {
+            Some(reason) => reason,
+            None => {
+                self.observe(Decision::Fresh);
+                return Ok(BeforeRequest::Fresh(
+                    self.cached_response_with(now, rewrite)?,
+                ));
             }
+        };
+        self.observe(Decision::Stale(reason));
+
+        let request = if may_revalidate {
+            self.revalidation_request_with(req, rewrite)?
         } else {
-            BeforeRequest::Stale {
-                request: self.request_from_headers(req_headers.clone()),
-                matches,
+            self.request_from_headers(req_headers.clone())
+        };
+        Ok(BeforeRequest::Stale {
+            request,
+            matches,
+            reason,
+        })
+    }
+
+    /// Like [`before_request()`][Self::before_request], but updates the caller's request in place
+    ///
+    /// When the stored response needs revalidation, hop-by-hop headers are removed from `req` and
+    /// the conditional headers (`If-None-Match`, `If-Modified-Since`, `If-Range`) are added or
+    /// removed so that it can be forwarded as-is. The method and URI are left untouched, as are the
+    /// headers of requests that can't revalidate the stored response. This saves copying the
+    /// request for callers that are going to forward it anyway
+    pub fn before_request_mut(
+        &self,
+        req: &mut http::request::Parts,
+        now: SystemTime,
+    ) -> BeforeRequestMut {
+        lossy(self.before_request_mut_with(req, now, Rewrite::Lossy))
+    }
+
+    /// Like [`before_request_mut()`][Self::before_request_mut], but fails instead of dropping
+    /// anything that can't be written into the headers
+    ///
+    /// `req` may have been partially updated when this fails
+    pub fn try_before_request_mut(
+        &self,
+        req: &mut http::request::Parts,
+        now: SystemTime,
+    ) -> Result<BeforeRequestMut, Error> {
+        self.before_request_mut_with(req, now, Rewrite::Strict)
+    }
+
+    fn before_request_mut_with(
+        &self,
+        req: &mut http::request::Parts,
+        now: SystemTime,
+        rewrite: Rewrite,
+    ) -> Result<BeforeRequestMut, Error> {
+        let (matches, may_revalidate) = self.request_matches(&*req);
+
+        let reason = match self.stale_reason(&*req, None, now) {
+            Some(reason) => reason,
+            None => {
+                self.observe(Decision::Fresh);
+                return Ok(BeforeRequestMut::Fresh(
+                    self.cached_response_with(now, rewrite)?,
+                ));
             }
+        };
+        self.observe(Decision::Stale(reason));
+
+        if may_revalidate {
+            Self::remove_hop_by_hop_headers(&mut req.headers, rewrite)?;
+            self.add_revalidation_headers(&mut req.headers, rewrite)?;
         }
+        Ok(BeforeRequestMut::Stale { matches, reason })
+    }
+
+    /// If the stored response was for an equivalent request
+    ///
+    /// This checks the method, URI, `Host`, the headers nominated by `Vary`, and the body digest
+    /// without considering freshness at all, which makes it handy for picking between several
+    /// candidate entries for the same URI before calling [`before_request()`][Self::before_request]
+    pub fn matches<Req: RequestLike>(&self, req: &Req) -> bool {
+        self.request_matches(req).0
     }
 
-    fn satisfies_without_revalidation(&self, req_headers: &HeaderMap, now: SystemTime) -> bool {
+    /// If the stored response can be reused for `req` at `now` without revalidation
+    ///
+    /// This is the same check that decides between [`BeforeRequest::Fresh`] and
+    /// [`BeforeRequest::Stale`] without building the response or revalidation request, so it's
+    /// cheap enough for metrics and admission decisions
+    pub fn satisfies_without_revalidation<Req: RequestLike>(
+        &self,
+        req: &Req,
+        now: SystemTime,
+    ) -> bool {
+        self.stale_reason(req, None, now).is_none()
+    }
+
+    /// Like [`satisfies_without_revalidation()`][Self::satisfies_without_revalidation], but with
+    /// the request's already parsed `Cache-Control` directives
+    ///
+    /// See [`before_request_with_directives()`][Self::before_request_with_directives]
+    pub fn satisfies_without_revalidation_with_directives<Req: RequestLike>(
+        &self,
+        req: &Req,
+        directives: &RequestDirectives,
+        now: SystemTime,
+    ) -> bool {
+        self.stale_reason(req, Some(directives), now).is_none()
+    }
+
+    /// Classifies how the stored response can be used for `req` at `now`
+    ///
+    /// This folds the request's `max-stale` and the response's `stale-while-revalidate` and
+    /// `stale-if-error` directives into a single answer to branch on
+    pub fn freshness<Req: RequestLike>(&self, req: &Req, now: SystemTime) -> Freshness {
+        match self.stale_reason(req, None, now) {
+            None if self.is_stale(now) => Freshness::StaleButUsable(StaleAllowance::MaxStale),
+            None => Freshness::Fresh,
+            Some(StaleReason::Expired) if self.stale_while_revalidate_until(now).is_some() => {
+                Freshness::StaleButUsable(StaleAllowance::StaleWhileRevalidate)
+            }
+            Some(StaleReason::Expired) if self.stale_if_error_until(now).is_some() => {
+                Freshness::StaleButUsable(StaleAllowance::StaleIfError)
+            }
+            Some(reason) => Freshness::MustRevalidate(reason),
+        }
+    }
+
+    /// Why the stored response can't be reused for `req` as-is, if it can't
+    ///
+    /// The request's directives are only parsed (unless they're provided) when it matches
+    fn stale_reason<Req: RequestLike>(
+        &self,
+        req: &Req,
+        directives: Option<&RequestDirectives>,
+        now: SystemTime,
+    ) -> Option<StaleReason> {
+        if let Some(mismatch) = self.request_mismatch(req) {
+            return Some(mismatch);
+        }
+        if self.method != req.method() {
+            return Some(StaleReason::MethodMismatch);
+        }
+        match directives {
+            Some(directives) => self.unsatisfied_reason(directives, now),
+            None => self.unsatisfied_reason(&RequestDirectives::from_headers(req.headers()), now),
+        }
+    }
+
+    /// Explains how the policy evaluates `req` at `now`
+    ///
+    /// This reports the details that [`before_request()`][Self::before_request] consults, which is
+    /// handy for debugging why an entry needed revalidation
+    pub fn explain<Req: RequestLike>(&self, req: &Req, now: SystemTime) -> Explanation {
+        let directives = |cc: &DirectiveSet| {
+            let mut directives = cc
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_text().map(String::from)))
+                .collect::<Vec<_>>();
+            directives.sort();
+            directives
+        };
+        let (freshness_lifetime, freshness_source) = self.max_age_with_source(self.config.mode);
+
+        Explanation {
+            storability: self.storability(),
+            response_directives: directives(&self.res_cc),
+            request_directives: directives(&parse_cache_control(
+                req.headers().get_all(CACHE_CONTROL),
+            )),
+            freshness_source,
+            freshness_lifetime,
+            age: self.age(now),
+            time_to_live: self.time_to_live(now),
+            stale_reason: self.stale_reason(req, None, now),
+        }
+    }
+
+    /// Why the stored response can't be reused for a matching request without revalidation
+    fn unsatisfied_reason(
+        &self,
+        directives: &RequestDirectives,
+        now: SystemTime,
+    ) -> Option<StaleReason> {
         // When presented with a request, a cache MUST NOT reuse a stored response, unless:
         // the presented request does not contain the no-cache pragma (Section 5.4), nor the no-cache cache directive,
         // unless the stored response is successfully validated (Section 4.3), and
-        let req_cc = parse_cache_control(req_headers.get_all(CACHE_CONTROL));
-        if req_cc.contains_key("no-cache")
-            || req_headers
-                .get_str(&PRAGMA)
-                .map_or(false, |v| v.contains("no-cache"))
-        {
-            return false;
+        if directives.no_cache {
+            return Some(StaleReason::RequestNoCache);
         }
 
-        if let Some(max_age) = req_cc
-            .get("max-age")
-            .and_then(|v| v.as_ref())
-            .and_then(|p| p.parse().ok())
-        {
-            if self.age(now) > Duration::from_secs(max_age) {
-                return false;
+        if let Some(max_age) = directives.max_age {
+            if self.age(now) > max_age {
+                return Some(StaleReason::RequestMaxAge);
             }
         }
 
-        if let Some(min_fresh) = req_cc
-            .get("min-fresh")
-            .and_then(|v| v.as_ref())
-            .and_then(|p| p.parse().ok())
-        {
-            if self.time_to_live(now) < Duration::from_secs(min_fresh) {
-                return false;
+        if let Some(min_fresh) = directives.min_fresh {
+            if self.time_to_live(now) < min_fresh {
+                return Some(StaleReason::RequestMinFresh);
             }
         }
 
@@ -318,84 +1339,151 @@ impl CachePolicy {
         // fresh, or allowed to be served stale
         if self.is_stale(now) {
             // If no value is assigned to max-stale, then the client is willing to accept a stale response of any age.
-            let max_stale = req_cc.get("max-stale");
-            let has_max_stale = max_stale.is_some();
-            let max_stale = max_stale
-                .and_then(|m| m.as_ref())
-                .and_then(|s| s.parse().ok());
             let allows_stale = !self.res_cc.contains_key("must-revalidate")
-                && has_max_stale
-                && max_stale.map_or(true, |val| {
-                    Duration::from_secs(val) > self.age(now) - self.max_age()
+                && directives.max_stale.map_or(false, |max_stale| {
+                    max_stale.map_or(true, |val| val > self.age(now) - self.max_age())
                 });
             if !allows_stale {
-                return false;
+                return Some(StaleReason::Expired);
             }
         }
-
-        true
+
+        None
+    }
+
+    /// returns: matches including method, matches allowing head
+    fn request_matches<Req: RequestLike>(&self, req: &Req) -> (bool, bool) {
+        let matches = self.request_mismatch(req).is_none();
+        let exact_match = matches && self.method == req.method();
+
+        // the request method associated with the stored response allows it to be used for the presented request, and
+        (exact_match, exact_match || Method::HEAD == req.method())
+    }
+
+    /// Why the request's effective URI doesn't match the stored one
+    fn uri_mismatch<Req: RequestLike>(&self, req: &Req) -> Option<StaleReason> {
+        let host_mismatch = |stored: Option<&str>, presented: Option<&str>| {
+            let matches = match (stored, presented) {
+                (Some(stored), Some(presented)) => stored.eq_ignore_ascii_case(presented),
+                (stored, presented) => stored == presented,
+            };
+            (!matches).then_some(StaleReason::HostMismatch)
+        };
+
+        let is_same_uri = if self.config.ignored_query_params.is_empty() {
+            req.is_same_uri(&self.uri)
+        } else {
+            self.config.strip_ignored_query_params(req.uri()) == self.uri
+        };
+        if self.config.strict_host_matching {
+            if !is_same_uri {
+                return Some(StaleReason::UriMismatch);
+            }
+            return (self.req.get(HOST) != req.headers().get(HOST))
+                .then_some(StaleReason::HostMismatch);
+        }
+        if is_same_uri {
+            // Identical absolute URIs already agree on the authority
+            if self.uri.authority().is_some() {
+                return None;
+            }
+            return host_mismatch(
+                effective_authority(&self.uri, &self.req),
+                effective_authority(&self.uri, req.headers()),
+            );
+        }
+
+        // Either side may be in origin-form with the authority in the `Host` header instead
+        let uri = self.config.strip_ignored_query_params(req.uri());
+        let schemes_differ = match (self.uri.scheme(), uri.scheme()) {
+            (Some(stored), Some(presented)) => stored != presented,
+            _ => false,
+        };
+        if schemes_differ || self.uri.path() != uri.path() || self.uri.query() != uri.query() {
+            return Some(StaleReason::UriMismatch);
+        }
+        if self.uri.authority().is_some() && uri.authority().is_some() {
+            // Both absolute and they aren't the same
+            return Some(StaleReason::UriMismatch);
+        }
+        host_mismatch(
+            effective_authority(&self.uri, &self.req),
+            effective_authority(&uri, req.headers()),
+        )
     }
 
-    /// returns: matches including method, matches allowing head
-    fn request_matches<Req: RequestLike>(&self, req: &Req) -> (bool, bool) {
+    /// Why the request doesn't match the stored one, ignoring the method
+    fn request_mismatch<Req: RequestLike>(&self, req: &Req) -> Option<StaleReason> {
         // The presented effective request URI and that of the stored response match, and
-        let matches = req.is_same_uri(&self.uri) &&
-            (self.req.get(HOST) == req.headers().get(HOST)) &&
-            // selecting header fields nominated by the stored response (if any) match those presented, and
-            self.vary_matches(req);
-        let exact_match = matches && self.method == req.method();
-
-        // the request method associated with the stored response allows it to be used for the presented request, and
-        (exact_match, exact_match || Method::HEAD == req.method())
-    }
-
-    fn allows_storing_authenticated(&self) -> bool {
-        //  following Cache-Control response directives (Section 5.2.2) have such an effect: must-revalidate, public, and s-maxage.
-        self.res_cc.contains_key("must-revalidate")
-            || self.res_cc.contains_key("public")
-            || self.res_cc.contains_key("s-maxage")
+        if let Some(reason) = self.uri_mismatch(req) {
+            return Some(reason);
+        }
+        // selecting header fields nominated by the stored response (if any) match those presented, and
+        if !self.vary_matches(req) {
+            return Some(StaleReason::VaryMismatch);
+        }
+        // a shared cache doesn't hand a response obtained with one credential to another, unless
+        // the response is explicitly public. A credential that wasn't stored can't be compared
+        if self.config.mode.is_shared()
+            && !self.res_cc.contains_key("public")
+            && self.req.contains_key(AUTHORIZATION)
+            && (self.req.get(AUTHORIZATION) == Some(&REDACTED_AUTHORIZATION)
+                || self.req.get(AUTHORIZATION) != req.headers().get(AUTHORIZATION))
+        {
+            return Some(StaleReason::AuthorizationMismatch);
+        }
+        // the request content matches for methods that are keyed on it
+        if self.body_digest.as_deref() != req.body_digest() {
+            return Some(StaleReason::BodyDigestMismatch);
+        }
+        None
     }
 
     fn vary_matches<Req: RequestLike>(&self, req: &Req) -> bool {
-        for name in get_all_comma(self.res.get_all(VARY)) {
+        for name in self.vary.iter() {
             // A Vary header field-value of "*" always fails to match
             if name == "*" {
                 return false;
             }
-            let name = name.trim().to_ascii_lowercase();
-            if req.headers().get(&name) != self.req.get(&name) {
+            if req.headers().get(name) != self.req.get(name) {
                 return false;
             }
         }
         true
     }
 
-    fn copy_without_hop_by_hop_headers(in_headers: &HeaderMap) -> HeaderMap {
-        let mut headers = HeaderMap::with_capacity(in_headers.len());
-
-        for (h, v) in in_headers
-            .iter()
-            .filter(|(h, _)| !HOP_BY_HOP_HEADERS.contains(&h.as_str()))
-        {
-            headers.insert(h.clone(), v.clone());
-        }
+    fn copy_without_hop_by_hop_headers(
+        in_headers: &HeaderMap,
+        rewrite: Rewrite,
+    ) -> Result<HeaderMap, Error> {
+        let mut headers = in_headers.clone();
+        Self::remove_hop_by_hop_headers(&mut headers, rewrite)?;
+        Ok(headers)
+    }
 
+    fn remove_hop_by_hop_headers(headers: &mut HeaderMap, rewrite: Rewrite) -> Result<(), Error> {
         // 9.1.  Connection
-        for name in get_all_comma(in_headers.get_all(CONNECTION)) {
+        let connection_options = get_all_comma(headers.get_all(CONNECTION))
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        for name in connection_options {
             headers.remove(name);
         }
+        for name in HOP_BY_HOP_HEADERS {
+            headers.remove(*name);
+        }
 
-        let new_warnings = join(
-            get_all_comma(in_headers.get_all(WARNING)).filter(|warning| {
-                !warning.trim_start().starts_with('1') // FIXME: match 100-199, not 1 or 1000
-            }),
-        );
+        if !warning::members(headers.get_all(WARNING)).any(warning::is_1xx) {
+            return Ok(());
+        }
+        let new_warnings =
+            join(warning::members(headers.get_all(WARNING)).filter(|w| !warning::is_1xx(w)));
         if new_warnings.is_empty() {
             headers.remove(WARNING);
         } else {
-            headers.insert(WARNING, HeaderValue::from_str(&new_warnings).unwrap());
+            headers.insert(WARNING, rewrite.text(&WARNING, new_warnings)?);
         }
-        headers
+        Ok(())
     }
 
     /// Updates and filters the response headers for a cached response before
@@ -406,43 +1494,113 @@ impl CachePolicy {
     /// It returns response "parts" without a body. You can upgrade it to a full
     /// response with `Response::from_parts(parts, BYOB)`
     fn cached_response(&self, now: SystemTime) -> http::response::Parts {
-        let mut headers = Self::copy_without_hop_by_hop_headers(&self.res);
+        lossy(self.cached_response_with(now, Rewrite::Lossy))
+    }
+
+    fn cached_response_with(
+        &self,
+        now: SystemTime,
+        rewrite: Rewrite,
+    ) -> Result<http::response::Parts, Error> {
+        let mut parts = self.stored_response_parts();
+        self.update_cached_headers(&mut parts.headers, now, rewrite)?;
+        Ok(parts)
+    }
+
+    fn stored_response_parts(&self) -> http::response::Parts {
+        let mut parts = Response::new(()).into_parts().0;
+        parts.status = self.status;
+        parts.headers = HeaderMap::clone(&self.res);
+        parts
+    }
+
+    fn update_cached_headers(
+        &self,
+        headers: &mut HeaderMap,
+        now: SystemTime,
+        rewrite: Rewrite,
+    ) -> Result<(), Error> {
+        Self::remove_hop_by_hop_headers(headers, rewrite)?;
         let age = self.age(now);
-        let day = Duration::from_secs(3600 * 24);
+        if self.needs_heuristic_warning(age) {
+            headers.append(
+                WARNING,
+                HeaderValue::from_static(r#"113 - "rfc7234 5.5.4""#),
+            );
+        }
+        headers.insert(AGE, HeaderValue::from(age.as_secs()));
+        headers.insert(DATE, rewrite.date(now)?);
+        Ok(())
+    }
 
+    fn needs_heuristic_warning(&self, age: Duration) -> bool {
         // A cache SHOULD generate 113 warning if it heuristically chose a freshness
         // lifetime greater than 24 hours and the response's age is greater than 24 hours.
-        if age > day && !self.has_explicit_expiration() && self.max_age() > day {
+        let day = Duration::from_secs(3600 * 24);
+        age > day && !self.view().has_explicit_expiration(self.config.mode) && self.max_age() > day
+    }
+
+    /// Applies the cached response header updates onto `headers` at `now`
+    ///
+    /// This is what [`stale_response()`][Self::stale_response] does to the stored headers:
+    /// hop-by-hop headers are removed, `Age` and `Date` are set, and `Warning`s are added as
+    /// needed. Pass in the stored response's headers (e.g. kept alongside the body) to serve a hit
+    /// without building a new response
+    pub fn write_cached_headers(&self, headers: &mut HeaderMap, now: SystemTime) {
+        lossy(self.write_cached_headers_with(headers, now, Rewrite::Lossy));
+    }
+
+    /// Like [`write_cached_headers()`][Self::write_cached_headers], but fails instead of dropping
+    /// anything that can't be written into `headers`
+    ///
+    /// `headers` may have been partially updated when this fails
+    pub fn try_write_cached_headers(
+        &self,
+        headers: &mut HeaderMap,
+        now: SystemTime,
+    ) -> Result<(), Error> {
+        self.write_cached_headers_with(headers, now, Rewrite::Strict)
+    }
+
+    fn write_cached_headers_with(
+        &self,
+        headers: &mut HeaderMap,
+        now: SystemTime,
+        rewrite: Rewrite,
+    ) -> Result<(), Error> {
+        self.update_cached_headers(headers, now, rewrite)?;
+        if self.is_stale(now) {
             headers.append(
                 WARNING,
-                HeaderValue::from_static(r#"113 - "rfc7234 5.5.4""#),
+                HeaderValue::from_static(r#"110 - "Response is Stale""#),
             );
         }
-        headers.insert(
-            AGE,
-            HeaderValue::from_str(&age.as_secs().to_string()).unwrap(),
-        );
-        headers.insert(
-            DATE,
-            HeaderValue::from_str(&httpdate::fmt_http_date(now)).unwrap(),
-        );
-
-        let mut parts = Response::builder()
-            .status(self.status)
-            .body(())
-            .unwrap()
-            .into_parts()
-            .0;
-        parts.headers = headers;
-        parts
+        Ok(())
     }
 
-    fn raw_server_date(&self) -> SystemTime {
-        let date = self
-            .res
-            .get_str(&DATE)
-            .and_then(|date| httpdate::parse_http_date(date).ok());
-        date.unwrap_or(self.response_time)
+    /// The stored response to serve at `now` even though it may be stale
+    ///
+    /// This is for when serving a stale response is legitimate e.g. the request's `max-stale`,
+    /// `stale-while-revalidate`, `stale-if-error`, or when the origin can't be reached. The response
+    /// gets a `Warning: 110` (rfc7234 5.5.1) when it's stale at `now`.
+    pub fn stale_response(&self, now: SystemTime) -> http::response::Parts {
+        lossy(self.stale_response_with(now, Rewrite::Lossy))
+    }
+
+    /// Like [`stale_response()`][Self::stale_response], but fails instead of dropping anything
+    /// that can't be written into the response headers
+    pub fn try_stale_response(&self, now: SystemTime) -> Result<http::response::Parts, Error> {
+        self.stale_response_with(now, Rewrite::Strict)
+    }
+
+    fn stale_response_with(
+        &self,
+        now: SystemTime,
+        rewrite: Rewrite,
+    ) -> Result<http::response::Parts, Error> {
+        let mut parts = self.stored_response_parts();
+        self.write_cached_headers_with(&mut parts.headers, now, rewrite)?;
+        Ok(parts)
     }
 
     /// TODO
@@ -456,12 +1614,24 @@ impl CachePolicy {
     }
 
     fn age_header_value(&self) -> Duration {
-        Duration::from_secs(
-            self.res
-                .get_str(&AGE)
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0),
-        )
+        self.derived.age_header
+    }
+
+    /// The freshness lifetime of the stored response (rfc9111 4.2.1)
+    ///
+    /// This is how long the response stays fresh counting from when it was generated, taking into
+    /// account `s-maxage`, `max-age`, `Expires`, and the `Last-Modified` heuristic along with the
+    /// policy's configuration. It's zero for responses that always need revalidation.
+    pub fn freshness_lifetime(&self) -> Duration {
+        self.max_age()
+    }
+
+    /// The current age of the stored response (rfc9111 4.2.3)
+    ///
+    /// This is the response's `Age` header plus how long it's been resident in the cache, so the
+    /// response is fresh while `current_age(now) < freshness_lifetime()`
+    pub fn current_age(&self, now: SystemTime) -> Duration {
+        self.age(now)
     }
 
     /// Value of applicable max-age (or heuristic equivalent) in seconds.
@@ -470,71 +1640,81 @@ impl CachePolicy {
     ///
     /// For an up-to-date value, see `time_to_live()`.
     fn max_age(&self) -> Duration {
-        if !self.is_storable() || self.res_cc.contains_key("no-cache") {
-            return Duration::from_secs(0);
-        }
-
-        // Shared responses with cookies are cacheable according to the RFC, but IMHO it'd be unwise to do so by default
-        // so this implementation requires explicit opt-in via public header
-        if self.config.mode.is_shared()
-            && self.res.contains_key(SET_COOKIE)
-            && !self.res_cc.contains_key("public")
-            && !self.res_cc.contains_key("immutable")
-        {
-            return Duration::from_secs(0);
-        }
+        self.derived.max_age
+    }
 
-        if self.res.get_str(&VARY).map(str::trim) == Some("*") {
-            return Duration::from_secs(0);
+    fn max_age_with_source(&self, mode: Mode) -> (Duration, FreshnessSource) {
+        if mode == self.config.mode {
+            (self.derived.max_age, self.derived.freshness_source)
+        } else {
+            self.view().max_age_with_source(mode)
         }
+    }
 
-        if self.config.mode.is_shared() {
-            if self.res_cc.contains_key("proxy-revalidate") {
-                return Duration::from_secs(0);
-            }
-            // if a response includes the s-maxage directive, a shared cache recipient MUST ignore the Expires field.
-            if let Some(s_max) = self.res_cc.get("s-maxage").and_then(|v| v.as_ref()) {
-                return Duration::from_secs(s_max.parse().unwrap_or(0));
-            }
-        }
+    /// TODO
+    pub fn time_to_live(&self, now: SystemTime) -> Duration {
+        self.time_to_live_for(self.config.mode, now)
+    }
 
-        // If a response includes a Cache-Control field with the max-age directive, a recipient MUST ignore the Expires field.
-        if let Some(max_age) = self.res_cc.get("max-age").and_then(|v| v.as_ref()) {
-            return Duration::from_secs(max_age.parse().unwrap_or(0));
-        }
+    /// How much longer the response is fresh for a cache in `mode`
+    ///
+    /// See [`is_storable_for()`][Self::is_storable_for]
+    pub fn time_to_live_for(&self, mode: Mode, now: SystemTime) -> Duration {
+        self.max_age_with_source(mode)
+            .0
+            .checked_sub(self.age(now))
+            .unwrap_or_default()
+    }
 
-        let default_min_ttl = Duration::from_secs(0);
+    /// The time at which the stored response becomes stale
+    ///
+    /// This is on the local clock, so `time_to_live(now)` is the time left until `stale_at()`. It's
+    /// handy for storing an expiry alongside the entry instead of doing the clock math by hand.
+    pub fn stale_at(&self) -> SystemTime {
+        let remaining = self
+            .max_age()
+            .checked_sub(self.age_header_value())
+            .unwrap_or_default();
+        self.response_time + remaining
+    }
 
-        let server_date = self.raw_server_date();
-        if let Some(expires) = self.res.get_str(&EXPIRES) {
-            return match httpdate::parse_http_date(expires) {
-                // A cache recipient MUST interpret invalid date formats, especially the value "0", as representing a time in the past (i.e., "already expired").
-                Err(_) => Duration::from_secs(0),
-                Ok(expires) => {
-                    return default_min_ttl
-                        .max(expires.duration_since(server_date).unwrap_or_default());
-                }
-            };
-        }
+    /// The time at which the stored response expires on the origin server's clock
+    ///
+    /// This is the response's `Date` (or when it was received without one) plus its freshness
+    /// lifetime, which is what an equivalent `Expires` header would hold. Use
+    /// [`stale_at()`][Self::stale_at] for comparisons against the local clock.
+    pub fn expires_at(&self) -> SystemTime {
+        self.date() + self.max_age()
+    }
 
-        if let Some(last_modified) = self.res.get_str(&LAST_MODIFIED) {
-            if let Ok(last_modified) = httpdate::parse_http_date(last_modified) {
-                if let Ok(diff) = server_date.duration_since(last_modified) {
-                    let secs_left =
-                        diff.as_secs() as f64 * f64::from(f32::from(self.config.last_modified));
-                    return default_min_ttl.max(Duration::from_secs(secs_left as _));
-                }
-            }
-        }
+    /// The time until which the stored response may be served stale while it's revalidated in the
+    /// background (rfc5861 3)
+    ///
+    /// Returns `None` when the response has no `stale-while-revalidate` directive, forbids serving
+    /// it stale, or the window has already passed by `now`
+    pub fn stale_while_revalidate_until(&self, now: SystemTime) -> Option<SystemTime> {
+        self.stale_window_until(self.response_directives().stale_while_revalidate(), now)
+    }
 
-        default_min_ttl
+    /// The time until which the stored response may be served stale when revalidation fails with an
+    /// error (rfc5861 4)
+    ///
+    /// Returns `None` when the response has no `stale-if-error` directive, forbids serving it stale,
+    /// or the window has already passed by `now`
+    pub fn stale_if_error_until(&self, now: SystemTime) -> Option<SystemTime> {
+        self.stale_window_until(self.response_directives().stale_if_error(), now)
     }
 
-    /// TODO
-    pub fn time_to_live(&self, now: SystemTime) -> Duration {
-        self.max_age()
-            .checked_sub(self.age(now))
-            .unwrap_or_default()
+    fn stale_window_until(&self, window: Option<Duration>, now: SystemTime) -> Option<SystemTime> {
+        let directives = self.response_directives();
+        if !self.is_storable()
+            || directives.must_revalidate()
+            || (self.config.mode.is_shared() && directives.proxy_revalidate())
+        {
+            return None;
+        }
+        let until = self.stale_at() + window?;
+        (now < until).then_some(until)
     }
 
     /// TODO
@@ -542,28 +1722,52 @@ impl CachePolicy {
         self.max_age() <= self.age(now)
     }
 
-    /// TODO
-    fn revalidation_request<Req: RequestLike>(&self, incoming_req: &Req) -> http::request::Parts {
-        let mut headers = Self::copy_without_hop_by_hop_headers(incoming_req.headers());
+    fn revalidation_request_with<Req: RequestLike>(
+        &self,
+        incoming_req: &Req,
+        rewrite: Rewrite,
+    ) -> Result<http::request::Parts, Error> {
+        let mut headers = Self::copy_without_hop_by_hop_headers(incoming_req.headers(), rewrite)?;
+        self.add_revalidation_headers(&mut headers, rewrite)?;
+        Ok(self.request_from_headers(headers))
+    }
 
-        // This implementation does not understand range requests
+    /// Sets up the conditional headers to revalidate the stored response
+    fn add_revalidation_headers(
+        &self,
+        headers: &mut HeaderMap,
+        rewrite: Rewrite,
+    ) -> Result<(), Error> {
+        // Any If-Range from the client refers to the client's copy, not the one we have stored
         headers.remove(IF_RANGE);
 
         if !self.is_storable() {
             // not for the same resource, or wasn't allowed to be cached anyway
             headers.remove(IF_NONE_MATCH);
             headers.remove(IF_MODIFIED_SINCE);
-            return self.request_from_headers(headers);
+            return Ok(());
+        }
+
+        // A client MUST NOT generate an If-Range header field containing a weak validator. When
+        // the stored representation has a strong one the origin can send just the requested range
+        // if it's unchanged, or the full new representation otherwise
+        if headers.contains_key(RANGE) {
+            if let Some(validator) = self.strong_validator() {
+                headers.insert(IF_RANGE, validator.clone());
+            }
         }
 
         /* MUST send that entity-tag in any cache validation request (using If-Match or If-None-Match) if an entity-tag has been provided by the origin server. */
-        if let Some(etag) = self.res.get_str(&ETAG) {
-            let if_none = join(get_all_comma(headers.get_all(IF_NONE_MATCH)).chain(Some(etag)));
-            headers.insert(IF_NONE_MATCH, HeaderValue::from_str(&if_none).unwrap());
+        if let Some(etag) = etag(&self.res) {
+            // Clients revalidating repeatedly would otherwise grow the list with the same tag
+            let etags = dedup(entity_tags(headers.get_all(IF_NONE_MATCH)).chain(Some(etag)));
+            let if_none = join(etags.into_iter().map(EntityTag::as_str));
+            headers.insert(IF_NONE_MATCH, rewrite.text(&IF_NONE_MATCH, if_none)?);
         }
 
         // Clients MAY issue simple (non-subrange) GET requests with either weak validators or strong validators. Clients MUST NOT use weak validators in other forms of request.
         let forbids_weak_validators = self.method != Method::GET
+            || headers.contains_key(RANGE)
             || headers.contains_key(ACCEPT_RANGES)
             || headers.contains_key(IF_MATCH)
             || headers.contains_key(IF_UNMODIFIED_SINCE);
@@ -574,33 +1778,127 @@ impl CachePolicy {
             headers.remove(IF_MODIFIED_SINCE);
 
             let etags = join(
-                get_all_comma(headers.get_all(IF_NONE_MATCH))
-                    .filter(|etag| !etag.trim_start().starts_with("W/")),
+                entity_tags(headers.get_all(IF_NONE_MATCH))
+                    .filter(|etag| !etag.is_weak())
+                    .map(EntityTag::as_str),
             );
             if etags.is_empty() {
                 headers.remove(IF_NONE_MATCH);
             } else {
-                headers.insert(IF_NONE_MATCH, HeaderValue::from_str(&etags).unwrap());
+                headers.insert(IF_NONE_MATCH, rewrite.text(&IF_NONE_MATCH, etags)?);
             }
         } else if !headers.contains_key(IF_MODIFIED_SINCE) {
-            if let Some(last_modified) = self.res.get_str(&LAST_MODIFIED) {
-                headers.insert(
-                    IF_MODIFIED_SINCE,
-                    HeaderValue::from_str(last_modified).unwrap(),
-                );
+            if let Some(last_modified) = self.res.get(LAST_MODIFIED) {
+                headers.insert(IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// The stored strong validator, if any, preferring the entity-tag
+    fn strong_validator(&self) -> Option<&HeaderValue> {
+        let etag = strong_etag(&self.res).map(|_| &self.res[ETAG]);
+        etag.or_else(|| strong_last_modified(&self.res))
+    }
+
+    /// If the stored response and `headers` share the same strong validator (rfc9111 3.4)
+    fn strong_validator_matches(&self, headers: &HeaderMap) -> bool {
+        if let (Some(old), Some(new)) = (strong_etag(&self.res), strong_etag(headers)) {
+            return old.strong_eq(new);
+        }
+
+        match (
+            strong_last_modified(&self.res).and(self.derived.dates.last_modified),
+            strong_last_modified(headers).and_then(|_| date_header(headers, &LAST_MODIFIED)?),
+        ) {
+            (Some(old), Some(new)) => old == new,
+            _ => false,
+        }
+    }
+
+    /// The tags used for CDN-style purging from the stored response
+    ///
+    /// Collects the comma-separated `Cache-Tag` values along with the space-separated
+    /// `Surrogate-Key` and `xkey` values into a single deduplicated list, keeping the order that
+    /// they first appear in
+    pub fn cache_tags(&self) -> Vec<&str> {
+        let comma_separated = get_all_comma(self.res.get_all("cache-tag"));
+        let space_separated = ["surrogate-key", "xkey"].into_iter().flat_map(|name| {
+            self.res
+                .get_all(name)
+                .into_iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(str::split_ascii_whitespace)
+        });
+
+        let mut tags = Vec::new();
+        for tag in comma_separated.chain(space_separated) {
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// Checks if a `Range` request can be served by slicing the stored full response
+    ///
+    /// This requires the stored response to be a `200` advertising `Accept-Ranges: bytes` with a
+    /// known `Content-Length`. If the request carries an `If-Range` then it must match the stored
+    /// strong validator.
+    ///
+    /// Returns the inclusive `(first, last)` byte positions to send, or `None` when the request
+    /// isn't a valid range request or when the stored response can't be used to answer it
+    pub fn satisfies_range<Req: RequestLike>(&self, req: &Req) -> Option<Vec<(u64, u64)>> {
+        if self.status != StatusCode::OK {
+            return None;
+        }
+        let accepts_bytes = range::AcceptRanges::from_headers(self.res.get_all(ACCEPT_RANGES))
+            .map_or(false, |accept| accept.accepts_bytes());
+        if !accepts_bytes {
+            return None;
+        }
+        let complete_length = self.res.get_str(&CONTENT_LENGTH)?.trim().parse().ok()?;
+
+        let req_headers = req.headers();
+        if let Some(if_range) = req_headers.get(IF_RANGE) {
+            if !self.if_range_matches(if_range) {
+                return None;
+            }
+        }
+
+        let ranges =
+            range::Range::from_header(req_headers.get(RANGE)?)?.satisfiable_ranges(complete_length);
+        (!ranges.is_empty()).then_some(ranges)
+    }
+
+    fn if_range_matches(&self, if_range: &HeaderValue) -> bool {
+        let if_range = match if_range.to_str() {
+            Ok(if_range) => if_range.trim(),
+            Err(_) => return false,
+        };
+
+        if if_range.starts_with('"') {
+            // An entity-tag is always compared with the strong comparison function
+            let if_range = EntityTags::new(if_range).next();
+            match (etag(&self.res), if_range) {
+                (Some(stored), Some(if_range)) => stored.strong_eq(if_range),
+                _ => false,
+            }
+        } else {
+            // A date must be an exact match for a strong Last-Modified
+            let last_modified =
+                strong_last_modified(&self.res).and(self.derived.dates.last_modified);
+            match (httpdate::parse_http_date(if_range), last_modified) {
+                (Ok(if_range), Some(last_modified)) => if_range == last_modified,
+                _ => false,
             }
         }
-        self.request_from_headers(headers)
     }
 
     fn request_from_headers(&self, headers: HeaderMap) -> http::request::Parts {
-        let mut parts = Request::builder()
-            .method(self.method.clone())
-            .uri(self.uri.clone())
-            .body(())
-            .unwrap()
-            .into_parts()
-            .0;
+        let mut parts = Request::new(()).into_parts().0;
+        parts.method = self.method.clone();
+        parts.uri = self.uri.clone();
         parts.headers = headers;
         parts
     }
@@ -615,26 +1913,30 @@ impl CachePolicy {
         let response_headers = response.headers();
         let mut response_status = response.status();
 
-        let old_etag = &self.res.get_str(&ETAG).map(str::trim);
-        let old_last_modified = response_headers.get_str(&LAST_MODIFIED).map(str::trim);
-        let new_etag = response_headers.get_str(&ETAG).map(str::trim);
+        let old_etag = etag(&self.res);
+        let old_last_modified = self.res.get_str(&LAST_MODIFIED).map(str::trim);
+        let new_etag = etag(response_headers);
         let new_last_modified = response_headers.get_str(&LAST_MODIFIED).map(str::trim);
 
         // These aren't going to be supported exactly, since one CachePolicy object
         // doesn't know about all the other cached objects.
         let mut matches = false;
-        if response.status() != StatusCode::NOT_MODIFIED {
+        if response.status() == StatusCode::PARTIAL_CONTENT {
+            // A 206 can only be combined with the stored response when both share the same strong
+            // validator. The stored (full) response is then refreshed like with a 304
+            matches = self.strong_validator_matches(response_headers);
+        } else if response.status() != StatusCode::NOT_MODIFIED {
             matches = false;
-        } else if new_etag.map_or(false, |etag| !etag.starts_with("W/")) {
+        } else if let Some(new) = new_etag.filter(|etag| !etag.is_weak()) {
             // "All of the stored responses with the same strong validator are selected.
             // If none of the stored responses contain the same strong validator,
             // then the cache MUST NOT use the new response to update any stored responses."
-            matches = old_etag.map(|e| e.trim_start_matches("W/")) == new_etag;
+            matches = old_etag.map_or(false, |old| old.weak_eq(new));
         } else if let (Some(old), Some(new)) = (old_etag, new_etag) {
             // "If the new response contains a weak validator and that validator corresponds
             // to one of the cache's stored responses,
             // then the most recent of those matching stored responses is selected for update."
-            matches = old.trim_start_matches("W/") == new.trim_start_matches("W/");
+            matches = old.weak_eq(new);
         } else if old_last_modified.is_some() {
             matches = old_last_modified == new_last_modified;
         } else {
@@ -651,25 +1953,32 @@ impl CachePolicy {
             }
         }
 
-        let new_response_headers = if matches {
-            let mut new_response_headers = HeaderMap::with_capacity(self.res.keys_len());
-            // use other header fields provided in the 304 (Not Modified) response to replace all instances
-            // of the corresponding header fields in the stored response.
-            for (header, old_value) in &self.res {
-                let header = header.clone();
-                if let Some(new_value) = response_headers.get(&header) {
-                    if !EXCLUDED_FROM_REVALIDATION_UPDATE.contains(&header.as_str()) {
-                        new_response_headers.insert(header, new_value.clone());
-                        continue;
-                    }
-                }
-                new_response_headers.insert(header, old_value.clone());
-            }
+        // A 5xx received while validating doesn't update the stored response, but it may still be
+        // served stale (rfc9111 4.3.3)
+        if response.status().is_server_error() {
+            let mut parts = Response::new(()).into_parts().0;
+            parts.status = response.status();
+            parts.headers = response_headers.clone();
+            self.observe(Decision::FailedRevalidation);
+            return AfterResponse::FailedRevalidation(self.clone(), parts);
+        }
+
+        // A 304 that doesn't correspond to the stored response can't be used to update it, and it
+        // has no body to store in its place
+        if !matches && response.status() == StatusCode::NOT_MODIFIED {
+            self.observe(Decision::Mismatch);
+            return AfterResponse::Mismatch(self.unconditional_request(request));
+        }
+
+        let mut new_response_headers = if matches {
             response_status = self.status;
-            new_response_headers
+            self.updated_response_headers(response_headers)
         } else {
             response_headers.clone()
         };
+        if let Some(date) = received_date(response_headers, response_time) {
+            new_response_headers.insert(DATE, date);
+        }
 
         let new_policy = CachePolicy::from_details(
             request.uri(),
@@ -678,24 +1987,288 @@ impl CachePolicy {
             request.headers().clone(),
             new_response_headers,
             response_time,
-            self.config,
-        );
+            self.config.clone(),
+        )
+        .with_body_digest(request.body_digest().map(Box::from));
+        let new_response = new_policy.cached_response(response_time);
+
+        if matches && response.status() == StatusCode::NOT_MODIFIED {
+            self.observe(Decision::NotModified);
+            AfterResponse::NotModified(new_policy, new_response)
+        } else if matches && response.status() == StatusCode::PARTIAL_CONTENT {
+            self.observe(Decision::Partial);
+            AfterResponse::Partial(new_policy, new_response)
+        } else {
+            self.observe(Decision::Modified);
+            AfterResponse::Modified(new_policy, new_response)
+        }
+    }
+
+    /// The stored request with `request`'s headers minus any preconditions, so that it fetches the
+    /// full resource
+    fn unconditional_request<Req: RequestLike>(&self, request: &Req) -> http::request::Parts {
+        let mut headers = request.headers().clone();
+        for name in [
+            IF_MATCH,
+            IF_NONE_MATCH,
+            IF_MODIFIED_SINCE,
+            IF_UNMODIFIED_SINCE,
+            IF_RANGE,
+        ] {
+            headers.remove(name);
+        }
+        self.request_from_headers(headers)
+    }
+
+    /// Freshens the stored `GET` response using the response to a `HEAD` request (rfc9111 4.3.5)
+    ///
+    /// Since a `HEAD` response has no body it can only ever update the stored response's metadata.
+    ///
+    /// Returns the updated policy along with the response parts to store when the `HEAD` response
+    /// is a `200` or `304` that describes the same representation. Returns `None` when the
+    /// `Content-Length`, `ETag`, or `Last-Modified` show that the representation changed, in which
+    /// case the stored response should be invalidated.
+    ///
+    /// Anything else says nothing about the stored response, so the policy and stored response are
+    /// returned unmodified e.g. when the stored response isn't for a `GET`, the request isn't a
+    /// `HEAD`, the `HEAD` response has another status, or the stored response wasn't selected for
+    /// the request because of another URI or different `Vary` headers.
+    pub fn after_head_response<Req: RequestLike, Res: ResponseLike>(
+        &self,
+        request: &Req,
+        response: &Res,
+        response_time: SystemTime,
+    ) -> Option<(CachePolicy, http::response::Parts)> {
+        // The method differs from the stored GET by design, so only the rest of the request is
+        // compared
+        if self.method != Method::GET
+            || *request.method() != Method::HEAD
+            || !matches!(response.status(), StatusCode::OK | StatusCode::NOT_MODIFIED)
+            || self.request_mismatch(request).is_some()
+        {
+            return Some((self.clone(), self.stored_response_parts()));
+        }
+
+        let response_headers = response.headers();
+        let differs =
+            |name: &HeaderName| match (self.res.get_str(name), response_headers.get_str(name)) {
+                (Some(old), Some(new)) => old.trim() != new.trim(),
+                _ => false,
+            };
+        let date_differs = |name: &HeaderName| {
+            let parse = |headers: &HeaderMap| {
+                headers
+                    .get_str(name)
+                    .and_then(|date| httpdate::parse_http_date(date).ok())
+            };
+            match (parse(&self.res), parse(response_headers)) {
+                (Some(old), Some(new)) => old != new,
+                _ => false,
+            }
+        };
+        if differs(&CONTENT_LENGTH) || differs(&ETAG) || date_differs(&LAST_MODIFIED) {
+            return None;
+        }
+
+        let mut new_response_headers = self.updated_response_headers(response_headers);
+        if let Some(date) = received_date(response_headers, response_time) {
+            new_response_headers.insert(DATE, date);
+        }
+        // The stored request is kept since it's the one the GET response was selected with
+        let new_policy = CachePolicy::from_details(
+            self.uri.clone(),
+            self.method.clone(),
+            self.status,
+            Arc::clone(&self.req),
+            new_response_headers,
+            response_time,
+            self.config.clone(),
+        )
+        .with_body_digest(self.body_digest.clone());
         let new_response = new_policy.cached_response(response_time);
+        Some((new_policy, new_response))
+    }
+
+    /// Uses the header fields provided in a 304 (Not Modified) or similar response to replace all
+    /// instances of the corresponding header fields in the stored response
+    fn updated_response_headers(&self, response_headers: &HeaderMap) -> HeaderMap {
+        let mut new_response_headers = HeaderMap::with_capacity(self.res.keys_len());
+        for (header, old_value) in self.res.iter() {
+            let header = header.clone();
+            if let Some(new_value) = response_headers.get(&header) {
+                if !EXCLUDED_FROM_REVALIDATION_UPDATE.contains(&header.as_str()) {
+                    new_response_headers.insert(header, new_value.clone());
+                    continue;
+                }
+            }
+            new_response_headers.insert(header, old_value.clone());
+        }
+        new_response_headers
+    }
+
+    /// Evaluates the preconditions of a client's conditional request against the stored response
+    ///
+    /// This lets a cache answer `If-None-Match`/`If-Modified-Since` (and `If-Match`/
+    /// `If-Unmodified-Since`) from downstream clients itself, following the precedence from
+    /// rfc9110 13.2.2. It assumes that the stored response has already been selected for `req`
+    /// e.g. through [`before_request`][Self::before_request].
+    pub fn evaluate_conditional<Req: RequestLike>(
+        &self,
+        req: &Req,
+        now: SystemTime,
+    ) -> Conditional {
+        let req_headers = req.headers();
+        let is_get_or_head = *req.method() == Method::GET || *req.method() == Method::HEAD;
+        let last_modified = self.derived.dates.last_modified;
+
+        if req_headers.contains_key(IF_MATCH) {
+            // If-Match uses the strong comparison function
+            let stored = etag(&self.res);
+            let matches = entity_tags(req_headers.get_all(IF_MATCH))
+                .any(|tag| tag.is_any() || stored.map_or(false, |stored| stored.strong_eq(tag)));
+            if !matches {
+                return Conditional::PreconditionFailed;
+            }
+        } else if let Some(since) = req_headers
+            .get_str(&IF_UNMODIFIED_SINCE)
+            .and_then(|date| httpdate::parse_http_date(date).ok())
+        {
+            if last_modified.map_or(false, |modified| modified > since) {
+                return Conditional::PreconditionFailed;
+            }
+        }
+
+        if req_headers.contains_key(IF_NONE_MATCH) {
+            // If-None-Match uses the weak comparison function
+            let stored = etag(&self.res);
+            let matches = entity_tags(req_headers.get_all(IF_NONE_MATCH))
+                .any(|tag| tag.is_any() || stored.map_or(false, |stored| stored.weak_eq(tag)));
+            return if !matches {
+                Conditional::Full
+            } else if is_get_or_head {
+                Conditional::NotModified(self.not_modified_response(now))
+            } else {
+                Conditional::PreconditionFailed
+            };
+        }
+
+        if is_get_or_head {
+            if let Some(since) = req_headers
+                .get_str(&IF_MODIFIED_SINCE)
+                .and_then(|date| httpdate::parse_http_date(date).ok())
+            {
+                // Without a Last-Modified fall back to the Date, and then to when it was received
+                // (rfc9111 4.3.2)
+                let modified = last_modified.unwrap_or_else(|| self.date());
+                if modified <= since {
+                    return Conditional::NotModified(self.not_modified_response(now));
+                }
+            }
+        }
+
+        Conditional::Full
+    }
+
+    /// The stored response trimmed down to what a 304 (Not Modified) response carries
+    /// (rfc9110 15.4.5)
+    fn not_modified_response(&self, now: SystemTime) -> http::response::Parts {
+        const NOT_MODIFIED_HEADERS: &[HeaderName] = &[
+            AGE,
+            CACHE_CONTROL,
+            CONTENT_LOCATION,
+            DATE,
+            ETAG,
+            EXPIRES,
+            VARY,
+        ];
+
+        let mut parts = self.cached_response(now);
+        parts.status = StatusCode::NOT_MODIFIED;
+        let mut headers = HeaderMap::with_capacity(NOT_MODIFIED_HEADERS.len());
+        for name in NOT_MODIFIED_HEADERS {
+            for value in parts.headers.get_all(name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+        parts.headers = headers;
+        parts
+    }
+
+    /// The URIs that a cache should invalidate after forwarding `req` and receiving `res`
+    ///
+    /// A cache MUST invalidate the target URI when it receives a non-error status code in response
+    /// to an unsafe request method, and it MAY invalidate the URIs from the `Location` and
+    /// `Content-Location` response headers when they share the target URI's origin
+    /// (rfc9111 4.4).
+    ///
+    /// Returns an empty list for safe methods and error responses. Relative references are
+    /// resolved against the target URI.
+    pub fn invalidation_targets<Req: RequestLike, Res: ResponseLike>(
+        req: &Req,
+        res: &Res,
+    ) -> Vec<Uri> {
+        let status = res.status();
+        if req.method().is_safe() || !(status.is_success() || status.is_redirection()) {
+            return Vec::new();
+        }
 
-        if matches && response.status() == StatusCode::NOT_MODIFIED {
-            AfterResponse::NotModified(new_policy, new_response)
-        } else {
-            AfterResponse::Modified(new_policy, new_response)
+        let target = req.uri();
+        let host = req.headers().get(HOST);
+        let mut targets = vec![target.clone()];
+        for name in [LOCATION, CONTENT_LOCATION] {
+            let uri = res
+                .headers()
+                .get_str(&name)
+                .and_then(|reference| resolve_reference(&target, reference.trim()));
+            if let Some(uri) = uri {
+                if is_same_origin(&target, host, &uri) && !targets.contains(&uri) {
+                    targets.push(uri);
+                }
+            }
         }
+        targets
     }
 }
 
 /// TODO
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AfterResponse {
     /// TODO
-    NotModified(CachePolicy, http::response::Parts),
+    NotModified(
+        CachePolicy,
+        #[cfg_attr(feature = "serde", serde(with = "serde_parts::response"))] http::response::Parts,
+    ),
     /// TODO
-    Modified(CachePolicy, http::response::Parts),
+    Modified(
+        CachePolicy,
+        #[cfg_attr(feature = "serde", serde(with = "serde_parts::response"))] http::response::Parts,
+    ),
+    /// A 206 (Partial Content) response that shares a strong validator with the stored response
+    ///
+    /// The partial body can be combined with (or used to extend) the stored body. The refreshed
+    /// policy and response parts describe the stored representation, not the partial response
+    Partial(
+        CachePolicy,
+        #[cfg_attr(feature = "serde", serde(with = "serde_parts::response"))] http::response::Parts,
+    ),
+    /// A 304 (Not Modified) response whose validators don't correspond to the stored response
+    ///
+    /// Neither response can be used, so the full resource needs to be requested again with the
+    /// provided unconditional request
+    Mismatch(
+        #[cfg_attr(feature = "serde", serde(with = "serde_parts::request"))] http::request::Parts,
+    ),
+    /// A 5xx (Server Error) response received while revalidating
+    ///
+    /// The stored policy is returned unchanged along with the error response. The error can be
+    /// forwarded, or the stored response can be served with
+    /// [`CachePolicy::stale_response()`] e.g. while
+    /// [`CachePolicy::stale_if_error_until()`] allows it
+    FailedRevalidation(
+        CachePolicy,
+        #[cfg_attr(feature = "serde", serde(with = "serde_parts::response"))] http::response::Parts,
+    ),
 }
 
 impl AfterResponse {
@@ -703,6 +2276,198 @@ impl AfterResponse {
     pub fn is_modified(&self) -> bool {
         matches!(self, Self::Modified(..))
     }
+
+    /// The new policy unless the response was a [`Mismatch`][Self::Mismatch]
+    pub fn policy(&self) -> Option<&CachePolicy> {
+        match self {
+            Self::NotModified(policy, _)
+            | Self::Modified(policy, _)
+            | Self::Partial(policy, _)
+            | Self::FailedRevalidation(policy, _) => Some(policy),
+            Self::Mismatch(_) => None,
+        }
+    }
+
+    /// Summarizes how the new policy differs from the `stored` one that produced it
+    ///
+    /// This lets a cache skip rewriting entries whose stored headers didn't actually change.
+    /// Returns `None` for a [`Mismatch`][Self::Mismatch] since there's nothing to update.
+    pub fn update_metadata(&self, stored: &CachePolicy, now: SystemTime) -> Option<UpdateMetadata> {
+        let policy = self.policy()?;
+        let mut changed_headers = Vec::new();
+        for name in stored.res.keys().chain(policy.res.keys()) {
+            let differs = !stored
+                .res
+                .get_all(name)
+                .iter()
+                .eq(policy.res.get_all(name).iter());
+            if differs && !changed_headers.contains(name) {
+                changed_headers.push(name.clone());
+            }
+        }
+        changed_headers.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+
+        Some(UpdateMetadata {
+            time_to_live: policy.time_to_live(now),
+            status_changed: stored.status != policy.status,
+            changed_headers,
+        })
+    }
+}
+
+/// How a policy changed after a response
+///
+/// See [`AfterResponse::update_metadata()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UpdateMetadata {
+    /// The new policy's time to live
+    pub time_to_live: Duration,
+    /// If the stored status changed
+    pub status_changed: bool,
+    /// The names of the stored response headers that were added, removed, or changed
+    pub changed_headers: Vec<HeaderName>,
+}
+
+impl UpdateMetadata {
+    /// If anything about the stored response changed
+    pub fn is_changed(&self) -> bool {
+        self.status_changed || !self.changed_headers.is_empty()
+    }
+}
+
+/// Resolves a (possibly relative) URI reference against `base` (rfc3986 5.2)
+///
+/// Dot-segments are left as-is
+fn resolve_reference(base: &Uri, reference: &str) -> Option<Uri> {
+    if reference.is_empty() {
+        return None;
+    }
+    if reference.contains("://") {
+        return reference.parse().ok();
+    }
+    if let Some(network_path) = reference.strip_prefix("//") {
+        let scheme = base.scheme_str().unwrap_or("http");
+        return format!("{scheme}://{network_path}").parse().ok();
+    }
+
+    let path_and_query = if reference.starts_with('/') {
+        reference.to_owned()
+    } else if reference.starts_with('?') {
+        format!("{}{reference}", base.path())
+    } else {
+        let base_path = base.path();
+        let dir = base_path.rfind('/').map_or("/", |i| &base_path[..=i]);
+        format!("{dir}{reference}")
+    };
+    let mut builder = Uri::builder();
+    if let (Some(scheme), Some(authority)) = (base.scheme(), base.authority()) {
+        builder = builder.scheme(scheme.clone()).authority(authority.clone());
+    }
+    builder.path_and_query(path_and_query).build().ok()
+}
+
+/// `METHOD uri` with the uri's scheme and host lowercased and any default port dropped
+///
+/// The authority falls back to the `Host` header for URIs without one, and the body digest gets
+/// appended in hex when present
+fn primary_key(
+    method: &Method,
+    uri: &Uri,
+    host: Option<&HeaderValue>,
+    body_digest: Option<&[u8]>,
+) -> String {
+    let mut key = format!("{method} ");
+    if let Some(scheme) = uri.scheme_str() {
+        key.push_str(&scheme.to_ascii_lowercase());
+        key.push_str("://");
+    }
+    match uri.authority() {
+        Some(authority) => {
+            key.push_str(&authority.host().to_ascii_lowercase());
+            let default_port = match uri.scheme_str() {
+                Some(scheme) if scheme.eq_ignore_ascii_case("http") => Some(80),
+                Some(scheme) if scheme.eq_ignore_ascii_case("https") => Some(443),
+                _ => None,
+            };
+            if let Some(port) = uri.port_u16().filter(|&port| Some(port) != default_port) {
+                key.push_str(&format!(":{port}"));
+            }
+        }
+        None => {
+            if let Some(host) = host.and_then(|host| host.to_str().ok()) {
+                key.push_str(&host.trim().to_ascii_lowercase());
+            }
+        }
+    }
+    key.push_str(uri.path_and_query().map_or("/", |path| path.as_str()));
+
+    if let Some(digest) = body_digest {
+        key.push(' ');
+        for byte in digest {
+            key.push_str(&format!("{byte:02x}"));
+        }
+    }
+    key
+}
+
+/// The URI's authority falling back to the `Host` header (rfc9110 7.1)
+fn effective_authority<'a>(uri: &'a Uri, headers: &'a HeaderMap) -> Option<&'a str> {
+    match uri.authority() {
+        Some(authority) => Some(authority.as_str()),
+        None => headers.get_str(&HOST).map(str::trim),
+    }
+}
+
+/// If `uri` shares an origin with `target` whose authority falls back to the `Host` header
+fn is_same_origin(target: &Uri, host: Option<&HeaderValue>, uri: &Uri) -> bool {
+    let authority = match uri.authority() {
+        Some(authority) => authority,
+        // Resolved against the target, so it can only be on the same origin
+        None => return true,
+    };
+    if let (Some(target_scheme), Some(scheme)) = (target.scheme(), uri.scheme()) {
+        if target_scheme != scheme {
+            return false;
+        }
+    }
+    match target.authority() {
+        Some(target_authority) => target_authority == authority,
+        None => host
+            .and_then(|host| host.to_str().ok())
+            .map_or(false, |host| authority == host.trim()),
+    }
+}
+
+/// The entity-tag of a response
+fn etag(headers: &HeaderMap) -> Option<EntityTag<'_>> {
+    entity_tags(headers.get(ETAG)).next()
+}
+
+fn strong_etag(headers: &HeaderMap) -> Option<EntityTag<'_>> {
+    etag(headers).filter(|etag| !etag.is_weak())
+}
+
+/// A Last-Modified date is only strong when the server's Date is at least one second after it
+/// (rfc9110 8.8.2.2)
+fn strong_last_modified(headers: &HeaderMap) -> Option<&HeaderValue> {
+    let last_modified = headers.get(LAST_MODIFIED)?;
+    let modified = date_header(headers, &LAST_MODIFIED)??;
+    let date = date_header(headers, &DATE)??;
+    let is_strong = date
+        .duration_since(modified)
+        .map_or(false, |diff| diff >= Duration::from_secs(1));
+    is_strong.then_some(last_modified)
+}
+
+/// Parses a date header, where `Some(None)` is a header that isn't a valid HTTP date
+fn date_header(headers: &HeaderMap, name: &HeaderName) -> Option<Option<SystemTime>> {
+    let value = headers.get(name)?;
+    let date = value
+        .to_str()
+        .ok()
+        .and_then(|date| httpdate::parse_http_date(date.trim()).ok());
+    Some(date)
 }
 
 fn get_all_comma<'a>(
@@ -724,6 +2489,72 @@ impl GetHeaderStr for HeaderMap {
     }
 }
 
+/// A header value for text assembled from other header values e.g. with [`join()`]
+///
+/// Characters that aren't allowed in a header value are dropped instead of panicking
+fn text_value(mut text: String) -> HeaderValue {
+    if !text.chars().all(is_value_char) {
+        text.retain(is_value_char);
+    }
+    HeaderValue::try_from(text).unwrap_or_else(|_| HeaderValue::from_static(""))
+}
+
+fn is_value_char(c: char) -> bool {
+    c == '\t' || (c >= ' ' && c != '\x7f')
+}
+
+/// How header values are written when a response or request gets rewritten
+#[derive(Debug, Clone, Copy)]
+enum Rewrite {
+    /// Whatever can't be written is dropped or clamped, see [`text_value()`] and [`date_value()`]
+    Lossy,
+    /// Whatever can't be written is an [`Error`]
+    Strict,
+}
+
+impl Rewrite {
+    fn text(self, name: &HeaderName, text: String) -> Result<HeaderValue, Error> {
+        match self {
+            Self::Lossy => Ok(text_value(text)),
+            Self::Strict if text.chars().all(is_value_char) => Ok(text_value(text)),
+            Self::Strict => Err(Error::HeaderValue(name.clone())),
+        }
+    }
+
+    fn date(self, time: SystemTime) -> Result<HeaderValue, Error> {
+        match self {
+            Self::Strict if time < UNIX_EPOCH || time > max_http_date() => Err(Error::Date(time)),
+            Self::Lossy | Self::Strict => Ok(date_value(time)),
+        }
+    }
+}
+
+/// Unwraps the outcome of a [`Rewrite::Lossy`] rewrite, which never fails
+fn lossy<T>(result: Result<T, Error>) -> T {
+    result.expect("Lossy rewrites drop what they can't write")
+}
+
+/// The `Date` to add to a response that was received without one
+///
+/// A recipient with a clock that receives a response message without a Date header field MUST
+/// record the time it was received and append a corresponding Date header field to the message's
+/// header section if it is cached or forwarded downstream (rfc9110 6.6.1)
+fn received_date(received: &HeaderMap, response_time: SystemTime) -> Option<HeaderValue> {
+    (!received.contains_key(DATE)).then(|| date_value(response_time))
+}
+
+/// An HTTP-date, where times that can't be formatted are clamped to the supported range
+fn date_value(time: SystemTime) -> HeaderValue {
+    text_value(httpdate::fmt_http_date(
+        time.clamp(UNIX_EPOCH, max_http_date()),
+    ))
+}
+
+/// The last second of the year 9999, which is the latest time an HTTP-date can hold
+fn max_http_date() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(253_402_300_799)
+}
+
 fn join<'a>(parts: impl Iterator<Item = &'a str>) -> String {
     let mut out = String::new();
     for part in parts {
@@ -736,19 +2567,350 @@ fn join<'a>(parts: impl Iterator<Item = &'a str>) -> String {
     out
 }
 
+/// Whether a response can be stored, and if not then why
+///
+/// See [`CachePolicy::storability()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Storability {
+    /// The response can be stored
+    Storable,
+    /// The request has the `no-store` directive
+    RequestNoStore,
+    /// The request method isn't defined as cacheable
+    ///
+    /// See [`Config::cacheable_methods`]
+    UncacheableMethod,
+    /// A `QUERY` request, or one using a method in [`Config::body_digest_methods`], didn't provide
+    /// a [`RequestLike::body_digest()`]
+    MissingBodyDigest,
+    /// The response status isn't understood by the cache
+    ///
+    /// See [`Config::understood_statuses`]
+    StatusNotUnderstood,
+    /// The response has the `no-store` directive
+    ResponseNoStore,
+    /// The response has the `private` directive and the cache is shared
+    Private,
+    /// The request has an `Authorization` header and the cache is shared, but the response
+    /// doesn't explicitly allow storing it
+    Authorization,
+    /// The response has neither explicit freshness information nor a status that's heuristically
+    /// cacheable, or the request method requires explicit freshness information
+    NoExplicitExpiration,
+    /// A response header field is larger than [`Config::max_stored_header_bytes`]
+    ///
+    /// See [`Config::oversized_headers`]
+    HeadersTooLarge,
+}
+
+impl Storability {
+    /// If the response can be stored
+    pub fn is_storable(self) -> bool {
+        self == Self::Storable
+    }
+}
+
+/// The outcome of evaluating a client's conditional request against a stored response
+///
+/// See [`CachePolicy::evaluate_conditional()`]
+#[derive(Debug)]
+pub enum Conditional {
+    /// The request has no applicable preconditions, so send the full stored response
+    Full,
+    /// Send a 304 (Not Modified) response using these parts
+    ///
+    /// The headers are already limited to the ones that a 304 response must include
+    NotModified(http::response::Parts),
+    /// Send a 412 (Precondition Failed) response
+    PreconditionFailed,
+}
+
+impl Conditional {
+    /// If the client's copy is still valid
+    pub fn is_not_modified(&self) -> bool {
+        matches!(self, Self::NotModified(_))
+    }
+}
+
 /// TODO
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BeforeRequest {
     /// TODO
-    Fresh(http::response::Parts),
+    Fresh(
+        #[cfg_attr(feature = "serde", serde(with = "serde_parts::response"))] http::response::Parts,
+    ),
     /// TODO
     Stale {
         /// TODO
+        #[cfg_attr(feature = "serde", serde(with = "serde_parts::request"))]
         request: http::request::Parts,
         /// TODO
         matches: bool,
+        /// Why the stored response couldn't be used as-is
+        reason: StaleReason,
+    },
+}
+
+/// The outcome of [`CachePolicy::before_request_mut()`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BeforeRequestMut {
+    /// The stored response can be used as-is
+    Fresh(
+        #[cfg_attr(feature = "serde", serde(with = "serde_parts::response"))] http::response::Parts,
+    ),
+    /// The request has to go to the origin, and was updated in place to do so
+    Stale {
+        /// If the request matched the stored one
+        matches: bool,
+        /// Why the stored response couldn't be used as-is
+        reason: StaleReason,
     },
 }
 
+impl BeforeRequestMut {
+    /// If the stored response can be used as-is
+    pub fn is_fresh(&self) -> bool {
+        matches!(self, Self::Fresh(_))
+    }
+}
+
+/// A header value that [`CachePolicy`]'s `try_` methods can't write without changing it
+///
+/// The methods without the `try_` prefix drop the characters that a header value can't hold and
+/// clamp times to the range of an HTTP-date instead
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The rewritten value of this header has characters that a header value can't hold
+    HeaderValue(HeaderName),
+    /// A time before 1970 or after 9999, which an HTTP-date can't hold
+    Date(SystemTime),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HeaderValue(name) => write!(f, "invalid rewritten value for {name}"),
+            Self::Date(_) => f.write_str("time out of range for an HTTP-date"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// The keys that a storage layer can index an entry by
+///
+/// The primary key is the method and normalized URI (plus the body digest for methods keyed on
+/// it). Several variants of a resource share a primary key when the response has a `Vary` header,
+/// and the secondary key tells them apart by the values of the nominated request headers.
+///
+/// Use [`CachePolicy::primary_key()`] and [`CachePolicy::secondary_key()`] to compute the keys for
+/// a new request. Matching keys are a prerequisite for reuse, but
+/// [`CachePolicy::before_request()`] still has the final say.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    primary: String,
+    secondary: String,
+}
+
+impl CacheKey {
+    /// The method and normalized URI e.g. `GET https://example.com/a`
+    pub fn primary(&self) -> &str {
+        &self.primary
+    }
+
+    /// The `Vary`-selected request headers
+    ///
+    /// Each nominated header is on its own line as `name=value`, or just `name` when it was absent.
+    /// This is empty without a `Vary` header and `*` for `Vary: *` which never matches.
+    pub fn secondary(&self) -> &str {
+        &self.secondary
+    }
+}
+
+/// A one-line summary of a [`CachePolicy`]
+///
+/// See [`CachePolicy::summary()`]
+#[derive(Debug, Clone, Copy)]
+pub struct PolicySummary<'a> {
+    policy: &'a CachePolicy,
+    now: SystemTime,
+}
+
+impl fmt::Display for PolicySummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { policy, now } = *self;
+        write!(f, "{} {} {}", policy.method, policy.uri, policy.status)?;
+        match policy.storability() {
+            Storability::Storable => {
+                let ttl = policy.time_to_live(now).as_secs();
+                write!(f, " storable ttl={ttl}s")?;
+            }
+            reason => write!(f, " not storable ({reason:?})")?,
+        }
+        if let Some(etag) = policy.res.get_str(&ETAG) {
+            write!(f, " etag={}", etag.trim())?;
+        }
+        if let Some(last_modified) = policy.res.get_str(&LAST_MODIFIED) {
+            write!(f, " last-modified=\"{}\"", last_modified.trim())?;
+        }
+        Ok(())
+    }
+}
+
+/// Identifies a stored entry for deduplication
+///
+/// See [`CachePolicy::identity()`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PolicyIdentity {
+    key: CacheKey,
+    status: u16,
+    etag: Option<Box<str>>,
+    last_modified: Option<Box<str>>,
+    directives: Vec<(Box<str>, Option<Box<str>>)>,
+}
+
+/// A report of how a [`CachePolicy`] evaluates a request
+///
+/// See [`CachePolicy::explain()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Explanation {
+    /// Whether the stored response is storable
+    pub storability: Storability,
+    /// The stored response's `Cache-Control` directives sorted by name
+    pub response_directives: Vec<(String, Option<String>)>,
+    /// The presented request's `Cache-Control` directives sorted by name
+    pub request_directives: Vec<(String, Option<String>)>,
+    /// What determined the freshness lifetime
+    pub freshness_source: FreshnessSource,
+    /// How long the stored response is fresh for in total
+    pub freshness_lifetime: Duration,
+    /// The current age of the stored response
+    pub age: Duration,
+    /// How much longer the stored response is fresh for
+    pub time_to_live: Duration,
+    /// Why the stored response can't be used for the request as-is, or `None` when it can
+    pub stale_reason: Option<StaleReason>,
+}
+
+/// What determined the freshness lifetime of a stored response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FreshnessSource {
+    /// The response isn't storable, so it's never fresh
+    NotStorable,
+    /// The response has the `no-cache` directive or pragma
+    NoCache,
+    /// The response sets cookies without being `public` or `immutable`, and the cache is shared
+    SetCookie,
+    /// The response has `Vary: *`
+    VaryStar,
+    /// The response has the `proxy-revalidate` directive, and the cache is shared
+    ProxyRevalidate,
+    /// The response's `s-maxage` directive, and the cache is shared
+    SMaxAge,
+    /// The response's `max-age` directive
+    MaxAge,
+    /// The response's `Expires` header
+    Expires,
+    /// A heuristic based on the response's `Last-Modified` header
+    ///
+    /// See [`Config::last_modified`]
+    LastModifiedHeuristic,
+    /// The response is an error without explicit freshness information, so
+    /// [`Config::client_error_ttl`] or [`Config::server_error_ttl`] applies
+    NegativeTtl,
+    /// Nothing in the response indicates a freshness lifetime, so [`Config::default_ttl`] applies
+    DefaultTtl,
+    /// Nothing in the response indicates a freshness lifetime
+    Unknown,
+}
+
+/// Why a stored response can't be used for a request without going to the origin
+///
+/// The mismatch reasons (e.g. [`StaleReason::UriMismatch`]) mean that the stored response wasn't
+/// for the presented request at all, while the rest mean that it was, but needs revalidation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum StaleReason {
+    /// The request URI differs from the stored one
+    UriMismatch,
+    /// The request `Host` header differs from the stored one
+    HostMismatch,
+    /// The request method differs from the stored one
+    MethodMismatch,
+    /// A request header nominated by the stored response's `Vary` differs from the stored one
+    VaryMismatch,
+    /// The request's `Authorization` differs from the one that the stored response was obtained
+    /// with in a shared cache, and the response isn't `public`
+    AuthorizationMismatch,
+    /// The request body digest differs from the stored one
+    BodyDigestMismatch,
+    /// The request has the `no-cache` directive or pragma
+    RequestNoCache,
+    /// The stored response is older than the request's `max-age` directive allows
+    RequestMaxAge,
+    /// The stored response won't be fresh for as long as the request's `min-fresh` directive asks
+    RequestMinFresh,
+    /// The stored response is stale, and the request doesn't accept stale responses
+    Expired,
+}
+
+impl StaleReason {
+    /// If the stored response wasn't for the presented request at all
+    pub fn is_mismatch(self) -> bool {
+        matches!(
+            self,
+            Self::UriMismatch
+                | Self::HostMismatch
+                | Self::MethodMismatch
+                | Self::VaryMismatch
+                | Self::AuthorizationMismatch
+                | Self::BodyDigestMismatch
+        )
+    }
+}
+
+/// How a stored response can be used for a request
+///
+/// See [`CachePolicy::freshness()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Freshness {
+    /// The stored response can be used as-is
+    Fresh,
+    /// The stored response is stale, but it can still be used under some conditions
+    StaleButUsable(StaleAllowance),
+    /// The stored response can't be used without revalidating it first
+    MustRevalidate(StaleReason),
+}
+
+impl Freshness {
+    /// If the stored response can be used as-is
+    pub fn is_fresh(self) -> bool {
+        self == Self::Fresh
+    }
+}
+
+/// What allows a stale response to be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StaleAllowance {
+    /// The request's `max-stale` directive accepts it, so it can be used as-is
+    MaxStale,
+    /// The response's `stale-while-revalidate` directive allows using it while revalidating in the
+    /// background
+    StaleWhileRevalidate,
+    /// The response's `stale-if-error` directive allows using it if revalidation fails with an
+    /// error
+    StaleIfError,
+}
+
 impl BeforeRequest {
     /// TODO
     pub fn is_fresh(&self) -> bool {
@@ -756,6 +2918,13 @@ impl BeforeRequest {
     }
 }
 
+/// Evaluates a response received just now, see [`CachePolicy::from_owned_parts()`]
+impl From<(http::request::Parts, http::response::Parts)> for CachePolicy {
+    fn from((req, res): (http::request::Parts, http::response::Parts)) -> Self {
+        Self::from_owned_parts(req, res, SystemTime::now(), Config::default())
+    }
+}
+
 /// TODO
 pub trait RequestLike {
     /// TODO
@@ -766,6 +2935,59 @@ pub trait RequestLike {
     fn method(&self) -> &Method;
     /// TODO
     fn headers(&self) -> &HeaderMap;
+    /// A digest of the request content for methods whose responses are keyed on it e.g. `QUERY`
+    ///
+    /// The digest is part of the [cache key][CachePolicy::cache_key] and requests only match a
+    /// stored response with the same one, so it also keeps responses to e.g. `POST` requests with
+    /// different content apart. See [`Config::body_digest_methods`] for requiring one.
+    ///
+    /// Any digest works as long as the same one is used consistently. Defaults to `None`, see
+    /// [`WithBodyDigest`] for attaching one to an existing request.
+    fn body_digest(&self) -> Option<&[u8]> {
+        None
+    }
+}
+
+/// Attaches a digest of the request content to a request
+///
+/// ```
+/// use http::{Method, Request};
+/// use http_cache_policy::{RequestLike, WithBodyDigest};
+///
+/// let req = Request::builder().method("QUERY").body(()).unwrap();
+/// let digest = [0xab; 32]; // e.g. a SHA-256 of the request content
+/// let req = WithBodyDigest::new(&req, &digest);
+/// assert_eq!(req.body_digest(), Some(&digest[..]));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct WithBodyDigest<'a, Req> {
+    request: &'a Req,
+    digest: &'a [u8],
+}
+
+impl<'a, Req: RequestLike> WithBodyDigest<'a, Req> {
+    /// Attaches `digest` to `request`
+    pub fn new(request: &'a Req, digest: &'a [u8]) -> Self {
+        Self { request, digest }
+    }
+}
+
+impl<Req: RequestLike> RequestLike for WithBodyDigest<'_, Req> {
+    fn uri(&self) -> Uri {
+        self.request.uri()
+    }
+    fn is_same_uri(&self, other: &Uri) -> bool {
+        self.request.is_same_uri(other)
+    }
+    fn method(&self) -> &Method {
+        self.request.method()
+    }
+    fn headers(&self) -> &HeaderMap {
+        self.request.headers()
+    }
+    fn body_digest(&self) -> Option<&[u8]> {
+        Some(self.digest)
+    }
 }
 
 /// TODO
@@ -812,6 +3034,60 @@ impl RequestLike for (Uri, Method, HeaderMap) {
     }
 }
 
+impl<'a> RequestLike for (&'a Method, &'a Uri, &'a HeaderMap) {
+    fn uri(&self) -> Uri {
+        self.1.to_owned()
+    }
+
+    fn is_same_uri(&self, other: &Uri) -> bool {
+        self.1 == other
+    }
+
+    fn method(&self) -> &Method {
+        self.0
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.2
+    }
+}
+
+impl RequestLike for (Method, Uri, &HeaderMap) {
+    fn uri(&self) -> Uri {
+        self.1.clone()
+    }
+
+    fn is_same_uri(&self, other: &Uri) -> bool {
+        &self.1 == other
+    }
+
+    fn method(&self) -> &Method {
+        &self.0
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.2
+    }
+}
+
+impl RequestLike for (Method, Uri, Arc<HeaderMap>) {
+    fn uri(&self) -> Uri {
+        self.1.clone()
+    }
+
+    fn is_same_uri(&self, other: &Uri) -> bool {
+        &self.1 == other
+    }
+
+    fn method(&self) -> &Method {
+        &self.0
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        &self.2
+    }
+}
+
 impl ResponseLike for (StatusCode, &HeaderMap) {
     fn status(&self) -> StatusCode {
         self.0
@@ -832,6 +3108,16 @@ impl ResponseLike for (StatusCode, HeaderMap) {
     }
 }
 
+impl ResponseLike for (StatusCode, Arc<HeaderMap>) {
+    fn status(&self) -> StatusCode {
+        self.0
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        &self.1
+    }
+}
+
 impl<Body> RequestLike for Request<Body> {
     fn uri(&self) -> Uri {
         self.uri().clone()
@@ -910,3 +3196,34 @@ impl ResponseLike for reqwest::Response {
         self.headers()
     }
 }
+
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest-blocking")))]
+#[cfg(feature = "reqwest-blocking")]
+impl RequestLike for reqwest::blocking::Request {
+    fn uri(&self) -> Uri {
+        self.url()
+            .as_str()
+            .parse()
+            .expect("Uri and Url are incompatible!?")
+    }
+    fn is_same_uri(&self, other: &Uri) -> bool {
+        self.url().as_str() == other
+    }
+    fn method(&self) -> &Method {
+        self.method()
+    }
+    fn headers(&self) -> &HeaderMap {
+        self.headers()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest-blocking")))]
+#[cfg(feature = "reqwest-blocking")]
+impl ResponseLike for reqwest::blocking::Response {
+    fn status(&self) -> StatusCode {
+        self.status()
+    }
+    fn headers(&self) -> &HeaderMap {
+        self.headers()
+    }
+}
@@ -0,0 +1,82 @@
+//! Hooks for observing the decisions that policies make
+//!
+//! A [`DecisionObserver`] set through [`Config::observer()`][crate::Config::observer] is called
+//! with every evaluation outcome, which makes it easy to feed whatever metrics system is in use
+//! e.g. hit, miss, and stale counters per host.
+//!
+//! ```
+//! use std::sync::{
+//!     atomic::{AtomicUsize, Ordering},
+//!     Arc,
+//! };
+//!
+//! use http::{header, Request, Response};
+//! use http_cache_policy::{observer::Decision, CachePolicy, Config};
+//!
+//! let fresh = Arc::new(AtomicUsize::new(0));
+//! let counter = Arc::clone(&fresh);
+//! let config = Config::default().observer(move |_: &CachePolicy, decision: Decision| {
+//!     if decision == Decision::Fresh {
+//!         counter.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! });
+//!
+//! let req = Request::get("https://example.com/a").body(()).unwrap();
+//! let res = Response::builder().header(header::CACHE_CONTROL, "max-age=60").body(()).unwrap();
+//! let now = http_cache_policy::now();
+//! let policy = CachePolicy::with_config(&req, &res, now, config);
+//! policy.before_request(&req, now);
+//! assert_eq!(fresh.load(Ordering::Relaxed), 1);
+//! ```
+
+use std::fmt;
+
+use crate::{CachePolicy, StaleReason, Storability};
+
+/// Called with the outcome of every decision that a policy makes
+///
+/// This is implemented for closures taking the policy and the [`Decision`]. Observers are called
+/// synchronously, so they should be cheap e.g. bumping counters
+pub trait DecisionObserver: Send + Sync {
+    /// Observes a `decision` made by `policy`
+    fn observe(&self, policy: &CachePolicy, decision: Decision);
+}
+
+impl<F: Fn(&CachePolicy, Decision) + Send + Sync> DecisionObserver for F {
+    fn observe(&self, policy: &CachePolicy, decision: Decision) {
+        self(policy, decision)
+    }
+}
+
+impl fmt::Debug for dyn DecisionObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DecisionObserver")
+    }
+}
+
+/// A decision made by a policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Decision {
+    /// A response was evaluated when creating a policy e.g. with
+    /// [`CachePolicy::with_config()`]
+    Evaluated(Storability),
+    /// The stored response can be used as-is for a request
+    ///
+    /// From [`CachePolicy::before_request()`] or [`CachePolicy::before_request_mut()`]
+    Fresh,
+    /// The stored response can't be used as-is for a request
+    ///
+    /// From [`CachePolicy::before_request()`] or [`CachePolicy::before_request_mut()`]
+    Stale(StaleReason),
+    /// See [`AfterResponse::NotModified`][crate::AfterResponse::NotModified]
+    NotModified,
+    /// See [`AfterResponse::Modified`][crate::AfterResponse::Modified]
+    Modified,
+    /// See [`AfterResponse::Partial`][crate::AfterResponse::Partial]
+    Partial,
+    /// See [`AfterResponse::Mismatch`][crate::AfterResponse::Mismatch]
+    Mismatch,
+    /// See [`AfterResponse::FailedRevalidation`][crate::AfterResponse::FailedRevalidation]
+    FailedRevalidation,
+}
@@ -0,0 +1,169 @@
+//! Hooks for varying the config per request URI
+//!
+//! An [`Overrides`] set through [`Config::overrides()`][crate::Config::overrides] is consulted
+//! with the request URI whenever a policy is created, and the [`ConfigOverrides`] it returns are
+//! applied on top of the rest of the config. This lets a single config treat hosts or paths
+//! differently e.g. trusting one origin's lifetimes more than another's.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use http::{header, Request, Response, Uri};
+//! use http_cache_policy::{overrides::ConfigOverrides, CachePolicy, Config};
+//!
+//! let config = Config::default().overrides(|uri: &Uri| {
+//!     if uri.path().starts_with("/static/") {
+//!         ConfigOverrides::default().min_ttl(Duration::from_secs(3600))
+//!     } else {
+//!         ConfigOverrides::default()
+//!     }
+//! });
+//!
+//! let res = Response::builder().header(header::CACHE_CONTROL, "max-age=60").body(()).unwrap();
+//! let now = http_cache_policy::now();
+//!
+//! let req = Request::get("https://example.com/static/app.js").body(()).unwrap();
+//! let policy = CachePolicy::with_config(&req, &res, now, config.clone());
+//! assert_eq!(policy.time_to_live(now), Duration::from_secs(3600));
+//!
+//! let req = Request::get("https://example.com/api").body(()).unwrap();
+//! let policy = CachePolicy::with_config(&req, &res, now, config);
+//! assert_eq!(policy.time_to_live(now), Duration::from_secs(60));
+//! ```
+
+use std::{fmt, time::Duration};
+
+use http::Uri;
+
+use crate::config::{Config, LastModifiedHeuristic, Mode, StoredRequestHeaders};
+
+/// Picks the [`ConfigOverrides`] for a request URI
+///
+/// This is implemented for closures taking the URI. It's called every time a policy is created,
+/// so it should be cheap e.g. matching on the host or a path prefix
+pub trait Overrides: Send + Sync {
+    /// The overrides for requests to `uri`
+    fn overrides(&self, uri: &Uri) -> ConfigOverrides;
+}
+
+impl<F: Fn(&Uri) -> ConfigOverrides + Send + Sync> Overrides for F {
+    fn overrides(&self, uri: &Uri) -> ConfigOverrides {
+        self(uri)
+    }
+}
+
+impl fmt::Debug for dyn Overrides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Overrides")
+    }
+}
+
+/// Config options that replace the ones in the [`Config`] for a particular request URI
+///
+/// Every option is left alone when it's [`None`]
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// Replaces [`Config::mode`]
+    pub mode: Option<Mode>,
+    /// Replaces [`Config::last_modified`]
+    pub last_modified: Option<LastModifiedHeuristic>,
+    /// Replaces [`Config::stored_request_headers`]
+    pub stored_request_headers: Option<StoredRequestHeaders>,
+    /// Replaces [`Config::default_ttl`]
+    pub default_ttl: Option<Duration>,
+    /// Replaces [`Config::max_ttl`]
+    pub max_ttl: Option<Duration>,
+    /// Replaces [`Config::min_ttl`]
+    pub min_ttl: Option<Duration>,
+    /// Replaces [`Config::client_error_ttl`]
+    pub client_error_ttl: Option<Duration>,
+    /// Replaces [`Config::server_error_ttl`]
+    pub server_error_ttl: Option<Duration>,
+}
+
+impl ConfigOverrides {
+    /// Overrides [`Config::mode`]
+    #[must_use]
+    pub fn mode(self, mode: Mode) -> Self {
+        Self {
+            mode: Some(mode),
+            ..self
+        }
+    }
+
+    /// Overrides [`Config::last_modified`]
+    #[must_use]
+    pub fn last_modified(self, heuristic: LastModifiedHeuristic) -> Self {
+        Self {
+            last_modified: Some(heuristic),
+            ..self
+        }
+    }
+
+    /// Overrides [`Config::stored_request_headers`]
+    #[must_use]
+    pub fn stored_request_headers(self, stored: StoredRequestHeaders) -> Self {
+        Self {
+            stored_request_headers: Some(stored),
+            ..self
+        }
+    }
+
+    /// Overrides [`Config::default_ttl`]
+    #[must_use]
+    pub fn default_ttl(self, ttl: Duration) -> Self {
+        Self {
+            default_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Overrides [`Config::max_ttl`]
+    #[must_use]
+    pub fn max_ttl(self, ttl: Duration) -> Self {
+        Self {
+            max_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Overrides [`Config::min_ttl`]
+    #[must_use]
+    pub fn min_ttl(self, ttl: Duration) -> Self {
+        Self {
+            min_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Overrides [`Config::client_error_ttl`]
+    #[must_use]
+    pub fn client_error_ttl(self, ttl: Duration) -> Self {
+        Self {
+            client_error_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    /// Overrides [`Config::server_error_ttl`]
+    #[must_use]
+    pub fn server_error_ttl(self, ttl: Duration) -> Self {
+        Self {
+            server_error_ttl: Some(ttl),
+            ..self
+        }
+    }
+
+    pub(crate) fn apply(self, config: &mut Config) {
+        config.mode = self.mode.unwrap_or(config.mode);
+        config.last_modified = self.last_modified.unwrap_or(config.last_modified);
+        config.stored_request_headers = self
+            .stored_request_headers
+            .unwrap_or(config.stored_request_headers);
+        config.default_ttl = self.default_ttl.or(config.default_ttl);
+        config.max_ttl = self.max_ttl.or(config.max_ttl);
+        config.min_ttl = self.min_ttl.or(config.min_ttl);
+        config.client_error_ttl = self.client_error_ttl.or(config.client_error_ttl);
+        config.server_error_ttl = self.server_error_ttl.or(config.server_error_ttl);
+    }
+}
@@ -0,0 +1,300 @@
+//! Parsing and formatting for the `Range`, `Content-Range`, and `Accept-Ranges` headers
+//!
+//! Only the `bytes` range unit is understood (rfc9110 14.1.2). Values using any other unit are
+//! treated as unparseable.
+
+use std::fmt;
+
+use http::HeaderValue;
+
+fn is_bytes_unit(unit: &str) -> bool {
+    unit.trim().eq_ignore_ascii_case("bytes")
+}
+
+fn parse_int(s: &str) -> Option<u64> {
+    let s = s.trim();
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// A single `range-spec` from a `Range` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteRangeSpec {
+    /// `first-last` e.g. `0-99` (both positions are inclusive)
+    FromTo(u64, u64),
+    /// `first-` e.g. `100-` for everything from `first` onwards
+    AllFrom(u64),
+    /// `-length` e.g. `-500` for the final `length` bytes
+    Last(u64),
+}
+
+impl ByteRangeSpec {
+    fn parse(s: &str) -> Option<Self> {
+        let (first, last) = s.trim().split_once('-')?;
+        match (first.trim().is_empty(), last.trim().is_empty()) {
+            (true, true) => None,
+            (true, false) => parse_int(last).map(Self::Last),
+            (false, true) => parse_int(first).map(Self::AllFrom),
+            (false, false) => {
+                let (first, last) = (parse_int(first)?, parse_int(last)?);
+                (first <= last).then_some(Self::FromTo(first, last))
+            }
+        }
+    }
+
+    /// Resolves the spec against a representation's `complete_length`
+    ///
+    /// Returns the inclusive `(first, last)` byte positions, or `None` when the range is
+    /// unsatisfiable (rfc9110 14.1.1)
+    pub fn to_satisfiable_range(self, complete_length: u64) -> Option<(u64, u64)> {
+        match self {
+            Self::FromTo(first, last) if first < complete_length => {
+                Some((first, last.min(complete_length - 1)))
+            }
+            Self::AllFrom(first) if first < complete_length => Some((first, complete_length - 1)),
+            Self::Last(length) if length > 0 && complete_length > 0 => {
+                Some((complete_length.saturating_sub(length), complete_length - 1))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ByteRangeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FromTo(first, last) => write!(f, "{first}-{last}"),
+            Self::AllFrom(first) => write!(f, "{first}-"),
+            Self::Last(length) => write!(f, "-{length}"),
+        }
+    }
+}
+
+/// A parsed `Range` request header e.g. `bytes=0-99, 200-, -50`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Range {
+    ranges: Vec<ByteRangeSpec>,
+}
+
+impl Range {
+    /// Constructs a `Range` from one or more specs
+    ///
+    /// Returns `None` if `ranges` is empty since a `Range` header needs at least one range
+    pub fn new(ranges: Vec<ByteRangeSpec>) -> Option<Self> {
+        (!ranges.is_empty()).then_some(Self { ranges })
+    }
+
+    /// Parses a `Range` header value
+    ///
+    /// Returns `None` for units other than `bytes` or for syntactically invalid values
+    pub fn parse(s: &str) -> Option<Self> {
+        let (unit, set) = s.split_once('=')?;
+        if !is_bytes_unit(unit) {
+            return None;
+        }
+        let ranges = set
+            .split(',')
+            .filter(|spec| !spec.trim().is_empty())
+            .map(ByteRangeSpec::parse)
+            .collect::<Option<Vec<_>>>()?;
+        Self::new(ranges)
+    }
+
+    /// Parses a `Range` header value
+    pub fn from_header(value: &HeaderValue) -> Option<Self> {
+        value.to_str().ok().and_then(Self::parse)
+    }
+
+    /// The individual range specs in the order they were requested
+    pub fn ranges(&self) -> &[ByteRangeSpec] {
+        &self.ranges
+    }
+
+    /// Resolves every spec against `complete_length`, dropping any that are unsatisfiable
+    ///
+    /// An empty result means that the whole `Range` is unsatisfiable
+    pub fn satisfiable_ranges(&self, complete_length: u64) -> Vec<(u64, u64)> {
+        self.ranges
+            .iter()
+            .filter_map(|spec| spec.to_satisfiable_range(complete_length))
+            .collect()
+    }
+
+    /// Formats the range as a header value
+    pub fn to_header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.to_string()).expect("Formatted ranges are always valid")
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("bytes=")?;
+        for (i, spec) in self.ranges.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{spec}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A parsed `Content-Range` response header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentRange {
+    /// `bytes first-last/complete-length` where an unknown complete length is `*`
+    Bytes {
+        /// The first byte position (inclusive)
+        first: u64,
+        /// The last byte position (inclusive)
+        last: u64,
+        /// The length of the full representation if known
+        complete_length: Option<u64>,
+    },
+    /// `bytes */complete-length` as sent with a 416 (Range Not Satisfiable) response
+    Unsatisfied {
+        /// The length of the full representation
+        complete_length: u64,
+    },
+}
+
+impl ContentRange {
+    /// Parses a `Content-Range` header value
+    ///
+    /// Returns `None` for units other than `bytes` or for syntactically invalid values
+    pub fn parse(s: &str) -> Option<Self> {
+        let (unit, rest) = s.trim().split_once(' ')?;
+        if !is_bytes_unit(unit) {
+            return None;
+        }
+        let (range, complete_length) = rest.trim().split_once('/')?;
+        let complete_length = match complete_length.trim() {
+            "*" => None,
+            length => Some(parse_int(length)?),
+        };
+
+        if range.trim() == "*" {
+            return complete_length.map(|complete_length| Self::Unsatisfied { complete_length });
+        }
+
+        let (first, last) = range.split_once('-')?;
+        let (first, last) = (parse_int(first)?, parse_int(last)?);
+        let is_valid = first <= last && complete_length.map_or(true, |len| last < len);
+        is_valid.then_some(Self::Bytes {
+            first,
+            last,
+            complete_length,
+        })
+    }
+
+    /// Parses a `Content-Range` header value
+    pub fn from_header(value: &HeaderValue) -> Option<Self> {
+        value.to_str().ok().and_then(Self::parse)
+    }
+
+    /// The length of the full representation if known
+    pub fn complete_length(&self) -> Option<u64> {
+        match *self {
+            Self::Bytes {
+                complete_length, ..
+            } => complete_length,
+            Self::Unsatisfied { complete_length } => Some(complete_length),
+        }
+    }
+
+    /// Formats the content range as a header value
+    pub fn to_header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.to_string()).expect("Formatted ranges are always valid")
+    }
+}
+
+impl fmt::Display for ContentRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes {
+                first,
+                last,
+                complete_length: Some(len),
+            } => write!(f, "bytes {first}-{last}/{len}"),
+            Self::Bytes {
+                first,
+                last,
+                complete_length: None,
+            } => write!(f, "bytes {first}-{last}/*"),
+            Self::Unsatisfied { complete_length } => write!(f, "bytes */{complete_length}"),
+        }
+    }
+}
+
+/// A parsed `Accept-Ranges` response header e.g. `bytes` or `none`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AcceptRanges {
+    units: Vec<Box<str>>,
+}
+
+impl AcceptRanges {
+    /// `Accept-Ranges: bytes`
+    pub fn bytes() -> Self {
+        Self {
+            units: vec!["bytes".into()],
+        }
+    }
+
+    /// `Accept-Ranges: none`
+    pub fn none() -> Self {
+        Self {
+            units: vec!["none".into()],
+        }
+    }
+
+    /// Parses an `Accept-Ranges` header value into its lowercased range units
+    pub fn parse(s: &str) -> Option<Self> {
+        let units = s
+            .split(',')
+            .map(str::trim)
+            .filter(|unit| !unit.is_empty())
+            .map(|unit| unit.to_ascii_lowercase().into_boxed_str())
+            .collect::<Vec<_>>();
+        (!units.is_empty()).then_some(Self { units })
+    }
+
+    /// Parses every `Accept-Ranges` header value
+    pub fn from_headers<'a>(values: impl IntoIterator<Item = &'a HeaderValue>) -> Option<Self> {
+        let units = values
+            .into_iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(Self::parse)
+            .flat_map(|accept| accept.units)
+            .collect::<Vec<_>>();
+        (!units.is_empty()).then_some(Self { units })
+    }
+
+    /// The advertised range units
+    pub fn units(&self) -> impl Iterator<Item = &str> {
+        self.units.iter().map(|unit| &**unit)
+    }
+
+    /// If byte ranges are accepted
+    pub fn accepts_bytes(&self) -> bool {
+        self.units().any(|unit| unit == "bytes")
+    }
+
+    /// Formats the accepted ranges as a header value
+    pub fn to_header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.to_string()).expect("Range units are always valid")
+    }
+}
+
+impl fmt::Display for AcceptRanges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, unit) in self.units.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(unit)?;
+        }
+        Ok(())
+    }
+}
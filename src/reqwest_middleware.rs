@@ -0,0 +1,174 @@
+//! Client-side caching middleware for [`reqwest_middleware`](https://docs.rs/reqwest-middleware)
+//!
+//! [`CacheMiddleware`] answers requests from a [`CacheStorage`] when the stored policies allow it,
+//! revalidates stale responses with conditional requests, and stores the storable responses that
+//! come back.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//!
+//! use bytes::Bytes;
+//! use http_cache_policy::{reqwest_middleware::CacheMiddleware, storage::MemoryCache};
+//!
+//! let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+//!     .with(CacheMiddleware::new(Arc::new(MemoryCache::<Bytes>::new())))
+//!     .build();
+//! ```
+//!
+//! Responses that are served from the store, or that get rebuilt after buffering their body to
+//! store it, don't carry the request URL, so [`reqwest::Response::url()`] is a placeholder for
+//! them.
+
+use std::{fmt, sync::Arc, time::SystemTime};
+
+use ::reqwest_middleware::{Middleware, Next, Result};
+use bytes::Bytes;
+use http::{Extensions, HeaderMap, Method, Uri};
+use reqwest::{Request, Response};
+
+use crate::{
+    storage::CacheStorage, AfterResponse, BeforeRequest, CachePolicy, Config, RequestLike,
+};
+
+/// A [`Middleware`] caching responses in a [`CacheStorage`]
+pub struct CacheMiddleware<St> {
+    store: Arc<St>,
+    config: Config,
+}
+
+impl<St> CacheMiddleware<St> {
+    /// A middleware caching responses in `store` with the default [`Config`]
+    pub fn new(store: Arc<St>) -> Self {
+        Self {
+            store,
+            config: Config::default(),
+        }
+    }
+
+    /// The [`Config`] that stored policies are evaluated with
+    #[must_use]
+    pub fn config(self, config: Config) -> Self {
+        Self { config, ..self }
+    }
+}
+
+impl<St> Clone for CacheMiddleware<St> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<St> fmt::Debug for CacheMiddleware<St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheMiddleware")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl<St> Middleware for CacheMiddleware<St>
+where
+    St: CacheStorage<Bytes> + Send + Sync + 'static,
+{
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let now = SystemTime::now();
+        // The stored request headers shouldn't pick up the conditional headers added to this one
+        let original = (
+            RequestLike::uri(&req),
+            req.method().clone(),
+            req.headers().clone(),
+        );
+
+        let stored = match self.store.select(&req, &self.config) {
+            Some((policy, body)) => match policy.before_request(&req, now) {
+                BeforeRequest::Fresh(res) => {
+                    return Ok(http::Response::from_parts(res, body).into())
+                }
+                BeforeRequest::Stale { request, .. } => {
+                    *req.headers_mut() = request.headers;
+                    Some((policy, body))
+                }
+            },
+            None => None,
+        };
+
+        let retry = req.try_clone();
+        let res = next.clone().run(req, extensions).await?;
+        let now = SystemTime::now();
+        let (policy, body) = match stored {
+            Some(stored) => stored,
+            None => return self.store_response(&original, res, now).await,
+        };
+
+        match policy.after_response(&original, &response_parts(&res), now) {
+            AfterResponse::NotModified(updated, res) => {
+                // The updated policy can have a different key, so its old entry is replaced too
+                let key = updated.cache_key();
+                if key != policy.cache_key() {
+                    self.store.delete(&policy.cache_key());
+                }
+                self.store.put(key, updated, body.clone());
+                Ok(http::Response::from_parts(res, body).into())
+            }
+            AfterResponse::FailedRevalidation(policy, _)
+                if policy.stale_if_error_until(now).is_some() =>
+            {
+                Ok(http::Response::from_parts(policy.stale_response(now), body).into())
+            }
+            AfterResponse::FailedRevalidation(..) => Ok(res),
+            AfterResponse::Mismatch(unconditional) => {
+                self.store.delete(&policy.cache_key());
+                let mut retry = match retry {
+                    Some(retry) => retry,
+                    None => return Ok(res),
+                };
+                *retry.headers_mut() = unconditional.headers;
+                let res = next.run(retry, extensions).await?;
+                self.store_response(&original, res, SystemTime::now()).await
+            }
+            AfterResponse::Modified(..) | AfterResponse::Partial(..) => {
+                self.store_response(&original, res, now).await
+            }
+        }
+    }
+}
+
+impl<St: CacheStorage<Bytes>> CacheMiddleware<St> {
+    /// Stores `res` if it's storable, and drops the entries that it invalidates
+    ///
+    /// The body is only buffered when the response gets stored
+    async fn store_response(
+        &self,
+        req: &(Uri, Method, HeaderMap),
+        res: Response,
+        now: SystemTime,
+    ) -> Result<Response> {
+        let parts = response_parts(&res);
+        self.store.invalidate(req, &parts, &self.config);
+
+        let policy = CachePolicy::with_config(req, &parts, now, self.config.clone());
+        if !policy.is_storable() {
+            return Ok(res);
+        }
+        let body = res.bytes().await?;
+        self.store.put(policy.cache_key(), policy, body.clone());
+        Ok(http::Response::from_parts(parts, body).into())
+    }
+}
+
+fn response_parts(res: &Response) -> http::response::Parts {
+    let (mut parts, ()) = http::Response::new(()).into_parts();
+    parts.status = res.status();
+    parts.version = res.version();
+    parts.headers = res.headers().clone();
+    parts
+}
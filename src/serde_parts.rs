@@ -0,0 +1,82 @@
+//! `serde(with = "...")` helpers for `http`'s request and response parts
+//!
+//! Extensions can't be serialized, so they're dropped and come back empty
+
+pub(crate) mod request {
+    use http::{request::Parts, HeaderMap, Method, Request, Uri, Version};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct RequestParts {
+        #[serde(with = "http_serde::method")]
+        method: Method,
+        #[serde(with = "http_serde::uri")]
+        uri: Uri,
+        #[serde(with = "http_serde::version")]
+        version: Version,
+        #[serde(with = "http_serde::header_map")]
+        headers: HeaderMap,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(parts: &Parts, ser: S) -> Result<S::Ok, S::Error> {
+        RequestParts {
+            method: parts.method.clone(),
+            uri: parts.uri.clone(),
+            version: parts.version,
+            headers: parts.headers.clone(),
+        }
+        .serialize(ser)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Parts, D::Error> {
+        let RequestParts {
+            method,
+            uri,
+            version,
+            headers,
+        } = RequestParts::deserialize(de)?;
+        let (mut parts, ()) = Request::new(()).into_parts();
+        parts.method = method;
+        parts.uri = uri;
+        parts.version = version;
+        parts.headers = headers;
+        Ok(parts)
+    }
+}
+
+pub(crate) mod response {
+    use http::{response::Parts, HeaderMap, Response, StatusCode, Version};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct ResponseParts {
+        #[serde(with = "http_serde::status_code")]
+        status: StatusCode,
+        #[serde(with = "http_serde::version")]
+        version: Version,
+        #[serde(with = "http_serde::header_map")]
+        headers: HeaderMap,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(parts: &Parts, ser: S) -> Result<S::Ok, S::Error> {
+        ResponseParts {
+            status: parts.status,
+            version: parts.version,
+            headers: parts.headers.clone(),
+        }
+        .serialize(ser)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Parts, D::Error> {
+        let ResponseParts {
+            status,
+            version,
+            headers,
+        } = ResponseParts::deserialize(de)?;
+        let (mut parts, ()) = Response::new(()).into_parts();
+        parts.status = status;
+        parts.version = version;
+        parts.headers = headers;
+        Ok(parts)
+    }
+}
@@ -0,0 +1,124 @@
+//! The versioned serde representation of [`CachePolicy`]
+//!
+//! | version | changes |
+//! | :---: | :--- |
+//! | `0` | the layout from before the version was added, which has no `version` field |
+//! | `1` | adds the trailing `version` field |
+//!
+//! The version is the last field so that formats that write structs positionally (e.g.
+//! MessagePack by default) can read older entries, where it's simply missing. Any field that gets
+//! added later has to go before it and each older version gets migrated in
+//! [`Persisted::into_policy()`].
+
+use std::time::SystemTime;
+
+use http::{HeaderMap, Method, StatusCode, Uri};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{CachePolicy, Config, DirectiveSet};
+
+/// The version of the format that's written
+const VERSION: u32 = 1;
+
+#[derive(Serialize)]
+#[serde(rename = "CachePolicy")]
+struct PersistedRef<'a> {
+    #[serde(with = "http_serde::header_map")]
+    req: &'a HeaderMap,
+    #[serde(with = "http_serde::header_map")]
+    res: &'a HeaderMap,
+    #[serde(with = "http_serde::uri")]
+    uri: &'a Uri,
+    #[serde(with = "http_serde::status_code")]
+    status: StatusCode,
+    #[serde(with = "http_serde::method")]
+    method: &'a Method,
+    config: &'a Config,
+    res_cc: &'a DirectiveSet,
+    req_cc: &'a DirectiveSet,
+    response_time: SystemTime,
+    body_digest: Option<&'a [u8]>,
+    version: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "CachePolicy")]
+struct Persisted {
+    #[serde(with = "http_serde::header_map")]
+    req: HeaderMap,
+    #[serde(with = "http_serde::header_map")]
+    res: HeaderMap,
+    #[serde(with = "http_serde::uri")]
+    uri: Uri,
+    #[serde(with = "http_serde::status_code")]
+    status: StatusCode,
+    #[serde(with = "http_serde::method")]
+    method: Method,
+    config: Config,
+    res_cc: DirectiveSet,
+    req_cc: DirectiveSet,
+    response_time: SystemTime,
+    // Missing from version `0` entries written before request content digests were added
+    #[serde(default)]
+    body_digest: Option<Box<[u8]>>,
+    // Missing from version `0` entries
+    #[serde(default)]
+    version: u32,
+}
+
+impl Persisted {
+    fn into_policy<E: serde::de::Error>(self) -> Result<CachePolicy, E> {
+        match self.version {
+            // Version `1` only added the version itself, so the fields are read the same
+            0 | 1 => {}
+            version => {
+                return Err(E::custom(format_args!(
+                    "unsupported `CachePolicy` format version {} (the latest known is {})",
+                    version, VERSION
+                )))
+            }
+        }
+
+        let vary = crate::parse_vary(&self.res);
+        Ok(CachePolicy {
+            req: self.req.into(),
+            res: self.res.into(),
+            uri: self.uri,
+            status: self.status,
+            method: self.method,
+            config: self.config,
+            res_cc: self.res_cc,
+            req_cc: self.req_cc,
+            vary,
+            response_time: self.response_time,
+            body_digest: self.body_digest,
+            derived: crate::Derived::PENDING,
+        }
+        .with_derived())
+    }
+}
+
+impl Serialize for CachePolicy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PersistedRef {
+            req: &self.req,
+            res: &self.res,
+            uri: &self.uri,
+            status: self.status,
+            method: &self.method,
+            config: &self.config,
+            res_cc: &self.res_cc,
+            req_cc: &self.req_cc,
+            response_time: self.response_time,
+            body_digest: self.body_digest.as_deref(),
+            version: VERSION,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CachePolicy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Persisted::deserialize(deserializer)?.into_policy()
+    }
+}
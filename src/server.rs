@@ -0,0 +1,235 @@
+//! Origin-side caching for [`tower`](https://docs.rs/tower) servers e.g. `axum` apps
+//!
+//! [`ConditionalLayer`] stores the storable responses of a handler in a [`CacheStorage`], serves
+//! them while they're fresh without calling the handler again, and answers conditional `GET` and
+//! `HEAD` requests with `304 Not Modified` (or `412 Precondition Failed`) responses.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use bytes::Bytes;
+//! use http_cache_policy::{server::ConditionalLayer, storage::MemoryCache};
+//!
+//! // e.g. `axum::Router::new().route(..).layer(layer)`
+//! let layer = ConditionalLayer::new(Arc::new(MemoryCache::<Bytes>::new()));
+//! ```
+//!
+//! Stored bodies are buffered into [`Bytes`], while responses that aren't storable are passed
+//! through as they are.
+
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+
+use bytes::Bytes;
+use http::{Method, Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, Either, Full};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{storage::CacheStorage, BeforeRequest, CachePolicy, Conditional, Config};
+
+/// The error type for [`ConditionalService`]
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+/// The response body of a [`ConditionalService`]
+///
+/// Either a stored body, or the body of a response that wasn't stored
+pub type ConditionalBody<B> = Either<Full<Bytes>, B>;
+
+/// A [`Layer`] that wraps services in a [`ConditionalService`]
+pub struct ConditionalLayer<St> {
+    store: Arc<St>,
+    config: Config,
+}
+
+impl<St> ConditionalLayer<St> {
+    /// A layer storing responses in `store` with the default [`Config`]
+    pub fn new(store: Arc<St>) -> Self {
+        Self {
+            store,
+            config: Config::default(),
+        }
+    }
+
+    /// The [`Config`] that stored policies are evaluated with
+    #[must_use]
+    pub fn config(self, config: Config) -> Self {
+        Self { config, ..self }
+    }
+}
+
+impl<St> Clone for ConditionalLayer<St> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<St> fmt::Debug for ConditionalLayer<St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConditionalLayer")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, St> Layer<S> for ConditionalLayer<St> {
+    type Service = ConditionalService<S, St>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConditionalService {
+            inner,
+            store: Arc::clone(&self.store),
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// A service that answers requests from the stored responses of its inner service
+///
+/// Fresh stored responses are served without calling the inner service, and the preconditions of
+/// `GET` and `HEAD` requests are evaluated against the stored or freshly generated response.
+/// Stored entries for URIs that get invalidated by unsafe requests are removed
+pub struct ConditionalService<S, St> {
+    inner: S,
+    store: Arc<St>,
+    config: Config,
+}
+
+impl<S: Clone, St> Clone for ConditionalService<S, St> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            store: Arc::clone(&self.store),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S: fmt::Debug, St> fmt::Debug for ConditionalService<S, St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConditionalService")
+            .field("inner", &self.inner)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, St, ReqBody, ResBody> Service<Request<ReqBody>> for ConditionalService<S, St>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+    St: CacheStorage<Bytes> + Send + Sync + 'static,
+    ReqBody: Send + 'static,
+    ResBody: Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<ConditionalBody<ResBody>>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // The clone may not be ready, so use the one that `poll_ready()` was called on
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+        let store = Arc::clone(&self.store);
+        let config = self.config.clone();
+        Box::pin(conditional_call(inner, store, config, req))
+    }
+}
+
+async fn conditional_call<S, St, ReqBody, ResBody>(
+    mut inner: S,
+    store: Arc<St>,
+    config: Config,
+    req: Request<ReqBody>,
+) -> Result<Response<ConditionalBody<ResBody>>, BoxError>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Into<BoxError>,
+    St: CacheStorage<Bytes>,
+    ResBody: Body<Data = Bytes>,
+    ResBody::Error: Into<BoxError>,
+{
+    let now = SystemTime::now();
+    let (parts, body) = req.into_parts();
+
+    if let Some((policy, stored_body)) = store.select(&parts, &config) {
+        if let BeforeRequest::Fresh(res) = policy.before_request(&parts, now) {
+            return Ok(conditional_response(&policy, &parts, res, stored_body, now));
+        }
+    }
+
+    let res = inner
+        .call(Request::from_parts(parts.clone(), body))
+        .await
+        .map_err(Into::into)?;
+    let now = SystemTime::now();
+    let (res_parts, res_body) = res.into_parts();
+    store.invalidate(&parts, &res_parts, &config);
+
+    let policy = CachePolicy::with_config(&parts, &res_parts, now, config);
+    if !policy.is_storable() {
+        if let Some(res) = precondition_response(&policy, &parts, now) {
+            return Ok(res);
+        }
+        return Ok(Response::from_parts(res_parts, Either::Right(res_body)));
+    }
+
+    let body = res_body.collect().await.map_err(Into::into)?.to_bytes();
+    store.put(policy.cache_key(), policy.clone(), body.clone());
+    Ok(conditional_response(&policy, &parts, res_parts, body, now))
+}
+
+/// Answers `req` with `res`, unless its preconditions turn it into a `304` or `412`
+fn conditional_response<B>(
+    policy: &CachePolicy,
+    req: &http::request::Parts,
+    res: http::response::Parts,
+    body: Bytes,
+    now: SystemTime,
+) -> Response<ConditionalBody<B>> {
+    precondition_response(policy, req, now)
+        .unwrap_or_else(|| Response::from_parts(res, Either::Left(Full::new(body))))
+}
+
+/// The `304` or `412` response for the preconditions of `req`, if any apply
+///
+/// Only successful responses to `GET` and `HEAD` requests are considered since the inner service
+/// has already handled any other request (rfc9110 13.2.1)
+fn precondition_response<B>(
+    policy: &CachePolicy,
+    req: &http::request::Parts,
+    now: SystemTime,
+) -> Option<Response<ConditionalBody<B>>> {
+    if !(req.method == Method::GET || req.method == Method::HEAD) || !policy.status().is_success() {
+        return None;
+    }
+
+    match policy.evaluate_conditional(req, now) {
+        Conditional::Full => None,
+        Conditional::NotModified(res) => {
+            Some(Response::from_parts(res, Either::Left(Full::default())))
+        }
+        Conditional::PreconditionFailed => {
+            let mut res = Response::new(Either::Left(Full::default()));
+            *res.status_mut() = StatusCode::PRECONDITION_FAILED;
+            Some(res)
+        }
+    }
+}
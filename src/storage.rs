@@ -0,0 +1,426 @@
+//! Storage for cached responses, and a reference cache that keeps every variant of a resource
+//!
+//! [`CacheStorage`] is keyed by [`CacheKey`], so it can hold all of the variants that a `Vary`
+//! header selects between. It's what the middleware integrations store their entries in.
+//! [`MemoryCache`] keeps its entries in memory and wires [`CachePolicy::before_request()`] and
+//! [`CachePolicy::after_response()`] together, making it a working end-to-end example of the
+//! whole flow.
+//!
+//! ```
+//! use std::time::SystemTime;
+//!
+//! use http::{header, Request, Response};
+//! use http_cache_policy::storage::{Lookup, MemoryCache};
+//!
+//! let cache = MemoryCache::new();
+//! let now = SystemTime::now();
+//! let req = Request::get("https://example.com/a").body(()).unwrap();
+//!
+//! let res = match cache.lookup(&req, now) {
+//!     Lookup::Fresh(res, body) => Response::from_parts(res, body),
+//!     Lookup::Stale { .. } => unreachable!("nothing is stored yet"),
+//!     Lookup::Miss => {
+//!         // forward the request e.g. with an HTTP client
+//!         let res = Response::builder()
+//!             .header(header::CACHE_CONTROL, "max-age=60")
+//!             .body("hello")
+//!             .unwrap();
+//!         cache.insert(&req, &res, *res.body(), now);
+//!         res
+//!     }
+//! };
+//! assert_eq!(*res.body(), "hello");
+//! assert!(matches!(cache.lookup(&req, now), Lookup::Fresh(_, "hello")));
+//! ```
+
+use std::{collections::HashMap, fmt, sync::Mutex, time::SystemTime};
+
+use http::{HeaderMap, Method};
+
+use crate::{
+    AfterResponse, BeforeRequest, CacheKey, CachePolicy, Config, RequestLike, ResponseLike,
+};
+
+/// Where the entries of a cache are kept
+///
+/// Entries are keyed by [`CachePolicy::cache_key()`], so several variants of a resource can be
+/// stored under the same primary key. Bodies are cloned out of the storage on every hit, so
+/// cheaply cloneable bodies like `Bytes` work best
+pub trait CacheStorage<B> {
+    /// Every entry stored with the primary key `primary`
+    fn get(&self, primary: &str) -> Vec<(CachePolicy, B)>;
+    /// Stores an entry under `key`, replacing any earlier one with the same key
+    fn put(&self, key: CacheKey, policy: CachePolicy, body: B);
+    /// Removes the entry stored under `key`
+    fn delete(&self, key: &CacheKey);
+
+    /// Removes every entry stored with the primary key `primary`
+    fn delete_all(&self, primary: &str) {
+        for (policy, _) in self.get(primary) {
+            self.delete(&policy.cache_key());
+        }
+    }
+
+    /// The stored entry that `req` can use, if any [matches][CachePolicy::matches] it
+    fn select<Req: RequestLike>(&self, req: &Req, config: &Config) -> Option<(CachePolicy, B)>
+    where
+        Self: Sized,
+    {
+        let primary = CachePolicy::primary_key_with_config(req, config);
+        self.get(&primary)
+            .into_iter()
+            .find(|(policy, _)| policy.matches(req))
+    }
+
+    /// Removes the entries that forwarding `req` and receiving `res` invalidates
+    ///
+    /// See [`CachePolicy::invalidation_targets()`]
+    fn invalidate<Req: RequestLike, Res: ResponseLike>(&self, req: &Req, res: &Res, config: &Config)
+    where
+        Self: Sized,
+    {
+        for uri in CachePolicy::invalidation_targets(req, res) {
+            for method in [Method::GET, Method::HEAD] {
+                let target: (&_, &_, &HeaderMap) = (&uri, &method, req.headers());
+                self.delete_all(&CachePolicy::primary_key_with_config(&target, config));
+            }
+        }
+    }
+}
+
+/// The outcome of [`MemoryCache::lookup()`]
+#[derive(Debug, Clone)]
+pub enum Lookup<B> {
+    /// A stored response that can be used as-is
+    Fresh(http::response::Parts, B),
+    /// A stored response that has to be revalidated first
+    ///
+    /// Send `request` and pass the response to [`MemoryCache::update()`] along with the `policy`
+    /// and `body`
+    Stale {
+        /// The stored policy
+        policy: Box<CachePolicy>,
+        /// The request to revalidate the stored response with
+        request: http::request::Parts,
+        /// The stored body
+        body: B,
+    },
+    /// Nothing stored can be used, so the request has to be forwarded
+    ///
+    /// Pass the response to [`MemoryCache::insert()`]
+    Miss,
+}
+
+/// The stored variants of a single resource
+///
+/// Responses with a `Vary` header differ by the nominated request headers, so a cache ends up
+/// with several responses for the same URI. A `VariantSet` holds the entries for one primary key,
+/// selects the variant to use for a request, and evicts the ones that newer responses supersede.
+///
+/// ```
+/// use std::time::SystemTime;
+///
+/// use http::{header, Request, Response};
+/// use http_cache_policy::{storage::VariantSet, CachePolicy};
+///
+/// let req = |language| {
+///     Request::get("https://example.com/a")
+///         .header(header::ACCEPT_LANGUAGE, language)
+///         .body(())
+///         .unwrap()
+/// };
+/// let res = Response::builder()
+///     .header(header::CACHE_CONTROL, "max-age=60")
+///     .header(header::VARY, "accept-language")
+///     .body(())
+///     .unwrap();
+///
+/// let mut variants = VariantSet::new();
+/// variants.insert(CachePolicy::new(&req("en"), &res), "hello");
+/// variants.insert(CachePolicy::new(&req("fr"), &res), "bonjour");
+/// assert_eq!(variants.select(&req("fr")).map(|(_, body)| *body), Some("bonjour"));
+/// assert!(variants.select(&req("de")).is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct VariantSet<B> {
+    variants: Vec<(CacheKey, CachePolicy, B)>,
+}
+
+impl<B> VariantSet<B> {
+    /// An empty set
+    pub fn new() -> Self {
+        Self {
+            variants: Vec::new(),
+        }
+    }
+
+    /// The number of stored variants
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// If no variants are stored
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    /// The stored variants in the order they were inserted
+    pub fn iter(&self) -> impl Iterator<Item = (&CachePolicy, &B)> {
+        self.variants.iter().map(|(_, policy, body)| (policy, body))
+    }
+
+    /// The variant to use for `req`, if any [matches][CachePolicy::matches] it
+    ///
+    /// Variants are stored with the same set of `Vary` headers and distinct secondary keys, so at
+    /// most one can match
+    pub fn select<Req: RequestLike>(&self, req: &Req) -> Option<(&CachePolicy, &B)> {
+        let (_, policy, body) = &self.variants[self.select_index(req)?];
+        Some((policy, body))
+    }
+
+    fn select_index<Req: RequestLike>(&self, req: &Req) -> Option<usize> {
+        self.variants
+            .iter()
+            .position(|(_, policy, _)| policy.matches(req))
+    }
+
+    /// Stores a variant, evicting the variants that it supersedes
+    ///
+    /// A variant with the same [`CacheKey`] is replaced. Since the latest response's `Vary`
+    /// header describes how the origin currently selects representations, variants that were
+    /// stored with a different set of `Vary` headers are evicted too. Returns the evicted variants
+    pub fn insert(&mut self, policy: CachePolicy, body: B) -> Vec<(CachePolicy, B)> {
+        self.insert_keyed(policy.cache_key(), policy, body)
+    }
+
+    fn insert_keyed(
+        &mut self,
+        key: CacheKey,
+        policy: CachePolicy,
+        body: B,
+    ) -> Vec<(CachePolicy, B)> {
+        let vary = std::sync::Arc::clone(&policy.vary);
+        let mut evicted = Vec::new();
+        let mut kept = Vec::with_capacity(self.variants.len() + 1);
+        for (stored_key, stored, stored_body) in self.variants.drain(..) {
+            if stored_key == key || stored.vary != vary {
+                evicted.push((stored, stored_body));
+            } else {
+                kept.push((stored_key, stored, stored_body));
+            }
+        }
+        kept.push((key, policy, body));
+        self.variants = kept;
+        evicted
+    }
+
+    /// Removes the variant stored under `key`
+    pub fn remove(&mut self, key: &CacheKey) -> Option<(CachePolicy, B)> {
+        let index = self
+            .variants
+            .iter()
+            .position(|(stored, ..)| stored == key)?;
+        let (_, policy, body) = self.variants.remove(index);
+        Some((policy, body))
+    }
+
+    /// Updates the variant selected for `req` with the response to a revalidation request
+    ///
+    /// The selected variant's policy is replaced when the response shows that it's still valid
+    /// (or refreshes it with a partial response). The updated policy can have a different
+    /// [`CacheKey`] or `Vary` e.g. when the response drops `Vary`, so the variants it supersedes are
+    /// evicted like with [`insert()`][Self::insert]. Returns [`None`] when no variant matches `req`
+    pub fn update<Req: RequestLike, Res: ResponseLike>(
+        &mut self,
+        req: &Req,
+        res: &Res,
+        now: SystemTime,
+    ) -> Option<AfterResponse> {
+        let index = self.select_index(req)?;
+        let after = self.variants[index].1.after_response(req, res, now);
+        match &after {
+            AfterResponse::NotModified(policy, _) | AfterResponse::Partial(policy, _) => {
+                let (_, _, body) = self.variants.remove(index);
+                self.insert_keyed(policy.cache_key(), policy.clone(), body);
+            }
+            AfterResponse::Modified(..)
+            | AfterResponse::Mismatch(_)
+            | AfterResponse::FailedRevalidation(..) => {}
+        }
+        Some(after)
+    }
+}
+
+impl<B> Default for VariantSet<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`CacheStorage`] that keeps its entries in memory
+///
+/// Bodies are cloned out of the cache on every hit, so cheaply cloneable bodies like `Bytes` work
+/// best
+///
+/// Each primary key holds a [`VariantSet`], so storing a response also evicts the variants that
+/// it supersedes
+pub struct MemoryCache<B> {
+    entries: Mutex<HashMap<String, VariantSet<B>>>,
+    config: Config,
+}
+
+impl<B> MemoryCache<B> {
+    /// An empty cache that evaluates responses with the default [`Config`]
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::default(),
+            config: Config::default(),
+        }
+    }
+
+    /// The [`Config`] that stored policies are evaluated with
+    #[must_use]
+    pub fn config(self, config: Config) -> Self {
+        Self { config, ..self }
+    }
+
+    /// The number of stored entries, counting every variant
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap()
+            .values()
+            .map(VariantSet::len)
+            .sum()
+    }
+
+    /// If nothing is stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<B: Clone> MemoryCache<B> {
+    /// Looks up a stored response for `req` at `now`
+    ///
+    /// See [`VariantSet::select()`] for how the variant is selected
+    pub fn lookup<Req: RequestLike>(&self, req: &Req, now: SystemTime) -> Lookup<B> {
+        let primary = CachePolicy::primary_key_with_config(req, &self.config);
+        let entries = self.entries.lock().unwrap();
+        let selected = entries
+            .get(&primary)
+            .and_then(|variants| variants.select(req));
+        let (policy, body) = match selected {
+            Some((policy, body)) => (policy.clone(), body.clone()),
+            None => return Lookup::Miss,
+        };
+        drop(entries);
+
+        match policy.before_request(req, now) {
+            BeforeRequest::Fresh(res) => Lookup::Fresh(res, body),
+            BeforeRequest::Stale { request, .. } => Lookup::Stale {
+                policy: Box::new(policy),
+                request,
+                body,
+            },
+        }
+    }
+
+    /// Handles a response to a forwarded request, storing it if possible
+    ///
+    /// Entries that a response to an unsafe request invalidates are removed first. Returns if
+    /// the response was stored
+    pub fn insert<Req: RequestLike, Res: ResponseLike>(
+        &self,
+        req: &Req,
+        res: &Res,
+        body: B,
+        now: SystemTime,
+    ) -> bool {
+        self.invalidate(req, res, &self.config);
+
+        let policy = CachePolicy::with_config(req, res, now, self.config.clone());
+        if !policy.is_storable() {
+            return false;
+        }
+        self.put(policy.cache_key(), policy, body);
+        true
+    }
+
+    /// Handles the response to the revalidation request from a [`Lookup::Stale`]
+    ///
+    /// When the stored response is still valid its entry is refreshed and the response to serve
+    /// is returned. Otherwise this returns [`None`], and a new full response should be passed to
+    /// [`insert()`][Self::insert] instead
+    pub fn update<Req: RequestLike, Res: ResponseLike>(
+        &self,
+        policy: &CachePolicy,
+        body: B,
+        req: &Req,
+        res: &Res,
+        now: SystemTime,
+    ) -> Option<(http::response::Parts, B)> {
+        match policy.after_response(req, res, now) {
+            AfterResponse::NotModified(updated, parts) => {
+                // The updated policy can have a different key, so its old entry is replaced too
+                let key = updated.cache_key();
+                if key != policy.cache_key() {
+                    self.delete(&policy.cache_key());
+                }
+                self.put(key, updated, body.clone());
+                Some((parts, body))
+            }
+            AfterResponse::Modified(..)
+            | AfterResponse::Partial(..)
+            | AfterResponse::Mismatch(_)
+            | AfterResponse::FailedRevalidation(..) => None,
+        }
+    }
+}
+
+impl<B: Clone> CacheStorage<B> for MemoryCache<B> {
+    fn get(&self, primary: &str) -> Vec<(CachePolicy, B)> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(primary).map_or_else(Vec::new, |variants| {
+            variants
+                .iter()
+                .map(|(policy, body)| (policy.clone(), body.clone()))
+                .collect()
+        })
+    }
+
+    fn put(&self, key: CacheKey, policy: CachePolicy, body: B) {
+        let mut entries = self.entries.lock().unwrap();
+        let variants = entries.entry(key.primary().to_owned()).or_default();
+        variants.insert_keyed(key, policy, body);
+    }
+
+    fn delete(&self, key: &CacheKey) {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(variants) = entries.get_mut(key.primary()) {
+            variants.remove(key);
+            if variants.is_empty() {
+                entries.remove(key.primary());
+            }
+        }
+    }
+
+    fn delete_all(&self, primary: &str) {
+        self.entries.lock().unwrap().remove(primary);
+    }
+}
+
+impl<B> Default for MemoryCache<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> fmt::Debug for MemoryCache<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryCache")
+            .field("len", &self.len())
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
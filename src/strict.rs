@@ -0,0 +1,236 @@
+//! Strict parsing of the headers that policies depend on
+//!
+//! Policies are lenient by default: malformed `Cache-Control` members are skipped, invalid dates
+//! are ignored (or treated as already expired), and an invalid `Age` counts as zero, which is
+//! what browsers do. [`CachePolicy::try_new()`] instead reports these as [`ParseIssue`]s, for
+//! callers that want to reject or log misbehaving upstream responses.
+//!
+//! ```
+//! use http::{header, Request, Response};
+//! use http_cache_policy::{strict::ParseIssue, CachePolicy};
+//!
+//! let req = Request::get("https://example.com/a").body(()).unwrap();
+//! let res = Response::builder()
+//!     .header(header::CACHE_CONTROL, "max-age=soon")
+//!     .header(header::EXPIRES, "0")
+//!     .body(())
+//!     .unwrap();
+//!
+//! let err = CachePolicy::try_new(&req, &res).unwrap_err();
+//! assert_eq!(
+//!     err.issues(),
+//!     [
+//!         ParseIssue::InvalidSeconds {
+//!             directive: "max-age".into(),
+//!             value: Some("soon".into()),
+//!         },
+//!         ParseIssue::InvalidDate(header::EXPIRES),
+//!     ]
+//! );
+//! ```
+
+use std::{collections::HashMap, error::Error, fmt, time::SystemTime};
+
+use http::{
+    header::{AGE, CACHE_CONTROL, DATE, EXPIRES, LAST_MODIFIED},
+    HeaderName,
+};
+
+use crate::{
+    cache_control::{malformed_members, CacheControl},
+    CachePolicy, Config, RequestLike, ResponseLike,
+};
+
+/// The directives whose values are `delta-seconds`
+const SECONDS_DIRECTIVES: &[&str] = &[
+    "max-age",
+    "s-maxage",
+    "stale-while-revalidate",
+    "stale-if-error",
+];
+
+impl CachePolicy {
+    /// Like [`new()`][Self::new], but fails when the response's caching headers are malformed
+    ///
+    /// See [`parse_issues()`][Self::parse_issues] for what's checked
+    pub fn try_new<Req: RequestLike, Res: ResponseLike>(
+        req: &Req,
+        res: &Res,
+    ) -> Result<Self, ParseError> {
+        Self::try_with_config(req, res, SystemTime::now(), Config::default())
+    }
+
+    /// Like [`with_config()`][Self::with_config], but fails when the response's caching headers
+    /// are malformed
+    ///
+    /// See [`parse_issues()`][Self::parse_issues] for what's checked
+    pub fn try_with_config<Req: RequestLike, Res: ResponseLike>(
+        req: &Req,
+        res: &Res,
+        response_time: SystemTime,
+        config: Config,
+    ) -> Result<Self, ParseError> {
+        let policy = Self::unobserved(req, res, response_time, config);
+        let issues = policy.parse_issues();
+        if !issues.is_empty() {
+            return Err(ParseError { issues });
+        }
+        Ok(policy.evaluated())
+    }
+
+    /// The problems with the stored response's caching headers that were silently worked around
+    ///
+    /// This reports `Cache-Control` members that aren't valid directives, directives that appear
+    /// several times with different values, `delta-seconds` directives (e.g. `max-age`) without
+    /// a valid number, `Date`, `Expires`, and `Last-Modified` headers that aren't valid HTTP dates,
+    /// and an `Age` that isn't a number of seconds. Headers that were already dropped e.g. by
+    /// [`Config::strip_response_headers`] aren't checked.
+    pub fn parse_issues(&self) -> Vec<ParseIssue> {
+        let mut issues = Vec::new();
+
+        for value in self.res.get_all(CACHE_CONTROL) {
+            match value.to_str() {
+                Ok(value) => issues.extend(
+                    malformed_members(value)
+                        .into_iter()
+                        .map(|member| ParseIssue::MalformedDirective(member.into())),
+                ),
+                Err(_) => issues.push(ParseIssue::MalformedDirective(
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )),
+            }
+        }
+
+        let cc = CacheControl::from_headers(self.res.get_all(CACHE_CONTROL));
+        let is_seconds =
+            |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+        let mut seen = HashMap::new();
+        for directive in cc.directives() {
+            let name = directive.name();
+            match seen.get(name) {
+                Some(value) if *value != directive.value() => {
+                    if !issues.contains(&ParseIssue::ConflictingDirective(name.into())) {
+                        issues.push(ParseIssue::ConflictingDirective(name.into()));
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(name, directive.value());
+                }
+            }
+
+            if SECONDS_DIRECTIVES.contains(&name) && !directive.value().map_or(false, is_seconds) {
+                issues.push(ParseIssue::InvalidSeconds {
+                    directive: name.into(),
+                    value: directive.value().map(From::from),
+                });
+            }
+        }
+
+        for name in [DATE, EXPIRES, LAST_MODIFIED] {
+            let is_invalid = self.res.get_all(&name).iter().any(|value| {
+                value.to_str().map_or(true, |value| {
+                    httpdate::parse_http_date(value.trim()).is_err()
+                })
+            });
+            if is_invalid {
+                issues.push(ParseIssue::InvalidDate(name));
+            }
+        }
+
+        let is_invalid_age = self.res.get_all(AGE).iter().any(|value| {
+            let value = value.as_bytes();
+            value.is_empty() || !value.iter().all(u8::is_ascii_digit)
+        });
+        if is_invalid_age {
+            issues.push(ParseIssue::InvalidAge);
+        }
+
+        issues
+    }
+}
+
+/// A problem with a response's caching headers
+///
+/// See [`CachePolicy::parse_issues()`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseIssue {
+    /// A `Cache-Control` member that isn't a valid directive, so it was skipped
+    MalformedDirective(String),
+    /// A `Cache-Control` directive that appears several times with different values
+    ///
+    /// The policy treats the response as `must-revalidate` since its freshness is unclear
+    ConflictingDirective(String),
+    /// A directive like `max-age` without a valid number of seconds
+    ///
+    /// The policy treats the value as `0`
+    InvalidSeconds {
+        /// The directive's name
+        directive: String,
+        /// The directive's value if it had one
+        value: Option<String>,
+    },
+    /// A `Date`, `Expires`, or `Last-Modified` header that isn't a valid HTTP date
+    InvalidDate(HeaderName),
+    /// An `Age` header that isn't a number of seconds
+    ///
+    /// The policy treats the age as `0`
+    InvalidAge,
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedDirective(member) => {
+                write!(f, "malformed Cache-Control directive `{}`", member)
+            }
+            Self::ConflictingDirective(name) => {
+                write!(
+                    f,
+                    "conflicting values for Cache-Control directive `{}`",
+                    name
+                )
+            }
+            Self::InvalidSeconds {
+                directive,
+                value: Some(value),
+            } => write!(f, "invalid seconds `{}` for `{}`", value, directive),
+            Self::InvalidSeconds {
+                directive,
+                value: None,
+            } => write!(f, "missing seconds for `{}`", directive),
+            Self::InvalidDate(name) => write!(f, "invalid date in `{}`", name),
+            Self::InvalidAge => f.write_str("invalid `age`"),
+        }
+    }
+}
+
+/// A response with malformed caching headers
+///
+/// See [`CachePolicy::try_new()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    issues: Vec<ParseIssue>,
+}
+
+impl ParseError {
+    /// Every problem that was found
+    pub fn issues(&self) -> &[ParseIssue] {
+        &self.issues
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
@@ -0,0 +1,233 @@
+//! Caching middleware for [`tower`](https://docs.rs/tower) services
+//!
+//! [`CacheLayer`] wraps any `Service<Request<B>>` whose response bodies can be cloned (e.g.
+//! `Bytes` or `String`) with a cache that follows the policy's decisions: fresh responses are
+//! served from the [`CacheStorage`], stale ones are revalidated with a conditional request, and
+//! `304`s are answered with the stored body.
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use http_cache_policy::{storage::MemoryCache, tower::CacheLayer, Config};
+//! use tower_layer::Layer;
+//!
+//! # #[derive(Clone)] struct Client;
+//! # impl tower_service::Service<http::Request<()>> for Client {
+//! #     type Response = http::Response<String>;
+//! #     type Error = std::convert::Infallible;
+//! #     type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+//! #     fn poll_ready(&mut self, _: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+//! #         std::task::Poll::Ready(Ok(()))
+//! #     }
+//! #     fn call(&mut self, _: http::Request<()>) -> Self::Future {
+//! #         std::future::ready(Ok(http::Response::new(String::new())))
+//! #     }
+//! # }
+//! let layer = CacheLayer::new(Arc::new(MemoryCache::<String>::new())).config(Config::default());
+//! let service = layer.layer(Client);
+//! ```
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+
+use http::{Request, Response};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{storage::CacheStorage, AfterResponse, BeforeRequestMut, CachePolicy, Config};
+
+/// A [`Layer`] that wraps services in a [`CacheService`]
+pub struct CacheLayer<St> {
+    store: Arc<St>,
+    config: Config,
+}
+
+impl<St> CacheLayer<St> {
+    /// A layer caching responses in `store` with the default [`Config`]
+    pub fn new(store: Arc<St>) -> Self {
+        Self {
+            store,
+            config: Config::default(),
+        }
+    }
+
+    /// The [`Config`] that stored policies are evaluated with
+    #[must_use]
+    pub fn config(self, config: Config) -> Self {
+        Self { config, ..self }
+    }
+}
+
+impl<St> Clone for CacheLayer<St> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<St> fmt::Debug for CacheLayer<St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheLayer")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, St> Layer<S> for CacheLayer<St> {
+    type Service = CacheService<S, St>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CacheService {
+            inner,
+            store: Arc::clone(&self.store),
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// A service that answers requests from a [`CacheStorage`] when the stored policies allow it
+///
+/// Requests that can't be answered from the store are forwarded to the inner service, with the
+/// conditional headers added when a stored response is being revalidated. Storable responses are
+/// stored, and stored entries for URIs that get invalidated by unsafe requests are removed
+pub struct CacheService<S, St> {
+    inner: S,
+    store: Arc<St>,
+    config: Config,
+}
+
+impl<S: Clone, St> Clone for CacheService<S, St> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            store: Arc::clone(&self.store),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S: fmt::Debug, St> fmt::Debug for CacheService<S, St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheService")
+            .field("inner", &self.inner)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, St, ReqBody, ResBody> Service<Request<ReqBody>> for CacheService<S, St>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    St: CacheStorage<ResBody> + Send + Sync + 'static,
+    ReqBody: Default + Send + 'static,
+    ResBody: Clone + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // The clone may not be ready, so use the one that `poll_ready()` was called on
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+        let store = Arc::clone(&self.store);
+        let config = self.config.clone();
+        Box::pin(cached_call(inner, store, config, req))
+    }
+}
+
+async fn cached_call<S, St, ReqBody, ResBody>(
+    mut inner: S,
+    store: Arc<St>,
+    config: Config,
+    req: Request<ReqBody>,
+) -> Result<Response<ResBody>, S::Error>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    St: CacheStorage<ResBody>,
+    ReqBody: Default,
+    ResBody: Clone,
+{
+    let now = SystemTime::now();
+    let (parts, body) = req.into_parts();
+
+    // The stored request headers shouldn't pick up the conditional headers added to this one
+    let mut forwarded = parts.clone();
+    let stored = match store.select(&parts, &config) {
+        Some((policy, stored_body)) => match policy.before_request_mut(&mut forwarded, now) {
+            BeforeRequestMut::Fresh(res) => return Ok(Response::from_parts(res, stored_body)),
+            BeforeRequestMut::Stale { .. } => Some((policy, stored_body)),
+        },
+        None => None,
+    };
+
+    let res = inner.call(Request::from_parts(forwarded, body)).await?;
+    let now = SystemTime::now();
+    let (policy, stored_body) = match stored {
+        Some(stored) => stored,
+        None => return Ok(store_response(&*store, &config, &parts, res, now)),
+    };
+
+    let (res_parts, res_body) = res.into_parts();
+    match policy.after_response(&parts, &res_parts, now) {
+        AfterResponse::NotModified(updated, res) => {
+            // The updated policy can have a different key, so its old entry is replaced too
+            let key = updated.cache_key();
+            if key != policy.cache_key() {
+                store.delete(&policy.cache_key());
+            }
+            store.put(key, updated, stored_body.clone());
+            Ok(Response::from_parts(res, stored_body))
+        }
+        AfterResponse::FailedRevalidation(policy, res) => match policy.stale_if_error_until(now) {
+            Some(_) => Ok(Response::from_parts(
+                policy.stale_response(now),
+                stored_body,
+            )),
+            None => Ok(Response::from_parts(res, res_body)),
+        },
+        AfterResponse::Mismatch(unconditional) => {
+            store.delete(&policy.cache_key());
+            std::future::poll_fn(|cx| inner.poll_ready(cx)).await?;
+            let res = inner
+                .call(Request::from_parts(unconditional, ReqBody::default()))
+                .await?;
+            Ok(store_response(&*store, &config, &parts, res, now))
+        }
+        AfterResponse::Modified(..) | AfterResponse::Partial(..) => {
+            let res = Response::from_parts(res_parts, res_body);
+            Ok(store_response(&*store, &config, &parts, res, now))
+        }
+    }
+}
+
+/// Stores `res` if it's storable, and drops the entries that it invalidates
+fn store_response<St: CacheStorage<B>, B: Clone>(
+    store: &St,
+    config: &Config,
+    req: &http::request::Parts,
+    res: Response<B>,
+    now: SystemTime,
+) -> Response<B> {
+    let (res_parts, body) = res.into_parts();
+    store.invalidate(req, &res_parts, config);
+
+    let policy = CachePolicy::with_config(req, &res_parts, now, config.clone());
+    if policy.is_storable() {
+        store.put(policy.cache_key(), policy, body.clone());
+    }
+    Response::from_parts(res_parts, body)
+}
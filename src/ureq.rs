@@ -0,0 +1,85 @@
+//! Conversions for [`ureq`](https://docs.rs/ureq) 2.x requests and responses
+//!
+//! `ureq` keeps its own header representation, so these convert requests and responses into the
+//! tuples that implement [`RequestLike`][crate::RequestLike] and
+//! [`ResponseLike`][crate::ResponseLike].
+//!
+//! ```no_run
+//! use http_cache_policy::{ureq as convert, BeforeRequest, CachePolicy};
+//!
+//! let agent = ureq::agent();
+//! let req = agent.get("https://example.com/");
+//! let res = req.clone().call().unwrap();
+//! let policy = CachePolicy::new(&convert::request(&req).unwrap(), &convert::response(&res).unwrap());
+//!
+//! let req = agent.get("https://example.com/");
+//! match policy.before_request(&convert::request(&req).unwrap(), http_cache_policy::now()) {
+//!     BeforeRequest::Fresh(_) => { /* serve the stored body */ }
+//!     BeforeRequest::Stale { request, .. } => {
+//!         let res = convert::revalidation_request(&agent, &request).call();
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use http::{request::Parts, HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri};
+
+use crate::builder::BuildError;
+
+/// Converts a request into the `(Uri, Method, HeaderMap)` tuple that implements `RequestLike`
+pub fn request(req: &ureq::Request) -> Result<(Uri, Method, HeaderMap), BuildError> {
+    let uri = req.url().parse()?;
+    let method = Method::from_bytes(req.method().as_bytes())?;
+    let mut headers = HeaderMap::new();
+    for name in req.header_names() {
+        append_all(&mut headers, &name, req.all(&name))?;
+    }
+    Ok((uri, method, headers))
+}
+
+/// Converts a response into the `(StatusCode, HeaderMap)` tuple that implements `ResponseLike`
+pub fn response(res: &ureq::Response) -> Result<(StatusCode, HeaderMap), BuildError> {
+    let status = StatusCode::from_u16(res.status())?;
+    let mut headers = HeaderMap::new();
+    for name in res.headers_names() {
+        append_all(&mut headers, &name, res.all(&name))?;
+    }
+    Ok((status, headers))
+}
+
+/// Builds [`BeforeRequest::Stale`][crate::BeforeRequest::Stale]'s revalidation request with
+/// `agent`
+///
+/// `ureq` can't remove headers from a request, so the request is built from scratch instead of
+/// patching the original one, which may carry conditional headers e.g. a client's `If-Range`
+/// that the policy had to drop. Set per-request options like timeouts on the returned request.
+/// Headers with several values are combined into one comma-separated field, and values that
+/// aren't UTF-8 are left out.
+pub fn revalidation_request(agent: &ureq::Agent, parts: &Parts) -> ureq::Request {
+    let mut req = agent.request(parts.method.as_str(), &parts.uri.to_string());
+    for name in parts.headers.keys() {
+        let values: Option<Vec<_>> = parts
+            .headers
+            .get_all(name)
+            .iter()
+            .map(|value| value.to_str().ok())
+            .collect();
+        if let Some(values) = values {
+            req = req.set(name.as_str(), &values.join(", "));
+        }
+    }
+    req
+}
+
+/// Appends every value of a header, which is skipped when it was already appended since the
+/// names are listed once per field line
+fn append_all(headers: &mut HeaderMap, name: &str, values: Vec<&str>) -> Result<(), BuildError> {
+    let name = HeaderName::from_bytes(name.as_bytes())?;
+    if headers.contains_key(&name) {
+        return Ok(());
+    }
+    for value in values {
+        headers.append(&name, HeaderValue::from_str(value)?);
+    }
+    Ok(())
+}
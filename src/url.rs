@@ -0,0 +1,67 @@
+//! Requests identified by a [`url::Url`](https://docs.rs/url)
+
+use http::{HeaderMap, Method, Uri};
+use url::{Position, Url};
+
+use crate::RequestLike;
+
+/// A request made of a [`Url`], a method, and headers
+///
+/// The URL's fragment is never sent to servers, so it's left out when comparing against and
+/// converting into a [`Uri`]
+///
+/// ```
+/// use http::{HeaderMap, Method, Response};
+/// use http_cache_policy::{url::UrlRequest, CachePolicy};
+/// use url::Url;
+///
+/// let url = Url::parse("https://example.com/a#section").unwrap();
+/// let headers = HeaderMap::new();
+/// let req = UrlRequest::new(&url, &Method::GET, &headers);
+/// let res = Response::builder().header("cache-control", "max-age=60").body(()).unwrap();
+/// let policy = CachePolicy::new(&req, &res);
+/// assert_eq!(policy.uri(), "https://example.com/a");
+/// assert!(policy.matches(&req));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UrlRequest<'a> {
+    url: &'a Url,
+    method: &'a Method,
+    headers: &'a HeaderMap,
+}
+
+impl<'a> UrlRequest<'a> {
+    /// A request for `url`
+    pub fn new(url: &'a Url, method: &'a Method, headers: &'a HeaderMap) -> Self {
+        Self {
+            url,
+            method,
+            headers,
+        }
+    }
+
+    /// The URL without its fragment
+    fn without_fragment(&self) -> &str {
+        &self.url[..Position::AfterQuery]
+    }
+}
+
+impl RequestLike for UrlRequest<'_> {
+    fn uri(&self) -> Uri {
+        self.without_fragment()
+            .parse()
+            .expect("Uri and Url are incompatible!?")
+    }
+
+    fn is_same_uri(&self, other: &Uri) -> bool {
+        other == self.without_fragment()
+    }
+
+    fn method(&self) -> &Method {
+        self.method
+    }
+
+    fn headers(&self) -> &HeaderMap {
+        self.headers
+    }
+}
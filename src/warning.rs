@@ -0,0 +1,194 @@
+//! Parsing for the `Warning` header
+//!
+//! Each warning is `warn-code SP warn-agent SP warn-text [ SP warn-date ]` (rfc7234 5.5) where the
+//! text and date are quoted-strings that may contain commas, so a list of them can't just be split
+//! on commas. Parsing is lenient since warnings are informational: unquoted text is accepted, and
+//! members that don't parse are left alone when stored warnings are filtered.
+//!
+//! ```
+//! use http::{header::WARNING, HeaderMap};
+//! use http_cache_policy::warning::Warning;
+//!
+//! let mut headers = HeaderMap::new();
+//! headers.insert(
+//!     WARNING,
+//!     r#"110 proxy "Response is Stale", 299 - "a, b" "Tue, 15 Nov 1994 08:12:31 GMT""#
+//!         .parse()
+//!         .unwrap(),
+//! );
+//!
+//! let warnings: Vec<_> = Warning::from_headers(&headers).collect();
+//! assert_eq!(warnings.len(), 2);
+//! assert_eq!(warnings[0].code(), 110);
+//! assert_eq!(warnings[0].agent(), "proxy");
+//! assert_eq!(warnings[1].text(), "a, b");
+//! assert!(warnings[1].date().is_some());
+//! ```
+
+use std::{borrow::Cow, time::SystemTime};
+
+use http::{header::WARNING, HeaderMap, HeaderValue};
+
+/// A single warning e.g. `110 - "Response is Stale"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning<'a> {
+    raw: &'a str,
+    code: u16,
+    agent: &'a str,
+    text: Cow<'a, str>,
+    date: Option<SystemTime>,
+}
+
+impl<'a> Warning<'a> {
+    /// Parses a single warning, returning `None` if it doesn't start with a three digit code and
+    /// an agent
+    pub fn parse(s: &'a str) -> Option<Self> {
+        let raw = s.trim();
+        let (code, rest) = raw.split_once(' ')?;
+        if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let rest = rest.trim_start();
+        let (agent, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+        if agent.is_empty() {
+            return None;
+        }
+        let rest = rest.trim_start();
+
+        let (text, date) = match quoted_string(rest) {
+            Some((text, rest)) => {
+                let date = quoted_string(rest.trim_start())
+                    .and_then(|(date, _)| httpdate::parse_http_date(&date).ok());
+                (text, date)
+            }
+            // Not quoted as it should be, so the text is whatever is left
+            None => (Cow::Borrowed(rest), None),
+        };
+
+        Some(Self {
+            raw,
+            code: code.parse().ok()?,
+            agent,
+            text,
+            date,
+        })
+    }
+
+    /// Every warning that parses across all of the `Warning` headers
+    pub fn from_headers(headers: &'a HeaderMap) -> impl Iterator<Item = Warning<'a>> {
+        members(headers.get_all(WARNING)).filter_map(Warning::parse)
+    }
+
+    /// The three digit warn-code
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    /// The host or pseudonym that added the warning, `-` when it's unknown
+    pub fn agent(&self) -> &'a str {
+        self.agent
+    }
+
+    /// The unquoted warn-text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The warn-date if there is a valid one
+    pub fn date(&self) -> Option<SystemTime> {
+        self.date
+    }
+
+    /// The warning as it appeared in the header
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    /// 1xx warnings describe the freshness of a stored response, so caches drop them after a
+    /// successful validation and before serving it (rfc7234 5.5)
+    pub fn is_1xx(&self) -> bool {
+        (100..200).contains(&self.code)
+    }
+}
+
+/// Splits a leading quoted-string off of `s`, returning its unescaped contents and the rest
+fn quoted_string(s: &str) -> Option<(Cow<'_, str>, &str)> {
+    let inner = s.strip_prefix('"')?;
+    let mut escaped = false;
+    let mut has_escapes = false;
+    for (i, c) in inner.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+            has_escapes = true;
+        } else if c == '"' {
+            let quoted = &inner[..i];
+            let text = if has_escapes {
+                Cow::Owned(unescape(quoted))
+            } else {
+                Cow::Borrowed(quoted)
+            };
+            return Some((text, &inner[i + 1..]));
+        }
+    }
+    None
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Splits a `Warning` header into its members, ignoring commas in quoted-strings
+fn split(value: &str) -> impl Iterator<Item = &str> {
+    let mut rest = value;
+    std::iter::from_fn(move || loop {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut in_quotes = false;
+        let mut escaped = false;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                if escaped {
+                    escaped = false;
+                } else if in_quotes && c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    in_quotes = !in_quotes;
+                } else if c == ',' && !in_quotes {
+                    return true;
+                }
+                false
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        let member = rest[..end].trim();
+        rest = rest.get(end + 1..).unwrap_or("");
+        if !member.is_empty() {
+            return Some(member);
+        }
+    })
+}
+
+/// Every member of the `Warning` headers, including the ones that don't parse
+pub(crate) fn members<'a>(
+    all: impl IntoIterator<Item = &'a HeaderValue>,
+) -> impl Iterator<Item = &'a str> {
+    all.into_iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(split)
+}
+
+/// If a member of a `Warning` header is a 1xx warning that should be dropped
+pub(crate) fn is_1xx(member: &str) -> bool {
+    Warning::parse(member).map_or(false, |warning| warning.is_1xx())
+}
@@ -0,0 +1,220 @@
+//! [`wasm-bindgen`](https://docs.rs/wasm-bindgen) bindings for JS and Service Worker environments
+//!
+//! The bindings follow the API of the [`http-cache-semantics`](https://www.npmjs.com/package/http-cache-semantics)
+//! npm package, except that the clock isn't available on `wasm32-unknown-unknown`, so every method
+//! that needs the current time takes it as milliseconds since the Unix epoch e.g. `Date.now()`.
+//!
+//! ```js
+//! import { CachePolicy } from "http-cache-policy";
+//!
+//! const policy = new CachePolicy(
+//!   { method: "GET", url: "https://example.com/a", headers: request.headers },
+//!   { status: 200, headers: { "cache-control": "max-age=60" } },
+//!   Date.now(),
+//! );
+//! if (policy.storable()) {
+//!   await store.put(key, policy.toObject());
+//! }
+//! ```
+//!
+//! Requests are `{ method, url, headers }` objects and responses are `{ status, headers }` objects,
+//! where the headers can be a plain object (with string or array values) or a fetch `Headers`.
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri};
+use js_sys::{Array, Object, Reflect, JSON};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::{
+    builder::BuildError, config::Mode, js_json::headers_to_js, lossy, CachePolicy, Config, Rewrite,
+};
+
+/// A [`CachePolicy`] exposed to JS as `CachePolicy`
+#[wasm_bindgen(js_name = CachePolicy)]
+#[derive(Debug, Clone)]
+pub struct WasmCachePolicy(CachePolicy);
+
+#[wasm_bindgen(js_class = CachePolicy)]
+impl WasmCachePolicy {
+    /// Evaluates a response received at `now`, for a shared cache unless `shared` is `false`
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        req: &JsValue,
+        res: &JsValue,
+        now: f64,
+        shared: Option<bool>,
+    ) -> Result<WasmCachePolicy, JsError> {
+        let mut config = Config::default();
+        if shared == Some(false) {
+            config.mode = Mode::Private;
+        }
+        let policy = CachePolicy::with_config(&request(req)?, &response(res)?, time(now), config);
+        Ok(Self(policy))
+    }
+
+    /// If the response can be stored at all
+    pub fn storable(&self) -> bool {
+        self.0.is_storable()
+    }
+
+    /// If the stored response can be used for `req` at `now` without contacting the server
+    #[wasm_bindgen(js_name = satisfiesWithoutRevalidation)]
+    pub fn satisfies_without_revalidation(&self, req: &JsValue, now: f64) -> Result<bool, JsError> {
+        Ok(self
+            .0
+            .satisfies_without_revalidation(&request(req)?, time(now)))
+    }
+
+    /// The headers to serve the stored response with at `now`
+    #[wasm_bindgen(js_name = responseHeaders)]
+    pub fn response_headers(&self, now: f64) -> Result<JsValue, JsError> {
+        to_js(&headers_to_js(&self.0.cached_response(time(now)).headers))
+    }
+
+    /// The request headers to revalidate the stored response with, based on `req`'s headers
+    #[wasm_bindgen(js_name = revalidationHeaders)]
+    pub fn revalidation_headers(&self, req: &JsValue) -> Result<JsValue, JsError> {
+        let revalidation = lossy(
+            self.0
+                .revalidation_request_with(&request(req)?, Rewrite::Lossy),
+        );
+        to_js(&headers_to_js(&revalidation.headers))
+    }
+
+    /// How many milliseconds the response can still be stored for at `now`
+    #[wasm_bindgen(js_name = timeToLive)]
+    pub fn time_to_live(&self, now: f64) -> f64 {
+        self.0.time_to_live(time(now)).as_secs_f64() * 1000.0
+    }
+
+    /// If the response is stale at `now`
+    pub fn stale(&self, now: f64) -> bool {
+        self.0.is_stale(time(now))
+    }
+
+    /// The age of the response at `now` in seconds
+    pub fn age(&self, now: f64) -> f64 {
+        self.0.age(time(now)).as_secs_f64()
+    }
+
+    /// How long the response is fresh for in seconds
+    #[wasm_bindgen(js_name = maxAge)]
+    pub fn max_age(&self) -> f64 {
+        self.0.freshness_lifetime().as_secs_f64()
+    }
+
+    /// Converts the policy into the format of `http-cache-semantics`' `toObject()`
+    ///
+    /// See [`CachePolicy::to_js_json()`]
+    #[wasm_bindgen(js_name = toObject)]
+    pub fn to_object(&self) -> Result<JsValue, JsError> {
+        to_js(&self.0.to_js_json())
+    }
+
+    /// Reads a policy in the format of `http-cache-semantics`' `toObject()`
+    ///
+    /// See [`CachePolicy::from_js_json()`]
+    #[wasm_bindgen(js_name = fromObject)]
+    pub fn from_object(object: &JsValue) -> Result<WasmCachePolicy, JsError> {
+        let json = JSON::stringify(object)
+            .map_err(|_| JsError::new("policy isn't serializable"))?
+            .as_string()
+            .unwrap_or_default();
+        let value = serde_json::from_str(&json).map_err(js_error)?;
+        Ok(Self(CachePolicy::from_js_json(&value).map_err(js_error)?))
+    }
+}
+
+impl From<CachePolicy> for WasmCachePolicy {
+    fn from(policy: CachePolicy) -> Self {
+        Self(policy)
+    }
+}
+
+impl From<WasmCachePolicy> for CachePolicy {
+    fn from(policy: WasmCachePolicy) -> Self {
+        policy.0
+    }
+}
+
+/// Milliseconds since the Unix epoch as a `SystemTime`
+fn time(millis: f64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis.max(0.0) as u64)
+}
+
+fn js_error(err: impl fmt::Display) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+fn to_js(value: &serde_json::Value) -> Result<JsValue, JsError> {
+    JSON::parse(&value.to_string()).map_err(|_| JsError::new("invalid JSON"))
+}
+
+fn field(object: &JsValue, name: &str) -> Result<JsValue, JsError> {
+    Reflect::get(object, &JsValue::from_str(name))
+        .map_err(|_| js_error(format!("missing `{}`", name)))
+}
+
+fn request(value: &JsValue) -> Result<(Uri, Method, HeaderMap), JsError> {
+    let method = field(value, "method")?
+        .as_string()
+        .unwrap_or_else(|| "GET".into());
+    let uri = field(value, "url")?
+        .as_string()
+        .ok_or_else(|| JsError::new("missing request `url`"))?;
+    let uri: Uri = uri.parse().map_err(|err| js_error(BuildError::from(err)))?;
+    let method =
+        Method::from_bytes(method.as_bytes()).map_err(|err| js_error(BuildError::from(err)))?;
+    Ok((uri, method, headers(&field(value, "headers")?)?))
+}
+
+fn response(value: &JsValue) -> Result<(StatusCode, HeaderMap), JsError> {
+    let status = field(value, "status")?
+        .as_f64()
+        .ok_or_else(|| JsError::new("missing response `status`"))?;
+    let status =
+        StatusCode::from_u16(status as u16).map_err(|err| js_error(BuildError::from(err)))?;
+    Ok((status, headers(&field(value, "headers")?)?))
+}
+
+/// Reads headers from a fetch `Headers` or a plain object
+fn headers(value: &JsValue) -> Result<HeaderMap, JsError> {
+    let mut headers = HeaderMap::new();
+    if value.is_undefined() || value.is_null() {
+        return Ok(headers);
+    }
+
+    let entries: Vec<JsValue> =
+        match js_sys::try_iter(value).map_err(|_| JsError::new("invalid headers"))? {
+            Some(iter) => iter
+                .collect::<Result<_, _>>()
+                .map_err(|_| JsError::new("invalid headers"))?,
+            None => Object::entries(value.unchecked_ref()).iter().collect(),
+        };
+    for entry in entries {
+        let entry: Array = entry.unchecked_into();
+        let name = entry.get(0).as_string().unwrap_or_default();
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|err| js_error(BuildError::from(err)))?;
+        let value = entry.get(1);
+        let values = if Array::is_array(&value) {
+            Array::from(&value).iter().collect()
+        } else {
+            vec![value]
+        };
+        for value in values {
+            let value = value
+                .as_string()
+                .or_else(|| value.as_f64().map(|number| number.to_string()))
+                .unwrap_or_default();
+            let value =
+                HeaderValue::from_str(&value).map_err(|err| js_error(BuildError::from(err)))?;
+            headers.append(&name, value);
+        }
+    }
+    Ok(headers)
+}
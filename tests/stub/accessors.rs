@@ -0,0 +1,450 @@
+use http::{header, Method, Request, Response, StatusCode};
+use http_cache_policy::{
+    config::{Mode, StoredRequestHeaders},
+    CachePolicy, Config,
+};
+use std::time::{Duration, SystemTime};
+
+use crate::{request_parts, response_parts};
+
+#[test]
+fn exposes_stored_metadata() {
+    let policy = CachePolicy::new(
+        &request_parts(
+            Request::builder()
+                .method(Method::HEAD)
+                .uri("https://example.com/a")
+                .header(header::ACCEPT, "text/html"),
+        ),
+        &response_parts(
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header(header::CACHE_CONTROL, "max-age=60"),
+        ),
+    );
+
+    assert_eq!(policy.uri(), "https://example.com/a");
+    assert_eq!(policy.method(), Method::HEAD);
+    assert_eq!(policy.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        policy.request_headers().get(header::ACCEPT).unwrap(),
+        "text/html"
+    );
+    assert_eq!(
+        policy
+            .response_headers()
+            .get(header::CACHE_CONTROL)
+            .unwrap(),
+        "max-age=60"
+    );
+}
+
+#[test]
+fn exposes_response_time_and_date() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let date = now - Duration::from_secs(5);
+    let req = request_parts(Request::builder());
+    let policy = CachePolicy::with_config(
+        &req,
+        &response_parts(Response::builder().header(header::DATE, httpdate::fmt_http_date(date))),
+        now,
+        Default::default(),
+    );
+    assert_eq!(policy.response_time(), now);
+    assert_eq!(policy.date(), date);
+
+    let policy = CachePolicy::with_config(
+        &req,
+        &response_parts(Response::builder().header(header::DATE, "yesterday")),
+        now,
+        Default::default(),
+    );
+    assert_eq!(policy.date(), now);
+}
+
+#[test]
+fn exposes_options() {
+    let req = request_parts(Request::builder());
+    let res = response_parts(Response::builder());
+    let policy = CachePolicy::with_config(
+        &req,
+        &res,
+        SystemTime::now(),
+        Config::default()
+            .mode(Mode::Private)
+            .ignore_cargo_cult(true),
+    );
+    assert_eq!(policy.options().mode, Mode::Private);
+    assert!(policy.options().ignore_cargo_cult);
+
+    let policy = CachePolicy::new(&req, &res);
+    assert_eq!(policy.options().mode, Mode::Shared);
+    assert!(!policy.options().ignore_cargo_cult);
+}
+
+#[test]
+fn reevaluates_under_new_options() {
+    let now = SystemTime::now();
+    let shared = CachePolicy::with_config(
+        &request_parts(Request::builder()),
+        &response_parts(Response::builder().header(header::CACHE_CONTROL, "private, max-age=100")),
+        now,
+        Default::default(),
+    );
+    assert!(!shared.is_storable());
+
+    let private = shared.with_options(Config::default().mode(Mode::Private));
+    assert!(private.is_storable());
+    assert_eq!(private.options().mode, Mode::Private);
+    assert_eq!(private.response_time(), now);
+    assert_eq!(private.time_to_live(now), Duration::from_secs(100));
+    assert!(!private.with_options(Config::default()).is_storable());
+}
+
+#[test]
+fn answers_for_either_mode() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::with_config(
+        &request_parts(Request::builder()),
+        &response_parts(
+            Response::builder().header(header::CACHE_CONTROL, "private, max-age=100, s-maxage=10"),
+        ),
+        now,
+        Default::default(),
+    );
+    assert!(!policy.is_storable_for(Mode::Shared));
+    assert!(policy.is_storable_for(Mode::Private));
+    assert_eq!(policy.time_to_live_for(Mode::Shared, now), Duration::ZERO);
+    assert_eq!(
+        policy.time_to_live_for(Mode::Private, now),
+        Duration::from_secs(100)
+    );
+    assert_eq!(policy.time_to_live(now), Duration::ZERO);
+
+    let policy = CachePolicy::with_config(
+        &request_parts(Request::builder()),
+        &response_parts(
+            Response::builder().header(header::CACHE_CONTROL, "max-age=100, s-maxage=10"),
+        ),
+        now,
+        Default::default(),
+    );
+    assert_eq!(
+        policy.time_to_live_for(Mode::Shared, now),
+        Duration::from_secs(10)
+    );
+    assert_eq!(
+        policy.time_to_live_for(Mode::Private, now),
+        Duration::from_secs(100)
+    );
+}
+
+#[test]
+fn summarizes_policies() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let summary = |res: http::response::Builder| {
+        CachePolicy::with_config(
+            &request_parts(Request::builder().uri("https://example.com/a")),
+            &response_parts(res),
+            now,
+            Default::default(),
+        )
+        .summary(now)
+        .to_string()
+    };
+
+    assert_eq!(
+        summary(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=60")
+                .header(header::LAST_MODIFIED, "Mon, 07 Oct 2024 12:00:00 GMT")
+        ),
+        "GET https://example.com/a 200 OK storable ttl=60s \
+         last-modified=\"Mon, 07 Oct 2024 12:00:00 GMT\""
+    );
+    assert_eq!(
+        summary(
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header(header::CACHE_CONTROL, "private")
+        ),
+        "GET https://example.com/a 404 Not Found not storable (Private)"
+    );
+}
+
+#[test]
+fn new_at_uses_the_given_time() {
+    let then = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let policy = CachePolicy::new_at(
+        &request_parts(Request::builder()),
+        &response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=60")),
+        then,
+    );
+    assert_eq!(policy.response_time(), then);
+    assert_eq!(policy.options().mode, Mode::Shared);
+    assert_eq!(policy.time_to_live(then), Duration::from_secs(60));
+    assert!(policy.is_stale(then + Duration::from_secs(60)));
+}
+
+#[test]
+fn minimize_keeps_decisions() {
+    let now = SystemTime::now();
+    let request = request_parts(
+        Request::builder()
+            .uri("https://example.com/a")
+            .header(header::HOST, "example.com")
+            .header(header::ACCEPT_LANGUAGE, "en")
+            .header(header::USER_AGENT, "test")
+            .header(header::COOKIE, "a=1"),
+    );
+    let response = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::VARY, "accept-language")
+            .header(header::ETAG, "\"v1\"")
+            .header(header::CONTENT_TYPE, "text/html")
+            .header(header::CONTENT_SECURITY_POLICY, "default-src 'self'")
+            .header("x-trace", "1")
+            .header("x-trace", "2"),
+    );
+    let policy = CachePolicy::with_config(&request, &response, now, Config::default());
+    let mut minimized = policy.clone();
+    minimized.minimize();
+
+    let req_names: Vec<_> = minimized.request_headers().keys().collect();
+    assert_eq!(req_names, [header::HOST, header::ACCEPT_LANGUAGE]);
+    let res_names: Vec<_> = minimized.response_headers().keys().collect();
+    assert_eq!(
+        res_names,
+        [
+            header::CACHE_CONTROL,
+            header::VARY,
+            header::ETAG,
+            header::DATE
+        ]
+    );
+
+    let later = now + Duration::from_secs(120);
+    assert_eq!(minimized.is_storable(), policy.is_storable());
+    assert_eq!(minimized.time_to_live(now), policy.time_to_live(now));
+    assert_eq!(minimized.cache_key(), policy.cache_key());
+    assert!(minimized.matches(&request));
+    assert_eq!(
+        minimized.freshness(&request, later),
+        policy.freshness(&request, later)
+    );
+    match minimized.before_request(&request, later) {
+        http_cache_policy::BeforeRequest::Stale { request, .. } => {
+            assert_eq!(request.headers[header::IF_NONE_MATCH], "\"v1\"");
+        }
+        http_cache_policy::BeforeRequest::Fresh(_) => panic!("should be stale"),
+    }
+}
+
+#[test]
+fn minimize_headers_config() {
+    let now = SystemTime::now();
+    let request = request_parts(
+        Request::builder()
+            .header(header::HOST, "example.com")
+            .header(header::ACCEPT_LANGUAGE, "en")
+            .header(header::USER_AGENT, "test"),
+    );
+    let response = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::VARY, "accept-language")
+            .header(header::CONTENT_TYPE, "text/html")
+            .header("x-trace", "1"),
+    );
+    let full = CachePolicy::with_config(&request, &response, now, Config::default());
+    let policy = CachePolicy::with_config(
+        &request,
+        &response,
+        now,
+        Config::default().minimize_headers(true),
+    );
+
+    let mut minimized = full.clone();
+    minimized.minimize();
+    assert_eq!(policy.request_headers(), minimized.request_headers());
+    assert_eq!(policy.response_headers(), minimized.response_headers());
+    assert_eq!(policy.time_to_live(now), full.time_to_live(now));
+    assert!(policy.matches(&request));
+
+    let mut headers = response.headers.clone();
+    policy.write_cached_headers(&mut headers, now);
+    assert_eq!(headers[header::CONTENT_TYPE], "text/html");
+    assert_eq!(headers["x-trace"], "1");
+}
+
+#[test]
+fn owned_parts_match_borrowed_ones() {
+    let now = SystemTime::now();
+    let request = request_parts(
+        Request::builder()
+            .uri("https://example.com/a?b")
+            .header(header::HOST, "example.com")
+            .header(header::ACCEPT_LANGUAGE, "en"),
+    );
+    let response = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::VARY, "accept-language")
+            .header(header::ETAG, "\"v1\""),
+    );
+    let config = Config::default().mode(Mode::Private);
+    let borrowed = CachePolicy::with_config(&request, &response, now, config.clone());
+    let owned =
+        CachePolicy::from_owned_parts(request.clone(), response.clone(), now, config.clone());
+
+    assert_eq!(owned.identity(), borrowed.identity());
+    assert_eq!(owned.request_headers(), borrowed.request_headers());
+    assert_eq!(owned.response_headers(), borrowed.response_headers());
+    assert_eq!(owned.time_to_live(now), borrowed.time_to_live(now));
+    assert_eq!(owned.options().mode, Mode::Private);
+
+    let converted = CachePolicy::from((request.clone(), response));
+    assert_eq!(converted.cache_key(), borrowed.cache_key());
+    assert!(converted.is_storable());
+    assert!(converted.matches(&request));
+}
+
+#[test]
+fn approx_size_follows_stored_headers() {
+    let request = request_parts(Request::builder().uri("https://example.com/a"));
+    let small = CachePolicy::new(
+        &request,
+        &response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=60")),
+    );
+    let large = CachePolicy::new(
+        &request,
+        &response_parts(
+            Response::builder()
+                .header(
+                    header::CACHE_CONTROL,
+                    "max-age=60, x-extension=\"some value\"",
+                )
+                .header(
+                    header::CONTENT_SECURITY_POLICY,
+                    "default-src 'self'; ".repeat(50),
+                ),
+        ),
+    );
+    assert!(small.approx_size() > std::mem::size_of::<CachePolicy>());
+    assert!(large.approx_size() > small.approx_size() + 1000);
+
+    let mut minimized = large.clone();
+    minimized.minimize();
+    assert!(minimized.approx_size() < large.approx_size());
+}
+
+#[test]
+fn clones_share_headers() {
+    let request = request_parts(
+        Request::builder()
+            .header(header::HOST, "example.com")
+            .header(header::USER_AGENT, "test"),
+    );
+    let response = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::CONTENT_TYPE, "text/html"),
+    );
+    let policy = CachePolicy::new(&request, &response);
+
+    let clone = policy.clone();
+    assert!(std::ptr::eq(
+        clone.request_headers(),
+        policy.request_headers()
+    ));
+    assert!(std::ptr::eq(
+        clone.response_headers(),
+        policy.response_headers()
+    ));
+    let private = policy.with_options(Config::default().mode(Mode::Private));
+    assert!(std::ptr::eq(
+        private.request_headers(),
+        policy.request_headers()
+    ));
+    assert!(std::ptr::eq(
+        private.response_headers(),
+        policy.response_headers()
+    ));
+
+    let mut minimized = policy.clone();
+    minimized.minimize();
+    assert!(!std::ptr::eq(
+        minimized.request_headers(),
+        policy.request_headers()
+    ));
+    assert_eq!(policy.request_headers().len(), 2);
+    // Along with the `Date` synthesized from the response time
+    assert_eq!(policy.response_headers().len(), 3);
+}
+
+#[test]
+fn vary_stored_request_headers_keep_decisions() {
+    let now = SystemTime::now();
+    let request = request_parts(
+        Request::builder()
+            .uri("https://example.com/a")
+            .header(header::HOST, "example.com")
+            .header(header::ACCEPT_LANGUAGE, "en")
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .header(header::COOKIE, "a=1"),
+    );
+    let response = |cache_control| {
+        response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, cache_control)
+                .header(header::VARY, "accept-language"),
+        )
+    };
+    let config = Config::default().stored_request_headers(StoredRequestHeaders::Vary);
+
+    let public = CachePolicy::with_config(
+        &request,
+        &response("public, max-age=60"),
+        now,
+        config.clone(),
+    );
+    let req_names: Vec<_> = public.request_headers().keys().collect();
+    assert_eq!(
+        req_names,
+        [header::HOST, header::ACCEPT_LANGUAGE, header::AUTHORIZATION]
+    );
+    assert!(public.is_storable());
+    assert!(public.matches(&request));
+
+    // Only a placeholder for the credential is kept, so users can't be told apart
+    let authenticated =
+        CachePolicy::with_config(&request, &response("s-maxage=60"), now, config.clone());
+    assert_ne!(
+        authenticated.request_headers()[header::AUTHORIZATION],
+        "Bearer secret"
+    );
+    assert!(!authenticated.matches(&request));
+    let other_user = request_parts(
+        Request::builder()
+            .uri("https://example.com/a")
+            .header(header::HOST, "example.com")
+            .header(header::ACCEPT_LANGUAGE, "en")
+            .header(header::AUTHORIZATION, "Bearer other"),
+    );
+    assert!(!authenticated.matches(&other_user));
+
+    let private = CachePolicy::with_config(
+        &request,
+        &response("max-age=60"),
+        now,
+        config.clone().mode(Mode::Private),
+    );
+    assert!(private.is_storable());
+    assert!(private.matches(&request));
+
+    // The credential still counts when the policy is re-evaluated for a shared cache
+    let shared = private.with_options(config);
+    assert!(!shared.is_storable());
+    assert!(!private.is_storable_for(Mode::Shared));
+}
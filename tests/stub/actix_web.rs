@@ -0,0 +1,53 @@
+use actix_web::{test::TestRequest, HttpResponse};
+use http::{header, Method};
+use http_cache_policy::{actix_web as convert, CachePolicy};
+
+#[test]
+fn converts_requests_and_responses() {
+    let req = TestRequest::get()
+        .uri("/a?b=c")
+        .insert_header(("host", "example.com"))
+        .insert_header(("accept", "text/html"))
+        .to_http_request();
+    let (uri, method, headers) = convert::request(&req).unwrap();
+    assert_eq!(uri, "http://example.com/a?b=c");
+    assert_eq!(method, Method::GET);
+    assert_eq!(headers[header::ACCEPT], "text/html");
+
+    let res = HttpResponse::Ok()
+        .append_header(("set-cookie", "a=1"))
+        .append_header(("set-cookie", "b=2"))
+        .finish();
+    let (status, headers) = convert::response(&res).unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(headers.get_all(header::SET_COOKIE).iter().count(), 2);
+}
+
+#[test]
+fn evaluates_outgoing_responses() {
+    let req = TestRequest::get().uri("/a").to_http_request();
+    let res = HttpResponse::Ok()
+        .insert_header(("cache-control", "max-age=60"))
+        .insert_header(("vary", "accept"))
+        .finish();
+    let policy = CachePolicy::new(
+        &convert::request(&req).unwrap(),
+        &convert::response(&res).unwrap(),
+    );
+    assert!(policy.is_storable());
+
+    let other = TestRequest::get()
+        .uri("/a")
+        .insert_header(("accept", "text/html"))
+        .to_http_request();
+    assert!(!policy.matches(&convert::request(&other).unwrap()));
+
+    let res = HttpResponse::Ok()
+        .insert_header(("cache-control", "no-store"))
+        .finish();
+    let policy = CachePolicy::new(
+        &convert::request(&req).unwrap(),
+        &convert::response(&res).unwrap(),
+    );
+    assert!(!policy.is_storable());
+}
@@ -0,0 +1,112 @@
+use http::{header, HeaderMap, Request, Response};
+use http_cache_policy::{
+    borrowed::CachePolicyRef,
+    config::{HeaderNames, Mode},
+    BeforeRequest, CachePolicy, Config,
+};
+use std::time::{Duration, SystemTime};
+
+use crate::{request_parts, response_parts};
+
+fn assert_same_headers(a: &HeaderMap, b: &HeaderMap) {
+    for name in a.keys().chain(b.keys()) {
+        assert!(
+            a.get_all(name).iter().eq(b.get_all(name).iter()),
+            "{} differs",
+            name
+        );
+    }
+}
+
+#[test]
+fn matches_the_owned_response() {
+    let now = SystemTime::now();
+    let day = Duration::from_secs(3600 * 24);
+    let responses = [
+        (
+            now,
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=60")
+                .header(header::CONTENT_TYPE, "text/plain")
+                .header(header::AGE, "10"),
+        ),
+        (
+            now,
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=60")
+                .header(header::CONNECTION, "x-hop")
+                .header("x-hop", "1")
+                .header(header::TRANSFER_ENCODING, "chunked")
+                .header(header::WARNING, r#"110 - "stale", 299 - "kept""#),
+        ),
+        // Heuristically fresh for over a day, which adds a 113 warning
+        (
+            now - day * 2,
+            Response::builder()
+                .header(header::DATE, httpdate::fmt_http_date(now - day * 2))
+                .header(
+                    header::LAST_MODIFIED,
+                    httpdate::fmt_http_date(now - day * 400),
+                )
+                .header(header::WARNING, r#"199 - "dropped""#),
+        ),
+    ];
+    let req = request_parts(Request::builder());
+
+    for (response_time, response) in responses {
+        let policy = CachePolicy::new_at(&req, &response_parts(response), response_time);
+        let fresh = match policy.before_request(&req, now) {
+            BeforeRequest::Fresh(parts) => parts,
+            BeforeRequest::Stale { .. } => panic!("should be fresh"),
+        };
+        let cached = policy.cached_response_ref(now);
+        assert_eq!(cached.status(), fresh.status);
+        assert_same_headers(&cached.to_parts().headers, &fresh.headers);
+        assert_eq!(cached.get(&header::AGE), fresh.headers.get(header::AGE));
+        assert_eq!(cached.headers().count(), fresh.headers.len());
+    }
+}
+
+#[test]
+fn evaluates_like_the_owned_policy() {
+    let now = SystemTime::now();
+    let configs = [
+        Config::default(),
+        Config::default().mode(Mode::Private),
+        Config::default().ignore_cargo_cult(true),
+        Config::default().strip_response_headers(HeaderNames::from_static(&[header::SET_COOKIE])),
+    ];
+    let responses = [
+        Response::builder().header(header::CACHE_CONTROL, "max-age=60"),
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::SET_COOKIE, "a=b")
+            .header(header::AGE, "10"),
+        Response::builder().header(
+            header::CACHE_CONTROL,
+            "no-cache, no-store, max-age=100, pre-check=0, post-check=0",
+        ),
+        Response::builder().header(header::CACHE_CONTROL, "private, max-age=30"),
+        Response::builder().header(header::EXPIRES, "0"),
+    ]
+    .map(response_parts);
+    let req = request_parts(Request::builder());
+
+    for config in &configs {
+        for res in &responses {
+            let policy = CachePolicy::with_config(&req, res, now, config.clone());
+            let view = CachePolicyRef::with_config(&req, res, now, config);
+            assert_eq!(view.storability(), policy.storability());
+            assert_eq!(view.freshness_lifetime(), policy.freshness_lifetime());
+            assert_eq!(view.time_to_live(now), policy.time_to_live(now));
+
+            let owned = view.into_policy();
+            assert_eq!(owned.storability(), policy.storability());
+            assert_eq!(owned.time_to_live(now), policy.time_to_live(now));
+            assert_same_headers(
+                &owned.cached_response_ref(now).to_parts().headers,
+                &policy.cached_response_ref(now).to_parts().headers,
+            );
+        }
+    }
+}
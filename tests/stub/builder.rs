@@ -0,0 +1,72 @@
+use http::{header, Request, Response};
+use http_cache_policy::{builder::BuildError, CachePolicy, CachePolicyBuilder};
+use std::time::{Duration, SystemTime};
+
+use crate::{request_parts, response_parts};
+
+#[test]
+fn builds_the_same_policy_as_http_types() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let built = CachePolicyBuilder::new()
+        .method("HEAD")
+        .uri("https://example.com/a?b")
+        .request_header("Accept-Language", "en")
+        .status(" 404 ")
+        .response_header("cache-control", "public")
+        .response_header("cache-control", "max-age=60")
+        .response_header("vary", "accept-language")
+        .response_time(now)
+        .build()
+        .unwrap();
+    let expected = CachePolicy::with_config(
+        &request_parts(
+            Request::builder()
+                .method("HEAD")
+                .uri("https://example.com/a?b")
+                .header(header::ACCEPT_LANGUAGE, "en"),
+        ),
+        &response_parts(
+            Response::builder()
+                .status(404)
+                .header(header::CACHE_CONTROL, "public")
+                .header(header::CACHE_CONTROL, "max-age=60")
+                .header(header::VARY, "accept-language"),
+        ),
+        now,
+        Default::default(),
+    );
+
+    assert_eq!(built.identity(), expected.identity());
+    assert_eq!(built.response_time(), now);
+    assert_eq!(built.time_to_live(now), Duration::from_secs(60));
+    assert_eq!(built.request_headers(), expected.request_headers());
+    assert_eq!(built.response_headers(), expected.response_headers());
+}
+
+#[test]
+fn reports_the_first_invalid_value() {
+    let err = |builder: CachePolicyBuilder| builder.build().unwrap_err();
+
+    assert!(matches!(
+        err(CachePolicyBuilder::new().method("GE T")),
+        BuildError::Method(_)
+    ));
+    assert!(matches!(
+        err(CachePolicyBuilder::new().uri("https://exa mple.com")),
+        BuildError::Uri(_)
+    ));
+    assert!(matches!(
+        err(CachePolicyBuilder::new().status("abc")),
+        BuildError::Status(_)
+    ));
+    assert!(matches!(
+        err(CachePolicyBuilder::new()
+            .request_header("bad name", "value")
+            .status("abc")),
+        BuildError::HeaderName(_)
+    ));
+    assert!(matches!(
+        err(CachePolicyBuilder::new().response_header("x-ok", "line\nbreak")),
+        BuildError::HeaderValue(_)
+    ));
+}
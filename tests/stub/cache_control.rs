@@ -0,0 +1,130 @@
+use http::{header, HeaderValue, Request, Response};
+use http_cache_policy::{
+    cache_control::{CacheControl, CacheControlBuilder, Directive},
+    CachePolicy,
+};
+
+use crate::{request_parts, response_parts};
+
+fn pairs(cc: &CacheControl) -> Vec<(&str, Option<&str>)> {
+    cc.directives()
+        .iter()
+        .map(|d| (d.name(), d.value()))
+        .collect()
+}
+
+#[test]
+fn parses_tokens_and_quoted_strings() {
+    let cc = CacheControl::parse(
+        r#"public, MAX-AGE=60, no-cache="set-cookie, x-foo", x-esc="a\"b\\c", ,  s-maxage = "10" "#,
+    );
+    assert_eq!(
+        pairs(&cc),
+        [
+            ("public", None),
+            ("max-age", Some("60")),
+            ("no-cache", Some("set-cookie, x-foo")),
+            ("x-esc", Some(r#"a"b\c"#)),
+            ("s-maxage", Some("10")),
+        ]
+    );
+}
+
+#[test]
+fn skips_malformed_members() {
+    let cc = CacheControl::parse(r#"=60, bad name, x="unterminated, still"#);
+    assert_eq!(pairs(&cc), [("x", Some("unterminated, still"))]);
+
+    let cc = CacheControl::parse(r#"private="a" junk, max-age=5"#);
+    assert_eq!(pairs(&cc), [("private", Some("a")), ("max-age", Some("5"))]);
+}
+
+#[test]
+fn round_trips() {
+    for s in [
+        "max-age=60, private",
+        r#"no-cache="set-cookie, x-foo", x-empty="""#,
+        r#"x-esc="a\"b\\c""#,
+    ] {
+        let cc = CacheControl::parse(s);
+        assert_eq!(cc.to_string(), s);
+        assert_eq!(CacheControl::parse(&cc.to_string()), cc);
+    }
+}
+
+#[test]
+fn constructs_directives() {
+    let cc: CacheControl = [
+        Directive::new("Public", None).unwrap(),
+        Directive::new("x-note", Some("hello world")).unwrap(),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        cc.to_header_value(),
+        HeaderValue::from_static(r#"public, x-note="hello world""#)
+    );
+    assert!(Directive::new("bad name", None).is_none());
+    assert!(Directive::new("x", Some("line\nbreak")).is_none());
+}
+
+#[test]
+fn joins_multiple_headers() {
+    let headers = [
+        HeaderValue::from_static("max-age=60"),
+        HeaderValue::from_static("public"),
+    ];
+    let cc = CacheControl::from_headers(&headers);
+    assert_eq!(pairs(&cc), [("max-age", Some("60")), ("public", None)]);
+}
+
+#[test]
+fn quoted_commas_dont_split_directives() {
+    let policy = CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(Response::builder().header(
+            header::CACHE_CONTROL,
+            r#"private="a, max-age=0", max-age=60"#,
+        )),
+    );
+    assert_eq!(
+        policy.response_directives().max_age(),
+        Some(std::time::Duration::from_secs(60))
+    );
+}
+
+#[test]
+fn builds_headers() {
+    let cc = CacheControlBuilder::new()
+        .private()
+        .no_cache()
+        .max_age(10)
+        .directive(Directive::new("x-fields", Some("a, b")).unwrap())
+        .max_age(20)
+        .must_revalidate()
+        .build();
+    assert_eq!(
+        cc,
+        r#"private, no-cache, max-age=20, x-fields="a, b", must-revalidate"#
+    );
+
+    let policy = CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(
+            Response::builder().header(
+                header::CACHE_CONTROL,
+                CacheControlBuilder::new()
+                    .public()
+                    .s_maxage(60)
+                    .immutable()
+                    .build(),
+            ),
+        ),
+    );
+    let directives = policy.response_directives();
+    assert!(directives.is_public() && directives.immutable());
+    assert_eq!(
+        directives.s_maxage(),
+        Some(std::time::Duration::from_secs(60))
+    );
+}
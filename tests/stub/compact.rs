@@ -0,0 +1,91 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use http::{header, Request, Response};
+use http_cache_policy::{compact::DecodeError, CachePolicy, Config, WithBodyDigest};
+
+use crate::{private_config, request_parts, response_parts};
+
+fn policy() -> CachePolicy {
+    CachePolicy::new_at(
+        &request_parts(Request::builder().uri("/a").header(header::ACCEPT, "*/*")),
+        &response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=60")
+                .header(header::SET_COOKIE, "a=1")
+                .header(header::SET_COOKIE, "b=2"),
+        ),
+        UNIX_EPOCH + Duration::new(1_700_000_000, 5),
+    )
+}
+
+#[test]
+fn round_trips() {
+    let policy = policy();
+    let restored = CachePolicy::from_bytes(&policy.to_bytes(), Config::default()).unwrap();
+    assert_eq!(restored.identity(), policy.identity());
+    assert_eq!(restored.response_time(), policy.response_time());
+    assert_eq!(restored.request_headers(), policy.request_headers());
+    assert_eq!(restored.response_headers(), policy.response_headers());
+
+    let restored = CachePolicy::from_bytes(&policy.to_bytes(), private_config()).unwrap();
+    assert!(!restored.options().mode.is_shared());
+}
+
+#[test]
+fn keeps_body_digests() {
+    let req = request_parts(Request::builder().method("QUERY"));
+    let digest = [7; 4];
+    let policy = CachePolicy::new(
+        &WithBodyDigest::new(&req, &digest),
+        &response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=60")),
+    );
+    let restored = CachePolicy::from_bytes(&policy.to_bytes(), Config::default()).unwrap();
+    assert_eq!(restored.identity(), policy.identity());
+}
+
+#[test]
+fn version_1_is_stable() {
+    #[rustfmt::skip]
+    let expected: &[u8] = &[
+        1,
+        0, 0, 0, 3, b'G', b'E', b'T',
+        0, 0, 0, 2, b'/', b'a',
+        0, 200,
+        0, 0, 0, 0, 0x65, 0x53, 0xf1, 0x00, 0, 0, 0, 5,
+        0, 0, 0, 1,
+        0, 0, 0, 6, b'a', b'c', b'c', b'e', b'p', b't', 0, 0, 0, 3, b'*', b'/', b'*',
+        0, 0, 0, 4,
+        0, 0, 0, 13, b'c', b'a', b'c', b'h', b'e', b'-', b'c', b'o', b'n', b't', b'r', b'o', b'l',
+        0, 0, 0, 10, b'm', b'a', b'x', b'-', b'a', b'g', b'e', b'=', b'6', b'0',
+        0, 0, 0, 10, b's', b'e', b't', b'-', b'c', b'o', b'o', b'k', b'i', b'e',
+        0, 0, 0, 3, b'a', b'=', b'1',
+        0, 0, 0, 10, b's', b'e', b't', b'-', b'c', b'o', b'o', b'k', b'i', b'e',
+        0, 0, 0, 3, b'b', b'=', b'2',
+        0, 0, 0, 4, b'd', b'a', b't', b'e',
+        0, 0, 0, 29, b'T', b'u', b'e', b',', b' ', b'1', b'4', b' ', b'N', b'o', b'v', b' ',
+        b'2', b'0', b'2', b'3', b' ', b'2', b'2', b':', b'1', b'3', b':', b'2', b'0', b' ',
+        b'G', b'M', b'T',
+        0,
+    ];
+    assert_eq!(policy().to_bytes(), expected);
+    assert!(CachePolicy::from_bytes(expected, Config::default()).is_ok());
+}
+
+#[test]
+fn rejects_invalid_bytes() {
+    let bytes = policy().to_bytes();
+    let decode = |bytes: &[u8]| CachePolicy::from_bytes(bytes, Config::default()).unwrap_err();
+
+    let mut unknown = bytes.clone();
+    unknown[0] = 2;
+    assert_eq!(decode(&unknown), DecodeError::UnsupportedVersion(2));
+    assert_eq!(
+        decode(&bytes[..bytes.len() - 1]),
+        DecodeError::UnexpectedEnd
+    );
+    assert_eq!(
+        decode(&[bytes.as_slice(), &[0]].concat()),
+        DecodeError::Invalid
+    );
+    assert_eq!(decode(&[]), DecodeError::UnexpectedEnd);
+}
@@ -0,0 +1,145 @@
+use http::{header, Method, Request, Response, StatusCode};
+use http_cache_policy::{CachePolicy, Conditional};
+use std::time::SystemTime;
+
+use crate::{request_parts, response_parts};
+
+const LAST_MODIFIED: &str = "Tue, 15 Nov 1994 12:45:26 GMT";
+
+fn stored_policy() -> CachePolicy {
+    CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=3600")
+                .header(header::ETAG, "\"abc\"")
+                .header(header::LAST_MODIFIED, LAST_MODIFIED)
+                .header(header::CONTENT_TYPE, "text/plain")
+                .header(header::VARY, "accept"),
+        ),
+    )
+}
+
+fn evaluate(builder: http::request::Builder) -> Conditional {
+    stored_policy().evaluate_conditional(&request_parts(builder), SystemTime::now())
+}
+
+#[test]
+fn unconditional_requests_get_the_full_response() {
+    assert!(matches!(evaluate(Request::builder()), Conditional::Full));
+}
+
+#[test]
+fn matching_if_none_match_is_not_modified() {
+    for etag in ["\"abc\"", "W/\"abc\"", "\"x\", \"abc\"", "*"] {
+        match evaluate(Request::builder().header(header::IF_NONE_MATCH, etag)) {
+            Conditional::NotModified(parts) => {
+                assert_eq!(parts.status, StatusCode::NOT_MODIFIED);
+                assert_eq!(parts.headers.get(header::ETAG).unwrap(), "\"abc\"");
+                assert_eq!(parts.headers.get(header::VARY).unwrap(), "accept");
+                assert!(parts.headers.contains_key(header::AGE));
+                assert!(parts.headers.contains_key(header::DATE));
+                assert!(!parts.headers.contains_key(header::CONTENT_TYPE));
+                assert!(!parts.headers.contains_key(header::LAST_MODIFIED));
+            }
+            other => panic!("{etag}: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn mismatched_if_none_match_is_full_even_with_if_modified_since() {
+    let conditional = evaluate(
+        Request::builder()
+            .header(header::IF_NONE_MATCH, "\"other\"")
+            .header(header::IF_MODIFIED_SINCE, LAST_MODIFIED),
+    );
+    assert!(matches!(conditional, Conditional::Full));
+}
+
+#[test]
+fn if_none_match_on_unsafe_method_fails_precondition() {
+    let conditional = evaluate(
+        Request::builder()
+            .method(Method::PUT)
+            .header(header::IF_NONE_MATCH, "*"),
+    );
+    assert!(matches!(conditional, Conditional::PreconditionFailed));
+}
+
+#[test]
+fn etags_with_commas() {
+    let policy = CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=3600")
+                .header(header::ETAG, "\"a, b\""),
+        ),
+    );
+    let evaluate = |name, value| {
+        let req = request_parts(Request::builder().header(name, value));
+        policy.evaluate_conditional(&req, SystemTime::now())
+    };
+
+    let matching = evaluate(header::IF_NONE_MATCH, "\"x\", W/\"a, b\"");
+    assert!(matches!(matching, Conditional::NotModified(_)));
+    // Splitting on every comma would leave `"a` and `b"` which never match
+    let split = evaluate(header::IF_NONE_MATCH, "\"a\", \"b\"");
+    assert!(matches!(split, Conditional::Full));
+    let strong = evaluate(header::IF_MATCH, "\"a, b\"");
+    assert!(matches!(strong, Conditional::Full));
+    let weak = evaluate(header::IF_MATCH, "W/\"a, b\"");
+    assert!(matches!(weak, Conditional::PreconditionFailed));
+}
+
+#[test]
+fn if_modified_since() {
+    let not_modified = [LAST_MODIFIED, "Wed, 16 Nov 1994 12:45:26 GMT"];
+    for since in not_modified {
+        assert!(
+            evaluate(Request::builder().header(header::IF_MODIFIED_SINCE, since)).is_not_modified(),
+            "{since}"
+        );
+    }
+
+    let modified = ["Mon, 14 Nov 1994 12:45:26 GMT", "not a date"];
+    for since in modified {
+        assert!(
+            matches!(
+                evaluate(Request::builder().header(header::IF_MODIFIED_SINCE, since)),
+                Conditional::Full
+            ),
+            "{since}"
+        );
+    }
+}
+
+#[test]
+fn if_match_uses_strong_comparison() {
+    for (etag, passes) in [
+        ("\"abc\"", true),
+        ("*", true),
+        ("W/\"abc\"", false),
+        ("\"x\"", false),
+    ] {
+        let conditional = evaluate(Request::builder().header(header::IF_MATCH, etag));
+        assert_eq!(
+            !matches!(conditional, Conditional::PreconditionFailed),
+            passes,
+            "{etag}"
+        );
+    }
+}
+
+#[test]
+fn if_unmodified_since() {
+    let conditional = evaluate(
+        Request::builder().header(header::IF_UNMODIFIED_SINCE, "Mon, 14 Nov 1994 12:45:26 GMT"),
+    );
+    assert!(matches!(conditional, Conditional::PreconditionFailed));
+
+    let conditional =
+        evaluate(Request::builder().header(header::IF_UNMODIFIED_SINCE, LAST_MODIFIED));
+    assert!(matches!(conditional, Conditional::Full));
+}
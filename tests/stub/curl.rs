@@ -0,0 +1,67 @@
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+use curl::easy::Easy;
+use http::header;
+use http_cache_policy::curl::{builder, HeaderLines};
+
+#[test]
+fn keeps_the_last_response_headers() {
+    let mut lines = HeaderLines::new();
+    for line in [
+        &b"HTTP/1.1 301 Moved Permanently\r\n"[..],
+        b"Location: /b\r\n",
+        b"\r\n",
+        b"HTTP/1.1 200 OK\r\n",
+        b"Cache-Control: max-age=60\r\n",
+        b"Set-Cookie: a=1\r\n",
+        b"Set-Cookie: b=2\r\n",
+        b"\r\n",
+    ] {
+        assert!(lines.push(line));
+    }
+
+    let headers = lines.headers().unwrap();
+    assert!(!headers.contains_key(header::LOCATION));
+    assert_eq!(headers[header::CACHE_CONTROL], "max-age=60");
+    assert_eq!(headers.get_all(header::SET_COOKIE).iter().count(), 2);
+}
+
+#[test]
+fn builds_from_a_transfer() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0; 1024];
+        let _ = stream.read(&mut buf).unwrap();
+        stream
+            .write_all(
+                b"HTTP/1.1 200 OK\r\n\
+                  Cache-Control: max-age=60\r\n\
+                  Content-Length: 5\r\n\
+                  \r\n\
+                  hello",
+            )
+            .unwrap();
+    });
+
+    let mut easy = Easy::new();
+    easy.url(&format!("http://{}/a", addr)).unwrap();
+    let mut lines = HeaderLines::new();
+    {
+        let mut transfer = easy.transfer();
+        transfer.header_function(|line| lines.push(line)).unwrap();
+        transfer.write_function(|data| Ok(data.len())).unwrap();
+        transfer.perform().unwrap();
+    }
+    server.join().unwrap();
+
+    let policy = builder(&mut easy, &lines).unwrap().build().unwrap();
+    assert!(policy.is_storable());
+    assert_eq!(policy.uri().path(), "/a");
+    assert!(policy.time_to_live(http_cache_policy::now()).as_secs() > 50);
+}
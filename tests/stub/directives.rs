@@ -0,0 +1,150 @@
+use http::{header, Request, Response};
+use http_cache_policy::{directives::RequestDirectives, CachePolicy};
+use std::time::Duration;
+
+use crate::{request_parts, response_parts};
+
+fn policy(cc: &str) -> CachePolicy {
+    CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(Response::builder().header(header::CACHE_CONTROL, cc)),
+    )
+}
+
+#[test]
+fn typed_response_directives() {
+    let policy = policy(
+        "public, max-age=3600, s-maxage=60, stale-while-revalidate=30, stale-if-error=600, \
+         must-revalidate, immutable, x-custom=\"yes\"",
+    );
+    let directives = policy.response_directives();
+    assert_eq!(directives.max_age(), Some(Duration::from_secs(3600)));
+    assert_eq!(directives.s_maxage(), Some(Duration::from_secs(60)));
+    assert_eq!(
+        directives.stale_while_revalidate(),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(directives.stale_if_error(), Some(Duration::from_secs(600)));
+    assert!(directives.is_public());
+    assert!(directives.must_revalidate());
+    assert!(directives.immutable());
+    assert!(!directives.is_private());
+    assert!(!directives.no_cache());
+    assert!(!directives.no_store());
+    assert!(!directives.proxy_revalidate());
+    assert_eq!(directives.get("x-custom"), Some(Some("yes".into())));
+    assert_eq!(directives.get("public"), Some(None));
+    assert_eq!(directives.get("private"), None);
+    assert_eq!(directives.iter().count(), 8);
+}
+
+#[test]
+fn directive_values_keep_their_text() {
+    let policy =
+        policy("max-age=060, s-maxage=99999999999, stale-if-error=0, private=\"x-a\", MAX-STALE");
+    let directives = policy.response_directives();
+    assert_eq!(directives.get("max-age"), Some(Some("060".into())));
+    assert_eq!(directives.max_age(), Some(Duration::from_secs(60)));
+    assert_eq!(directives.get("s-maxage"), Some(Some("99999999999".into())));
+    assert_eq!(
+        directives.s_maxage(),
+        Some(Duration::from_secs(99_999_999_999))
+    );
+    assert_eq!(directives.get("stale-if-error"), Some(Some("0".into())));
+    assert_eq!(directives.get("private"), Some(Some("x-a".into())));
+    assert_eq!(directives.get("max-stale"), Some(None));
+    assert_eq!(directives.iter().count(), 5);
+}
+
+#[test]
+fn extension_directives() {
+    let policy = policy("max-age=60, X-Edge-TTL=300, stale-if-error=10, x-flag, private=\"a\"");
+    assert_eq!(
+        policy.response_directive("X-EDGE-TTL"),
+        Some(Some("300".into()))
+    );
+    assert_eq!(policy.response_directive("x-flag"), Some(None));
+    assert_eq!(
+        policy.response_directive("stale-if-error"),
+        Some(Some("10".into()))
+    );
+    assert_eq!(policy.response_directive("no-store"), None);
+    let extensions: Vec<_> = policy.extension_directives().collect();
+    assert_eq!(
+        extensions,
+        [("x-edge-ttl", Some("300".into())), ("x-flag", None)]
+    );
+}
+
+#[test]
+fn invalid_delta_seconds() {
+    let policy = policy("max-age=soon, s-maxage");
+    let directives = policy.response_directives();
+    assert_eq!(directives.max_age(), Some(Duration::ZERO));
+    assert_eq!(directives.s_maxage(), None);
+}
+
+#[test]
+fn pragma_no_cache_is_folded_in() {
+    let policy = CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(Response::builder().header(header::PRAGMA, "no-cache")),
+    );
+    assert!(policy.response_directives().no_cache());
+}
+
+#[test]
+fn typed_request_directives() {
+    let req = request_parts(
+        Request::builder()
+            .header(header::CACHE_CONTROL, "no-store, max-age=10, min-fresh=5")
+            .header(header::CACHE_CONTROL, "max-stale=30, no-transform"),
+    );
+    let directives = RequestDirectives::from_headers(&req.headers);
+    assert!(directives.no_store);
+    assert!(directives.no_transform);
+    assert!(!directives.no_cache);
+    assert!(!directives.only_if_cached);
+    assert_eq!(directives.max_age, Some(Duration::from_secs(10)));
+    assert_eq!(directives.min_fresh, Some(Duration::from_secs(5)));
+    assert_eq!(directives.max_stale, Some(Some(Duration::from_secs(30))));
+
+    let req = request_parts(Request::builder().header(header::PRAGMA, "no-cache"));
+    let directives = RequestDirectives::from_headers(&req.headers);
+    assert!(directives.no_cache);
+    assert_eq!(directives.max_stale, None);
+
+    let req = request_parts(
+        Request::builder().header(header::CACHE_CONTROL, "max-age=later, max-stale=never"),
+    );
+    let directives = RequestDirectives::from_headers(&req.headers);
+    assert_eq!(directives.max_age, None);
+    assert_eq!(directives.max_stale, Some(None));
+}
+
+#[test]
+fn pre_parsed_request_directives() {
+    let now = std::time::SystemTime::now();
+    let fresh = policy("max-age=60");
+    let stale = policy("max-age=0");
+    for cc in ["", "no-cache", "max-age=0", "max-stale"] {
+        let req = request_parts(Request::builder().header(header::CACHE_CONTROL, cc));
+        let directives = RequestDirectives::from_headers(&req.headers);
+        for policy in [&fresh, &stale] {
+            assert_eq!(
+                policy.satisfies_without_revalidation_with_directives(&req, &directives, now),
+                policy.satisfies_without_revalidation(&req, now),
+                "{}",
+                cc
+            );
+            assert_eq!(
+                policy
+                    .before_request_with_directives(&req, &directives, now)
+                    .is_fresh(),
+                policy.before_request(&req, now).is_fresh(),
+                "{}",
+                cc
+            );
+        }
+    }
+}
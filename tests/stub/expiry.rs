@@ -0,0 +1,196 @@
+use http::{header, Request, Response};
+use http_cache_policy::{AfterResponse, CachePolicy};
+use std::time::{Duration, SystemTime};
+
+use crate::{request_parts, response_parts};
+
+fn policy_at(res: http::response::Builder, now: SystemTime) -> CachePolicy {
+    CachePolicy::with_config(
+        &request_parts(Request::builder()),
+        &response_parts(res),
+        now,
+        Default::default(),
+    )
+}
+
+#[test]
+fn stale_at_accounts_for_age() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let policy = policy_at(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=100")
+            .header(header::AGE, "30"),
+        now,
+    );
+    assert_eq!(policy.stale_at(), now + Duration::from_secs(70));
+    assert!(!policy.is_stale(policy.stale_at() - Duration::from_secs(1)));
+    assert!(policy.is_stale(policy.stale_at()));
+
+    let policy = policy_at(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=100")
+            .header(header::AGE, "300"),
+        now,
+    );
+    assert_eq!(policy.stale_at(), now);
+}
+
+#[test]
+fn expires_at_uses_the_server_clock() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let date = now - Duration::from_secs(3600);
+    let policy = policy_at(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=100")
+            .header(header::DATE, httpdate::fmt_http_date(date)),
+        now,
+    );
+    assert_eq!(policy.expires_at(), date + Duration::from_secs(100));
+    assert_eq!(policy.stale_at(), now + Duration::from_secs(100));
+
+    let policy = policy_at(
+        Response::builder().header(header::CACHE_CONTROL, "max-age=100"),
+        now,
+    );
+    assert_eq!(policy.expires_at(), now + Duration::from_secs(100));
+}
+
+#[test]
+fn unstorable_entries_are_immediately_stale() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let policy = policy_at(
+        Response::builder().header(header::CACHE_CONTROL, "no-store"),
+        now,
+    );
+    assert_eq!(policy.stale_at(), now);
+    assert_eq!(policy.expires_at(), now);
+}
+
+#[test]
+fn stale_window_deadlines() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let policy = policy_at(
+        Response::builder().header(
+            header::CACHE_CONTROL,
+            "max-age=100, stale-while-revalidate=30, stale-if-error=600",
+        ),
+        now,
+    );
+    let swr = now + Duration::from_secs(130);
+    let sie = now + Duration::from_secs(700);
+    assert_eq!(policy.stale_while_revalidate_until(now), Some(swr));
+    assert_eq!(policy.stale_if_error_until(now), Some(sie));
+    assert_eq!(policy.stale_while_revalidate_until(swr), None);
+    assert_eq!(policy.stale_if_error_until(swr), Some(sie));
+    assert_eq!(policy.stale_if_error_until(sie), None);
+}
+
+#[test]
+fn stale_windows_need_permission_to_serve_stale() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    for cc in [
+        "max-age=100",
+        "max-age=100, stale-while-revalidate",
+        "max-age=100, stale-while-revalidate=30, must-revalidate",
+        "max-age=100, stale-while-revalidate=30, proxy-revalidate",
+        "no-store, stale-while-revalidate=30",
+    ] {
+        let policy = policy_at(Response::builder().header(header::CACHE_CONTROL, cc), now);
+        assert_eq!(policy.stale_while_revalidate_until(now), None, "{cc}");
+    }
+}
+
+#[test]
+fn rfc_named_freshness_calculations() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let policy = policy_at(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=100")
+            .header(header::AGE, "30"),
+        now,
+    );
+    assert_eq!(policy.freshness_lifetime(), Duration::from_secs(100));
+    assert_eq!(policy.current_age(now), Duration::from_secs(30));
+    let later = now + Duration::from_secs(20);
+    assert_eq!(policy.current_age(later), Duration::from_secs(50));
+    assert_eq!(
+        policy.time_to_live(later),
+        policy.freshness_lifetime() - policy.current_age(later)
+    );
+
+    let policy = policy_at(
+        Response::builder().header(header::CACHE_CONTROL, "no-cache, max-age=100"),
+        now,
+    );
+    assert_eq!(policy.freshness_lifetime(), Duration::ZERO);
+}
+
+#[test]
+fn dates_are_parsed_consistently() {
+    let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let policy = policy_at(
+        Response::builder()
+            .header(header::DATE, format!(" {} ", httpdate::fmt_http_date(now)))
+            .header(
+                header::EXPIRES,
+                format!(
+                    "{} ",
+                    httpdate::fmt_http_date(now + Duration::from_secs(60))
+                ),
+            ),
+        now,
+    );
+    assert_eq!(policy.date(), now);
+    assert_eq!(policy.time_to_live(now), Duration::from_secs(60));
+
+    // An Expires that isn't text is as invalid as any other bad date
+    let policy = policy_at(
+        Response::builder()
+            .header(header::EXPIRES, &b"\xff"[..])
+            .header(
+                header::LAST_MODIFIED,
+                httpdate::fmt_http_date(now - Duration::from_secs(1000)),
+            ),
+        now,
+    );
+    assert!(policy.is_stale(now));
+}
+
+#[test]
+fn missing_dates_are_synthesized_from_the_response_time() {
+    let received = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let policy = policy_at(
+        Response::builder().header(
+            header::EXPIRES,
+            httpdate::fmt_http_date(received + Duration::from_secs(60)),
+        ),
+        received,
+    );
+    assert_eq!(
+        policy.response_headers()[header::DATE],
+        httpdate::fmt_http_date(received)
+    );
+    assert_eq!(policy.date(), received);
+    assert_eq!(policy.time_to_live(received), Duration::from_secs(60));
+
+    // A 304 without a Date refreshes it along with the response time
+    let revalidated = received + Duration::from_secs(120);
+    let not_modified = response_parts(Response::builder().status(304));
+    let policy = match policy.after_response(
+        &request_parts(Request::builder()),
+        &not_modified,
+        revalidated,
+    ) {
+        AfterResponse::NotModified(policy, _) => policy,
+        _ => panic!("should be not modified"),
+    };
+    assert_eq!(policy.date(), revalidated);
+
+    // An origin's own Date is kept even when the clocks disagree
+    let origin = received - Duration::from_secs(30);
+    let policy = policy_at(
+        Response::builder().header(header::DATE, httpdate::fmt_http_date(origin)),
+        received,
+    );
+    assert_eq!(policy.date(), origin);
+}
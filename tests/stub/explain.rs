@@ -0,0 +1,100 @@
+use http::{header, Request, Response};
+use http_cache_policy::{CachePolicy, FreshnessSource, StaleReason, Storability};
+use std::time::{Duration, SystemTime};
+
+use crate::{private_config, request_parts, response_parts};
+
+#[test]
+fn explains_a_fresh_entry() {
+    let now = SystemTime::now();
+    let req = request_parts(Request::builder().header(header::CACHE_CONTROL, "max-stale"));
+    let policy = CachePolicy::with_config(
+        &req,
+        &response_parts(Response::builder().header(header::CACHE_CONTROL, "public, max-age=100")),
+        now,
+        Default::default(),
+    );
+    let explanation = policy.explain(&req, now + Duration::from_secs(30));
+
+    assert_eq!(explanation.storability, Storability::Storable);
+    assert_eq!(
+        explanation.response_directives,
+        [
+            ("max-age".to_owned(), Some("100".to_owned())),
+            ("public".to_owned(), None),
+        ]
+    );
+    assert_eq!(
+        explanation.request_directives,
+        [("max-stale".to_owned(), None)]
+    );
+    assert_eq!(explanation.freshness_source, FreshnessSource::MaxAge);
+    assert_eq!(explanation.freshness_lifetime, Duration::from_secs(100));
+    assert_eq!(explanation.age, Duration::from_secs(30));
+    assert_eq!(explanation.time_to_live, Duration::from_secs(70));
+    assert_eq!(explanation.stale_reason, None);
+}
+
+#[test]
+fn explains_which_check_failed() {
+    let now = SystemTime::now();
+    let req = request_parts(Request::builder());
+    let policy = CachePolicy::new(
+        &req,
+        &response_parts(
+            Response::builder().header(header::CACHE_CONTROL, "max-age=100, s-maxage=10"),
+        ),
+    );
+    let explanation = policy.explain(&req, now + Duration::from_secs(30));
+
+    assert_eq!(explanation.freshness_source, FreshnessSource::SMaxAge);
+    assert_eq!(explanation.freshness_lifetime, Duration::from_secs(10));
+    assert_eq!(explanation.time_to_live, Duration::ZERO);
+    assert_eq!(explanation.stale_reason, Some(StaleReason::Expired));
+
+    let no_cache = request_parts(Request::builder().header(header::CACHE_CONTROL, "no-cache"));
+    let explanation = policy.explain(&no_cache, now);
+    assert_eq!(explanation.stale_reason, Some(StaleReason::RequestNoCache));
+}
+
+#[test]
+fn explains_freshness_sources() {
+    let req = request_parts(Request::builder());
+    let explain = |res: http::response::Builder| {
+        CachePolicy::with_config(
+            &req,
+            &response_parts(res),
+            SystemTime::now(),
+            private_config(),
+        )
+        .explain(&req, SystemTime::now())
+    };
+
+    let explanation = explain(Response::builder().header(header::CACHE_CONTROL, "no-store"));
+    assert_eq!(explanation.storability, Storability::ResponseNoStore);
+    assert_eq!(explanation.freshness_source, FreshnessSource::NotStorable);
+
+    let explanation = explain(Response::builder().header(header::CACHE_CONTROL, "no-cache"));
+    assert_eq!(explanation.freshness_source, FreshnessSource::NoCache);
+
+    let explanation = explain(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=100")
+            .header(header::VARY, "*"),
+    );
+    assert_eq!(explanation.freshness_source, FreshnessSource::VaryStar);
+
+    let explanation = explain(
+        Response::builder()
+            .header(header::DATE, "Mon, 07 Oct 2024 12:00:00 GMT")
+            .header(header::LAST_MODIFIED, "Sun, 06 Oct 2024 12:00:00 GMT"),
+    );
+    assert_eq!(
+        explanation.freshness_source,
+        FreshnessSource::LastModifiedHeuristic
+    );
+
+    let explanation = explain(Response::builder());
+    assert_eq!(explanation.freshness_source, FreshnessSource::Unknown);
+    assert_eq!(explanation.freshness_lifetime, Duration::ZERO);
+}
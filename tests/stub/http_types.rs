@@ -0,0 +1,47 @@
+use http::{header, Method as HttpMethod};
+use http_cache_policy::{http_types as convert, BeforeRequest, CachePolicy};
+use http_types::{Method, Request, Response, StatusCode};
+
+fn response() -> Response {
+    let mut res = Response::new(StatusCode::Ok);
+    res.insert_header("cache-control", "max-age=0");
+    res.insert_header("etag", "\"v1\"");
+    res.append_header("set-cookie", "a=1");
+    res.append_header("set-cookie", "b=2");
+    res
+}
+
+#[test]
+fn converts_requests_and_responses() {
+    let mut req = Request::new(Method::Get, "https://example.com/a");
+    req.insert_header("accept", "text/html");
+    let (uri, method, headers) = convert::request(&req).unwrap();
+    assert_eq!(uri, "https://example.com/a");
+    assert_eq!(method, HttpMethod::GET);
+    assert_eq!(headers[header::ACCEPT], "text/html");
+
+    let (status, headers) = convert::response(&response()).unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(headers[header::ETAG], "\"v1\"");
+    assert_eq!(headers.get_all(header::SET_COOKIE).iter().count(), 2);
+}
+
+#[test]
+fn applies_revalidation_headers() {
+    let mut req = Request::new(Method::Get, "https://example.com/a");
+    // Refers to the client's copy rather than the stored one, so it must not be sent
+    req.insert_header("if-range", "\"client\"");
+    let policy = CachePolicy::new(
+        &convert::request(&req).unwrap(),
+        &convert::response(&response()).unwrap(),
+    );
+
+    let now = http_cache_policy::now();
+    let revalidation = match policy.before_request(&convert::request(&req).unwrap(), now) {
+        BeforeRequest::Stale { request, .. } => request,
+        BeforeRequest::Fresh(_) => panic!("should be stale"),
+    };
+    convert::apply_revalidation_headers(&mut req, &revalidation.headers);
+    assert_eq!(req["if-none-match"], "\"v1\"");
+    assert!(req.header("if-range").is_none());
+}
@@ -0,0 +1,77 @@
+use std::{
+    convert::Infallible,
+    future::{ready, Future, Ready},
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+};
+
+use bytes::Bytes;
+use http::{header, Request, Response, StatusCode};
+use http_body_util::{Empty, Full};
+use http_cache_policy::{hyper::BufferBody, storage::MemoryCache, tower::CacheLayer};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Streams `hello` with `max-age=0` and an `ETag`, answering matching revalidations with `304`s
+#[derive(Clone, Default)]
+struct Origin {
+    requests: Arc<Mutex<usize>>,
+}
+
+impl Service<Request<Empty<Bytes>>> for Origin {
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Empty<Bytes>>) -> Self::Future {
+        *self.requests.lock().unwrap() += 1;
+        let res = Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=0")
+            .header(header::ETAG, "\"v1\"");
+        let res = if req.headers().contains_key(header::IF_NONE_MATCH) {
+            res.status(StatusCode::NOT_MODIFIED).body(Full::default())
+        } else {
+            res.body(Full::new(Bytes::from_static(b"hello")))
+        };
+        ready(Ok(res.unwrap()))
+    }
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    struct Noop;
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(Noop));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = Box::pin(fut);
+    loop {
+        if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+#[test]
+fn buffered_bodies_are_stored_and_substituted_for_304s() {
+    let origin = Origin::default();
+    let mut service =
+        CacheLayer::new(Arc::new(MemoryCache::default())).layer(BufferBody::new(origin.clone()));
+    let get = || {
+        Request::get("http://example.com/")
+            .body(Empty::new())
+            .unwrap()
+    };
+
+    for _ in 0..2 {
+        let res = block_on(service.call(get())).unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body(), "hello");
+    }
+    assert_eq!(*origin.requests.lock().unwrap(), 2);
+}
@@ -0,0 +1,97 @@
+use http::{header, Method, Request, Response, StatusCode, Uri};
+use http_cache_policy::CachePolicy;
+
+use crate::{request_parts, response_parts};
+
+fn targets(method: Method, uri: &str, response: http::response::Builder) -> Vec<Uri> {
+    CachePolicy::invalidation_targets(
+        &request_parts(Request::builder().method(method).uri(uri)),
+        &response_parts(response),
+    )
+}
+
+#[test]
+fn unsafe_methods_invalidate_the_target() {
+    for method in [Method::POST, Method::PUT, Method::DELETE, Method::PATCH] {
+        assert_eq!(
+            targets(method.clone(), "https://example.com/a", Response::builder()),
+            ["https://example.com/a"],
+            "{method}"
+        );
+    }
+}
+
+#[test]
+fn safe_methods_and_errors_invalidate_nothing() {
+    for method in [Method::GET, Method::HEAD, Method::OPTIONS, Method::TRACE] {
+        assert!(targets(method, "/a", Response::builder()).is_empty());
+    }
+    for status in [StatusCode::NOT_FOUND, StatusCode::INTERNAL_SERVER_ERROR] {
+        assert!(targets(Method::POST, "/a", Response::builder().status(status)).is_empty());
+    }
+}
+
+#[test]
+fn location_headers_are_resolved_and_invalidated() {
+    let response = Response::builder()
+        .status(StatusCode::CREATED)
+        .header(header::LOCATION, "items/42")
+        .header(header::CONTENT_LOCATION, "/items/42?full=1");
+    assert_eq!(
+        targets(Method::POST, "https://example.com/api/new", response),
+        [
+            "https://example.com/api/new",
+            "https://example.com/api/items/42",
+            "https://example.com/items/42?full=1",
+        ]
+    );
+
+    let response = Response::builder()
+        .status(StatusCode::SEE_OTHER)
+        .header(header::LOCATION, "//example.com/done");
+    assert_eq!(
+        targets(Method::PUT, "https://example.com/a", response),
+        ["https://example.com/a", "https://example.com/done"]
+    );
+}
+
+#[test]
+fn cross_origin_locations_are_ignored() {
+    let response = Response::builder()
+        .header(header::LOCATION, "https://evil.example/a")
+        .header(header::CONTENT_LOCATION, "http://example.com/a");
+    assert_eq!(
+        targets(Method::DELETE, "https://example.com/a", response),
+        ["https://example.com/a"]
+    );
+}
+
+#[test]
+fn origin_form_targets_use_the_host_header() {
+    let req = request_parts(
+        Request::builder()
+            .method(Method::POST)
+            .uri("/a")
+            .header(header::HOST, "example.com"),
+    );
+    let res = response_parts(
+        Response::builder()
+            .header(header::LOCATION, "https://example.com/b")
+            .header(header::CONTENT_LOCATION, "https://other.example/c"),
+    );
+    assert_eq!(
+        CachePolicy::invalidation_targets(&req, &res),
+        [
+            Uri::from_static("/a"),
+            Uri::from_static("https://example.com/b")
+        ]
+    );
+}
+
+#[test]
+fn duplicate_locations_are_only_listed_once() {
+    let response = Response::builder()
+        .header(header::LOCATION, "/a")
+        .header(header::CONTENT_LOCATION, "/a");
+    assert_eq!(targets(Method::POST, "/a", response), ["/a"]);
+}
@@ -0,0 +1,54 @@
+use http::{header, Method};
+use http_cache_policy::{isahc as convert, BeforeRequest, CachePolicy};
+
+fn request() -> isahc::Request<()> {
+    isahc::Request::get("https://example.com/a")
+        .header("Accept", "text/html")
+        .body(())
+        .unwrap()
+}
+
+fn response() -> isahc::Response<()> {
+    isahc::Response::builder()
+        .header("Cache-Control", "max-age=0")
+        .header("ETag", "\"v1\"")
+        .header("Set-Cookie", "a=1")
+        .header("Set-Cookie", "b=2")
+        .body(())
+        .unwrap()
+}
+
+#[test]
+fn converts_requests_and_responses() {
+    let (uri, method, headers) = convert::request(&request()).unwrap();
+    assert_eq!(uri, "https://example.com/a");
+    assert_eq!(method, Method::GET);
+    assert_eq!(headers[header::ACCEPT], "text/html");
+
+    let (status, headers) = convert::response(&response()).unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(headers[header::ETAG], "\"v1\"");
+    assert_eq!(headers.get_all(header::SET_COOKIE).iter().count(), 2);
+}
+
+#[test]
+fn applies_revalidation_headers() {
+    let policy = CachePolicy::new(
+        &convert::request(&request()).unwrap(),
+        &convert::response(&response()).unwrap(),
+    );
+
+    let mut req = request();
+    // Refers to the client's copy rather than the stored one, so it must not be sent
+    req.headers_mut()
+        .insert("if-range", "\"client\"".parse().unwrap());
+    let now = http_cache_policy::now();
+    let revalidation = match policy.before_request(&convert::request(&req).unwrap(), now) {
+        BeforeRequest::Stale { request, .. } => request,
+        BeforeRequest::Fresh(_) => panic!("should be stale"),
+    };
+    convert::apply_revalidation_headers(&mut req, &revalidation.headers);
+    assert_eq!(req.headers()["if-none-match"], "\"v1\"");
+    assert_eq!(req.headers()["accept"], "text/html");
+    assert!(!req.headers().contains_key("if-range"));
+}
@@ -0,0 +1,120 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use http::{header, Request, Response};
+use http_cache_policy::{js_json::JsJsonError, BeforeRequest, CachePolicy};
+use serde_json::json;
+
+use crate::{request_parts, response_parts};
+
+fn js_policy() -> serde_json::Value {
+    // As written by `http-cache-semantics`' `toObject()`
+    json!({
+        "v": 1,
+        "t": 1700000000000u64,
+        "sh": false,
+        "ch": 0.1,
+        "imm": 86400000,
+        "st": 200,
+        "resh": {
+            "cache-control": "max-age=300",
+            "etag": "\"a\"",
+            "vary": "accept",
+            "set-cookie": ["a=1", "b=2"]
+        },
+        "rescc": { "max-age": "300" },
+        "m": "GET",
+        "u": "/a?b=c",
+        "h": "example.com",
+        "a": true,
+        "reqh": { "host": "example.com", "accept": "text/html" },
+        "reqcc": {}
+    })
+}
+
+#[test]
+fn reads_js_policies() {
+    let policy = CachePolicy::from_js_json(&js_policy()).unwrap();
+    assert!(policy.is_storable());
+    assert_eq!(policy.status(), 200);
+    assert_eq!(policy.uri(), "/a?b=c");
+    assert_eq!(
+        policy.response_time(),
+        UNIX_EPOCH + Duration::from_millis(1700000000000)
+    );
+    assert!(!policy.options().mode.is_shared());
+    assert_eq!(
+        policy
+            .response_headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .count(),
+        2
+    );
+
+    let req = request_parts(
+        Request::builder()
+            .uri("/a?b=c")
+            .header(header::HOST, "example.com")
+            .header(header::ACCEPT, "text/html"),
+    );
+    assert!(policy.matches(&req));
+    let now = policy.response_time() + Duration::from_secs(10);
+    assert!(matches!(
+        policy.before_request(&req, now),
+        BeforeRequest::Fresh(_)
+    ));
+}
+
+#[test]
+fn round_trips_js_policies() {
+    let policy = CachePolicy::from_js_json(&js_policy()).unwrap();
+    assert_eq!(policy.to_js_json(), js_policy());
+}
+
+#[test]
+fn writes_js_policies() {
+    let policy = CachePolicy::new(
+        &request_parts(
+            Request::builder()
+                .uri("https://example.com/a")
+                .header(header::AUTHORIZATION, "secret"),
+        ),
+        &response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "public, max-age=60")
+                .header(header::CONTENT_LANGUAGE, "en")
+                .header(header::CONTENT_LANGUAGE, "fr"),
+        ),
+    );
+    let js = policy.to_js_json();
+    assert_eq!(js["v"], 1);
+    assert_eq!(js["sh"], true);
+    assert_eq!(js["u"], "https://example.com/a");
+    assert_eq!(js["h"], "example.com");
+    assert_eq!(js["a"], false);
+    assert_eq!(js["resh"]["content-language"], "en, fr");
+    assert_eq!(js["rescc"], json!({ "public": true, "max-age": "60" }));
+
+    let read = CachePolicy::from_js_json(&js).unwrap();
+    assert_eq!(
+        read.response_headers()[header::CACHE_CONTROL],
+        "public, max-age=60"
+    );
+    assert!(read.is_storable());
+}
+
+#[test]
+fn rejects_unknown_versions() {
+    let mut js = js_policy();
+    js["v"] = json!(2);
+    let err = CachePolicy::from_js_json(&js).unwrap_err();
+    assert!(matches!(err, JsJsonError::UnsupportedVersion(Some(2))));
+    assert_eq!(err.to_string(), "unsupported format version 2");
+
+    let mut js = js_policy();
+    js.as_object_mut().unwrap().remove("st");
+    assert_eq!(
+        CachePolicy::from_js_json(&js).unwrap_err().to_string(),
+        "missing field `st`"
+    );
+}
@@ -0,0 +1,178 @@
+use http::{header, Method, Request, Response};
+use http_cache_policy::{CachePolicy, Config, WithBodyDigest};
+use std::{
+    collections::HashSet,
+    time::{Duration, SystemTime},
+};
+
+use crate::{request_parts, response_parts};
+
+fn policy(req: &http::request::Parts, res: http::response::Builder) -> CachePolicy {
+    CachePolicy::new(req, &response_parts(res))
+}
+
+#[test]
+fn primary_key_normalizes_the_uri() {
+    for (uri, expected) in [
+        ("https://Example.COM/a?b=c", "GET https://example.com/a?b=c"),
+        ("HTTPS://example.com:443/a", "GET https://example.com/a"),
+        ("http://example.com:80", "GET http://example.com/"),
+        ("http://example.com:8080/a", "GET http://example.com:8080/a"),
+    ] {
+        let req = request_parts(Request::builder().uri(uri));
+        assert_eq!(CachePolicy::primary_key(&req), expected, "{uri}");
+        assert_eq!(
+            policy(&req, Response::builder()).cache_key().primary(),
+            expected
+        );
+    }
+}
+
+#[test]
+fn primary_key_falls_back_to_the_host_header() {
+    let req = request_parts(
+        Request::builder()
+            .method(Method::HEAD)
+            .uri("/a")
+            .header(header::HOST, "Example.com"),
+    );
+    assert_eq!(CachePolicy::primary_key(&req), "HEAD example.com/a");
+}
+
+#[test]
+fn primary_key_includes_the_body_digest() {
+    let req = request_parts(
+        Request::builder()
+            .method(Method::from_bytes(b"QUERY").unwrap())
+            .uri("https://example.com/search"),
+    );
+    let req = WithBodyDigest::new(&req, &[0x0f, 0xa0]);
+    assert_eq!(
+        CachePolicy::primary_key(&req),
+        "QUERY https://example.com/search 0fa0"
+    );
+}
+
+#[test]
+fn secondary_key_follows_vary() {
+    let req = request_parts(
+        Request::builder()
+            .header(header::ACCEPT_LANGUAGE, "en")
+            .header(header::ACCEPT_ENCODING, "gzip"),
+    );
+    let policy = policy(
+        &req,
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=100")
+            .header(header::VARY, "Accept-Language, accept-encoding")
+            .header(header::VARY, "dnt, accept-language"),
+    );
+    let key = policy.cache_key();
+    assert_eq!(
+        key.secondary(),
+        "accept-encoding=gzip\naccept-language=en\ndnt"
+    );
+    assert_eq!(policy.secondary_key(&req), key.secondary());
+
+    let other = request_parts(Request::builder().header(header::ACCEPT_LANGUAGE, "de"));
+    assert_eq!(
+        policy.secondary_key(&other),
+        "accept-encoding\naccept-language=de\ndnt"
+    );
+}
+
+#[test]
+fn secondary_key_without_vary() {
+    let req = request_parts(Request::builder().header(header::ACCEPT_LANGUAGE, "en"));
+    let key = policy(&req, Response::builder()).cache_key();
+    assert_eq!(key.secondary(), "");
+
+    let key = policy(&req, Response::builder().header(header::VARY, "*")).cache_key();
+    assert_eq!(key.secondary(), "*");
+}
+
+#[test]
+fn identity_ignores_ordering_and_time() {
+    let req = request_parts(Request::builder().uri("https://example.com/a"));
+    let identity = |res: http::response::Builder, secs| {
+        CachePolicy::with_config(
+            &req,
+            &response_parts(res),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+            Default::default(),
+        )
+        .identity()
+    };
+
+    let a = identity(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "public, max-age=60")
+            .header(header::ETAG, "\"v1\"")
+            .header(header::SERVER, "a"),
+        1,
+    );
+    let b = identity(
+        Response::builder()
+            .header(header::SERVER, "b")
+            .header(header::ETAG, "\"v1\"")
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::CACHE_CONTROL, "public"),
+        2,
+    );
+    assert_eq!(a, b);
+    assert_eq!(HashSet::from([a.clone(), b]).len(), 1);
+
+    let c = identity(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "public, max-age=60")
+            .header(header::ETAG, "\"v2\""),
+        1,
+    );
+    assert_ne!(a, c);
+    let d = identity(
+        Response::builder()
+            .status(404)
+            .header(header::CACHE_CONTROL, "public, max-age=60")
+            .header(header::ETAG, "\"v1\""),
+        1,
+    );
+    assert_ne!(a, d);
+}
+
+#[test]
+fn ignored_query_params_are_left_out() {
+    let config = Config::default().ignored_query_params(["utm_*", "fbclid"]);
+    let get = |uri: &str| request_parts(Request::get(uri));
+    let policy = CachePolicy::with_config(
+        &get("https://example.com/a?utm_source=x&page=2&fbclid=y"),
+        &response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=60")),
+        SystemTime::now(),
+        config.clone(),
+    );
+
+    assert_eq!(policy.uri(), "https://example.com/a?page=2");
+    assert_eq!(
+        policy.cache_key().primary(),
+        "GET https://example.com/a?page=2"
+    );
+    assert_eq!(
+        CachePolicy::primary_key_with_config(
+            &get("https://example.com/a?page=2&utm_medium=z"),
+            &config
+        ),
+        policy.cache_key().primary(),
+    );
+    assert!(policy.matches(&get("https://example.com/a?page=2")));
+    assert!(policy.matches(&get("https://example.com/a?page=2&utm_campaign=q&fbclid=1")));
+    assert!(!policy.matches(&get("https://example.com/a?page=3")));
+    assert!(!policy.matches(&get("https://example.com/a?gclid=1&page=2")));
+
+    let policy = CachePolicy::with_config(
+        &get("https://example.com/a?utm_source=x"),
+        &response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=60")),
+        SystemTime::now(),
+        config,
+    );
+    assert_eq!(policy.uri(), "https://example.com/a");
+    assert!(policy.matches(&get("https://example.com/a")));
+}
@@ -1,9 +1,55 @@
+mod accessors;
+#[cfg(feature = "actix-web")]
+mod actix_web;
+mod borrowed;
+mod builder;
+mod cache_control;
+mod compact;
+mod conditional;
+#[cfg(feature = "curl")]
+mod curl;
+mod directives;
+mod expiry;
+mod explain;
+#[cfg(feature = "http-types")]
+mod http_types;
+#[cfg(feature = "hyper")]
+mod hyper;
+mod invalidation;
+#[cfg(feature = "isahc")]
+mod isahc;
+#[cfg(feature = "js-json")]
+mod js_json;
+mod key;
+mod observer;
 mod okhttp;
+mod overrides;
+mod query;
+mod range;
 mod request;
+#[cfg(feature = "reqwest-blocking")]
+mod reqwest_blocking;
+#[cfg(feature = "reqwest-middleware")]
+mod reqwest_middleware;
 mod response;
 mod responsetest;
 mod revalidate;
 mod satisfy;
+#[cfg(feature = "serde")]
+mod serde;
+#[cfg(feature = "server")]
+mod server;
+mod stale;
+mod storage;
+mod strict;
+mod tags;
 mod tests;
+#[cfg(feature = "tower")]
+mod tower;
+mod ttl;
 mod update;
+#[cfg(feature = "ureq")]
+mod ureq;
+#[cfg(feature = "url")]
+mod url;
 mod vary;
@@ -0,0 +1,85 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use http::{header, Request, Response, StatusCode};
+use http_cache_policy::{
+    observer::Decision, CachePolicy, CachePolicyBuilder, Config, StaleReason, Storability,
+};
+
+use crate::{request_parts, response_parts};
+
+fn recording() -> (Config, Arc<Mutex<Vec<Decision>>>) {
+    let decisions = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&decisions);
+    let config = Config::default().observer(move |_: &CachePolicy, decision| {
+        recorded.lock().unwrap().push(decision);
+    });
+    (config, decisions)
+}
+
+#[test]
+fn observes_every_decision() {
+    let (config, decisions) = recording();
+    let now = http_cache_policy::now();
+    let req = request_parts(Request::builder().uri("https://example.com/a"));
+    let res = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::ETAG, "\"v1\""),
+    );
+
+    let policy = CachePolicy::with_config(&req, &res, now, config);
+    policy.before_request(&req, now);
+    let later = now + Duration::from_secs(120);
+    policy.before_request(&req, later);
+    let not_modified = response_parts(
+        Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, "\"v1\""),
+    );
+    policy.after_response(&req, &not_modified, later);
+    let error = response_parts(Response::builder().status(StatusCode::BAD_GATEWAY));
+    policy.after_response(&req, &error, later);
+
+    assert_eq!(
+        *decisions.lock().unwrap(),
+        [
+            Decision::Evaluated(Storability::Storable),
+            Decision::Fresh,
+            Decision::Stale(StaleReason::Expired),
+            Decision::NotModified,
+            Decision::FailedRevalidation,
+        ]
+    );
+}
+
+#[test]
+fn observes_built_policies() {
+    let (config, decisions) = recording();
+    CachePolicyBuilder::new()
+        .uri("https://example.com/a")
+        .response_header("cache-control", "no-store")
+        .config(config)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        *decisions.lock().unwrap(),
+        [Decision::Evaluated(Storability::ResponseNoStore)]
+    );
+}
+
+#[test]
+fn observers_carry_over_to_updated_policies() {
+    let (config, decisions) = recording();
+    let now = http_cache_policy::now();
+    let req = request_parts(Request::builder().uri("https://example.com/a"));
+    let res = response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=60"));
+    let policy = CachePolicy::with_config(&req, &res, now, config);
+
+    let updated = policy.with_options(policy.options().clone());
+    updated.before_request(&req, now);
+    assert_eq!(decisions.lock().unwrap().last(), Some(&Decision::Fresh));
+}
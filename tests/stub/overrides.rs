@@ -0,0 +1,53 @@
+use std::time::{Duration, SystemTime};
+
+use http::{header, Request, Response, Uri};
+use http_cache_policy::{
+    borrowed::CachePolicyRef, config::Mode, overrides::ConfigOverrides, CachePolicy, Config,
+};
+
+use crate::{request_parts, response_parts};
+
+fn per_host() -> Config {
+    Config::default()
+        .max_ttl(Duration::from_secs(60))
+        .overrides(|uri: &Uri| match uri.host() {
+            Some("trusted.example.com") => {
+                ConfigOverrides::default().max_ttl(Duration::from_secs(3600))
+            }
+            Some("private.example.com") => ConfigOverrides::default().mode(Mode::Private),
+            _ => ConfigOverrides::default(),
+        })
+}
+
+#[test]
+fn overrides_apply_per_uri() {
+    let now = SystemTime::now();
+    let res =
+        response_parts(Response::builder().header(header::CACHE_CONTROL, "private, max-age=86400"));
+    let policy = |uri: &str| {
+        let req = request_parts(Request::builder().uri(uri));
+        CachePolicy::with_config(&req, &res, now, per_host())
+    };
+
+    let shared = policy("https://example.com/a");
+    assert!(!shared.is_storable());
+
+    let private = policy("https://private.example.com/a");
+    assert!(private.is_storable());
+    assert_eq!(private.options().mode, Mode::Private);
+    assert_eq!(private.time_to_live(now), Duration::from_secs(60));
+
+    let res = response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=86400"));
+    let req = request_parts(Request::builder().uri("https://trusted.example.com/a"));
+    let trusted = CachePolicy::with_config(&req, &res, now, per_host());
+    assert_eq!(trusted.time_to_live(now), Duration::from_secs(3600));
+    assert_eq!(trusted.options().max_ttl, Some(Duration::from_secs(3600)));
+
+    let config = per_host();
+    let borrowed = CachePolicyRef::with_config(&req, &res, now, &config);
+    assert_eq!(borrowed.time_to_live(now), Duration::from_secs(3600));
+    assert_eq!(
+        borrowed.into_policy().time_to_live(now),
+        Duration::from_secs(3600)
+    );
+}
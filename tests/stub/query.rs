@@ -0,0 +1,100 @@
+use http::{header, Method, Request, Response};
+use http_cache_policy::{config::Methods, CachePolicy, Config, Storability, WithBodyDigest};
+use std::time::{Duration, SystemTime};
+
+use crate::{request_parts, response_parts};
+
+fn query_request() -> http::request::Parts {
+    request_parts(
+        Request::builder()
+            .method(Method::from_bytes(b"QUERY").unwrap())
+            .uri("https://example.com/search")
+            .header(header::CONTENT_TYPE, "application/sql"),
+    )
+}
+
+fn cacheable_response() -> http::response::Parts {
+    response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=60"))
+}
+
+fn query_config() -> Config {
+    Config::default().cache_query(true)
+}
+
+#[test]
+fn query_is_not_cached_by_default() {
+    let req = query_request();
+    let req = WithBodyDigest::new(&req, b"digest");
+    let policy = CachePolicy::with_config(
+        &req,
+        &cacheable_response(),
+        SystemTime::now(),
+        Config::default(),
+    );
+    assert!(!policy.is_storable());
+}
+
+#[test]
+fn query_requires_a_body_digest() {
+    let policy = CachePolicy::with_config(
+        &query_request(),
+        &cacheable_response(),
+        SystemTime::now(),
+        query_config(),
+    );
+    assert!(!policy.is_storable());
+}
+
+#[test]
+fn query_matches_on_body_digest() {
+    let now = SystemTime::now();
+    let req = query_request();
+    let policy = CachePolicy::with_config(
+        &WithBodyDigest::new(&req, b"select 1"),
+        &cacheable_response(),
+        now,
+        query_config(),
+    );
+    assert!(policy.is_storable());
+    assert_eq!(policy.time_to_live(now), Duration::from_secs(60));
+
+    let later = now + Duration::from_secs(30);
+    assert!(policy
+        .before_request(&WithBodyDigest::new(&req, b"select 1"), later)
+        .is_fresh());
+    assert!(!policy
+        .before_request(&WithBodyDigest::new(&req, b"select 2"), later)
+        .is_fresh());
+    assert!(!policy.before_request(&req, later).is_fresh());
+    assert!(!policy
+        .before_request(
+            &WithBodyDigest::new(&req, b"select 1"),
+            now + Duration::from_secs(61)
+        )
+        .is_fresh());
+}
+
+#[test]
+fn post_can_require_a_body_digest() {
+    let now = SystemTime::now();
+    let req = request_parts(
+        Request::builder()
+            .method(Method::POST)
+            .uri("https://example.com/graphql"),
+    );
+    let config = Config::default().body_digest_methods(Methods::from_static(&[Method::POST]));
+
+    let policy = CachePolicy::with_config(&req, &cacheable_response(), now, config.clone());
+    assert_eq!(policy.storability(), Storability::MissingBodyDigest);
+
+    let first = WithBodyDigest::new(&req, b"{ a }");
+    let second = WithBodyDigest::new(&req, b"{ b }");
+    let policy = CachePolicy::with_config(&first, &cacheable_response(), now, config);
+    assert!(policy.is_storable());
+    assert!(policy.before_request(&first, now).is_fresh());
+    assert!(!policy.before_request(&second, now).is_fresh());
+    assert_ne!(
+        CachePolicy::primary_key(&first),
+        CachePolicy::primary_key(&second)
+    );
+}
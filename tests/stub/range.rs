@@ -0,0 +1,218 @@
+use http::{header, HeaderValue, Request, Response, StatusCode};
+use http_cache_policy::{
+    range::{AcceptRanges, ByteRangeSpec, ContentRange, Range},
+    CachePolicy,
+};
+
+use crate::{request_parts, response_parts};
+
+#[test]
+fn parses_single_range() {
+    let range = Range::parse("bytes=0-99").unwrap();
+    assert_eq!(range.ranges(), &[ByteRangeSpec::FromTo(0, 99)]);
+    assert_eq!(range.to_string(), "bytes=0-99");
+}
+
+#[test]
+fn parses_multiple_and_suffix_ranges() {
+    let range = Range::parse("Bytes= 0-0 , 100-,-500").unwrap();
+    assert_eq!(
+        range.ranges(),
+        &[
+            ByteRangeSpec::FromTo(0, 0),
+            ByteRangeSpec::AllFrom(100),
+            ByteRangeSpec::Last(500),
+        ]
+    );
+    assert_eq!(range.to_header_value(), "bytes=0-0, 100-, -500");
+}
+
+#[test]
+fn rejects_invalid_ranges() {
+    for invalid in [
+        "",
+        "bytes=",
+        "bytes=-",
+        "bytes=10-5",
+        "bytes=a-b",
+        "bytes=+1-2",
+        "items=0-5",
+        "bytes 0-5",
+    ] {
+        assert_eq!(Range::parse(invalid), None, "{invalid:?}");
+    }
+    assert_eq!(Range::new(Vec::new()), None);
+}
+
+#[test]
+fn resolves_satisfiable_ranges() {
+    let range = Range::parse("bytes=0-99, 150-, -20, 500-600").unwrap();
+    assert_eq!(
+        range.satisfiable_ranges(200),
+        vec![(0, 99), (150, 199), (180, 199)]
+    );
+    assert_eq!(
+        ByteRangeSpec::FromTo(100, 500).to_satisfiable_range(200),
+        Some((100, 199))
+    );
+    assert_eq!(
+        ByteRangeSpec::Last(500).to_satisfiable_range(200),
+        Some((0, 199))
+    );
+    assert_eq!(ByteRangeSpec::Last(0).to_satisfiable_range(200), None);
+    assert_eq!(ByteRangeSpec::AllFrom(0).to_satisfiable_range(0), None);
+}
+
+#[test]
+fn content_range_round_trips() {
+    for (value, expected) in [
+        (
+            "bytes 0-99/200",
+            ContentRange::Bytes {
+                first: 0,
+                last: 99,
+                complete_length: Some(200),
+            },
+        ),
+        (
+            "bytes 100-199/*",
+            ContentRange::Bytes {
+                first: 100,
+                last: 199,
+                complete_length: None,
+            },
+        ),
+        (
+            "bytes */1000",
+            ContentRange::Unsatisfied {
+                complete_length: 1000,
+            },
+        ),
+    ] {
+        let parsed = ContentRange::from_header(&HeaderValue::from_static(value)).unwrap();
+        assert_eq!(parsed, expected);
+        assert_eq!(parsed.to_header_value(), value);
+    }
+}
+
+#[test]
+fn rejects_invalid_content_ranges() {
+    for invalid in [
+        "bytes 0-99",
+        "bytes 99-0/200",
+        "bytes 0-200/200",
+        "bytes */*",
+        "items 0-5/10",
+        "bytes=0-5/10",
+    ] {
+        assert_eq!(ContentRange::parse(invalid), None, "{invalid:?}");
+    }
+}
+
+#[test]
+fn accept_ranges() {
+    let bytes = AcceptRanges::parse("Bytes").unwrap();
+    assert!(bytes.accepts_bytes());
+    assert_eq!(bytes, AcceptRanges::bytes());
+    assert_eq!(bytes.to_header_value(), "bytes");
+
+    let none = AcceptRanges::parse("none").unwrap();
+    assert!(!none.accepts_bytes());
+    assert_eq!(none, AcceptRanges::none());
+
+    let values = [
+        HeaderValue::from_static("none, items"),
+        HeaderValue::from_static("bytes"),
+    ];
+    let combined = AcceptRanges::from_headers(&values).unwrap();
+    assert!(combined.accepts_bytes());
+    assert_eq!(
+        combined.units().collect::<Vec<_>>(),
+        ["none", "items", "bytes"]
+    );
+
+    assert_eq!(AcceptRanges::parse(" , "), None);
+}
+
+fn ranged_policy(builder: http::response::Builder) -> CachePolicy {
+    CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(
+            builder
+                .header(header::CACHE_CONTROL, "max-age=3600")
+                .header(header::CONTENT_LENGTH, "1000")
+                .header(header::DATE, "Tue, 15 Nov 1994 12:45:27 GMT")
+                .header(header::LAST_MODIFIED, "Tue, 15 Nov 1994 12:45:26 GMT")
+                .header(header::ETAG, "\"abc\""),
+        ),
+    )
+}
+
+fn range_request(range: &str) -> http::request::Builder {
+    Request::builder().header(header::RANGE, range)
+}
+
+#[test]
+fn satisfies_range_from_stored_full_response() {
+    let policy = ranged_policy(Response::builder().header(header::ACCEPT_RANGES, "bytes"));
+
+    let req = request_parts(range_request("bytes=0-99, -100, 2000-"));
+    assert_eq!(
+        policy.satisfies_range(&req),
+        Some(vec![(0, 99), (900, 999)])
+    );
+
+    let req = request_parts(range_request("bytes=1000-"));
+    assert_eq!(policy.satisfies_range(&req), None, "unsatisfiable range");
+
+    let req = request_parts(Request::builder());
+    assert_eq!(policy.satisfies_range(&req), None, "not a range request");
+}
+
+#[test]
+fn satisfies_range_requires_byte_ranges_and_length() {
+    let req = request_parts(range_request("bytes=0-99"));
+
+    let policy = ranged_policy(Response::builder());
+    assert_eq!(policy.satisfies_range(&req), None, "no Accept-Ranges");
+
+    let policy = ranged_policy(Response::builder().header(header::ACCEPT_RANGES, "none"));
+    assert_eq!(policy.satisfies_range(&req), None, "Accept-Ranges: none");
+
+    let policy = CachePolicy::new(
+        &req,
+        &response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=3600")
+                .header(header::ACCEPT_RANGES, "bytes"),
+        ),
+    );
+    assert_eq!(policy.satisfies_range(&req), None, "no Content-Length");
+
+    let policy = ranged_policy(
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(header::ACCEPT_RANGES, "bytes"),
+    );
+    assert_eq!(policy.satisfies_range(&req), None, "not a 200");
+}
+
+#[test]
+fn satisfies_range_checks_if_range() {
+    let policy = ranged_policy(Response::builder().header(header::ACCEPT_RANGES, "bytes"));
+
+    for (if_range, satisfied) in [
+        ("\"abc\"", true),
+        ("\"other\"", false),
+        ("W/\"abc\"", false),
+        ("Tue, 15 Nov 1994 12:45:26 GMT", true),
+        ("Tue, 15 Nov 1994 12:45:25 GMT", false),
+    ] {
+        let req = request_parts(range_request("bytes=0-9").header(header::IF_RANGE, if_range));
+        assert_eq!(
+            policy.satisfies_range(&req).is_some(),
+            satisfied,
+            "{if_range:?}"
+        );
+    }
+}
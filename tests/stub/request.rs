@@ -1,6 +1,7 @@
 use std::time::SystemTime;
 
 use http::{header, Method, Request, Response};
+use http_cache_policy::{config::Methods, CachePolicy, Config, Storability};
 
 use crate::{
     harness, private_config, req_cache_control, request_parts, resp_cache_control, response_parts,
@@ -37,6 +38,43 @@ fn post_cacheable_explicitly() {
         .test_with_response(public_cacheable_response());
 }
 
+#[test]
+fn post_not_cacheable_when_not_configured() {
+    harness()
+        .no_store()
+        .config(Config::default().cacheable_methods(Methods::CACHEABLE.without(&Method::POST)))
+        .request(request_parts(Request::builder().method(Method::POST)))
+        .test_with_response(public_cacheable_response());
+}
+
+#[test]
+fn custom_method_cacheable_explicitly_when_configured() {
+    let purge = Method::from_bytes(b"PURGE").unwrap();
+    harness()
+        .config(Config::default().cacheable_methods(Methods::CACHEABLE.with(purge.clone())))
+        .request(request_parts(Request::builder().method(purge)))
+        .test_with_response(public_cacheable_response());
+}
+
+#[test]
+fn custom_method_needs_explicit_expiration() {
+    let purge = Method::from_bytes(b"PURGE").unwrap();
+    harness()
+        .no_store()
+        .config(Config::default().cacheable_methods([purge.clone()].into_iter().collect()))
+        .request(request_parts(Request::builder().method(purge)))
+        .test_with_cache_control("public");
+}
+
+#[test]
+fn custom_method_not_cacheable_by_default() {
+    let purge = Method::from_bytes(b"PURGE").unwrap();
+    harness()
+        .no_store()
+        .request(request_parts(Request::builder().method(purge)))
+        .test_with_response(public_cacheable_response());
+}
+
 #[test]
 fn public_cacheable_auth_is_ok() {
     harness()
@@ -75,6 +113,99 @@ fn auth_prevents_caching_by_default() {
         .test_with_response(cacheable_response());
 }
 
+#[test]
+fn never_cache_authorized_overrides_public() {
+    harness()
+        .no_store()
+        .config(Config::default().never_cache_authorized(true))
+        .request(request_parts(
+            Request::builder().header(header::AUTHORIZATION, "test"),
+        ))
+        .test_with_response(public_cacheable_response());
+}
+
+#[test]
+fn never_cache_authorized_overrides_must_revalidate_and_s_maxage() {
+    for cache_control in ["max-age=80, must-revalidate", "s-maxage=80"] {
+        harness()
+            .no_store()
+            .config(Config::default().never_cache_authorized(true))
+            .request(request_parts(
+                Request::builder().header(header::AUTHORIZATION, "test"),
+            ))
+            .test_with_cache_control(cache_control);
+    }
+}
+
+#[test]
+fn never_cache_authorized_only_applies_to_shared_caches() {
+    harness()
+        .config(private_config().never_cache_authorized(true))
+        .request(request_parts(
+            Request::builder().header(header::AUTHORIZATION, "test"),
+        ))
+        .test_with_response(cacheable_response());
+}
+
+#[test]
+fn auth_is_not_shared_between_credentials() {
+    let now = SystemTime::now();
+    let with_auth = |credentials: &str| {
+        request_parts(Request::builder().header(header::AUTHORIZATION, credentials))
+    };
+    let policy = CachePolicy::with_config(
+        &with_auth("alice"),
+        &resp_cache_control("max-age=80, must-revalidate"),
+        now,
+        Config::default(),
+    );
+    assert!(policy.is_storable());
+
+    assert!(policy.before_request(&with_auth("alice"), now).is_fresh());
+    for req in [with_auth("bob"), request_parts(Request::builder())] {
+        match policy.before_request(&req, now) {
+            http_cache_policy::BeforeRequest::Stale {
+                matches, reason, ..
+            } => {
+                assert!(!matches);
+                assert_eq!(
+                    reason,
+                    http_cache_policy::StaleReason::AuthorizationMismatch
+                );
+            }
+            http_cache_policy::BeforeRequest::Fresh(_) => panic!("served to other credentials"),
+        }
+    }
+}
+
+#[test]
+fn public_auth_is_shared_between_credentials() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::with_config(
+        &request_parts(Request::builder().header(header::AUTHORIZATION, "alice")),
+        &public_cacheable_response(),
+        now,
+        Config::default(),
+    );
+
+    let req = request_parts(Request::builder().header(header::AUTHORIZATION, "bob"));
+    assert!(policy.before_request(&req, now).is_fresh());
+}
+
+#[test]
+fn private_cache_ignores_credential_changes() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::with_config(
+        &request_parts(Request::builder().header(header::AUTHORIZATION, "alice")),
+        &cacheable_response(),
+        now,
+        private_config(),
+    );
+
+    let req = request_parts(Request::builder().header(header::AUTHORIZATION, "bob"));
+    assert!(policy.before_request(&req, now).is_fresh());
+}
+
 #[test]
 fn no_cache_bypasses_cache() {
     let now = SystemTime::now();
@@ -99,3 +230,127 @@ fn no_cache_bypasses_cache() {
         .before_request(&req_cache_control("no-cache"), now)
         .is_fresh());
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn cacheable_methods_survive_serde() {
+    let purge = Method::from_bytes(b"PURGE").unwrap();
+    let config = Config::default().cacheable_methods(Methods::CACHEABLE.with(purge.clone()));
+    let json = serde_json::to_string(&config).unwrap();
+    let config: Config = serde_json::from_str(&json).unwrap();
+    assert!(config.cacheable_methods.contains(&purge));
+    assert!(config.cacheable_methods.contains(&Method::POST));
+}
+
+#[test]
+fn methods_mix_standard_and_extension_methods() {
+    let purge = Method::from_bytes(b"PURGE").unwrap();
+    let methods = Methods::CACHEABLE
+        .with(Method::PUT)
+        .with(purge.clone())
+        .without(&Method::POST);
+    assert!(methods.contains(&purge));
+    assert!(methods.contains(&Method::PUT));
+    assert!(!methods.contains(&Method::POST));
+    assert_eq!(
+        methods,
+        [Method::GET, Method::HEAD, Method::PUT, purge]
+            .into_iter()
+            .collect()
+    );
+    assert_eq!(
+        Methods::from_static(&[Method::GET, Method::HEAD, Method::POST]),
+        Methods::CACHEABLE
+    );
+}
+
+#[test]
+fn storability_explains_refusals() {
+    let policy = |req: http::request::Parts, res: http::response::Parts| {
+        CachePolicy::with_config(&req, &res, SystemTime::now(), Config::default())
+    };
+    let get = || request_parts(Request::builder());
+
+    assert_eq!(
+        policy(get(), cacheable_response()).storability(),
+        Storability::Storable
+    );
+    assert_eq!(
+        policy(req_cache_control("no-store"), cacheable_response()).storability(),
+        Storability::RequestNoStore
+    );
+    assert_eq!(
+        policy(
+            request_parts(Request::builder().method(Method::PUT)),
+            cacheable_response()
+        )
+        .storability(),
+        Storability::UncacheableMethod
+    );
+    assert_eq!(
+        policy(
+            request_parts(Request::builder().method(Method::POST)),
+            resp_cache_control("public")
+        )
+        .storability(),
+        Storability::NoExplicitExpiration
+    );
+    assert_eq!(
+        policy(
+            get(),
+            response_parts(
+                Response::builder()
+                    .status(http::StatusCode::PARTIAL_CONTENT)
+                    .header(header::CACHE_CONTROL, "max-age=1")
+            )
+        )
+        .storability(),
+        Storability::StatusNotUnderstood
+    );
+    assert_eq!(
+        policy(get(), resp_cache_control("max-age=1, no-store")).storability(),
+        Storability::ResponseNoStore
+    );
+    assert_eq!(
+        policy(get(), resp_cache_control("max-age=1, private")).storability(),
+        Storability::Private
+    );
+    assert_eq!(
+        policy(
+            request_parts(Request::builder().header(header::AUTHORIZATION, "test")),
+            cacheable_response()
+        )
+        .storability(),
+        Storability::Authorization
+    );
+    assert_eq!(
+        policy(
+            get(),
+            response_parts(Response::builder().status(http::StatusCode::FOUND))
+        )
+        .storability(),
+        Storability::NoExplicitExpiration
+    );
+}
+
+#[test]
+fn method_first_tuples_are_requests() {
+    let uri: http::Uri = "https://example.com/a".parse().unwrap();
+    let mut headers = http::HeaderMap::new();
+    headers.insert(header::ACCEPT, "text/html".parse().unwrap());
+    let res = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::VARY, "accept"),
+    );
+
+    let policy = CachePolicy::new(&(Method::GET, uri.clone(), &headers), &res);
+    assert!(policy.is_storable());
+    assert!(policy.matches(&(&Method::GET, &uri, &headers)));
+    assert!(!policy.matches(&(&Method::GET, &uri, &http::HeaderMap::new())));
+    assert!(policy.matches(&request_parts(
+        Request::builder()
+            .uri("https://example.com/a")
+            .header(header::ACCEPT, "text/html")
+    )));
+}
@@ -0,0 +1,34 @@
+use http::{header, Request, Response};
+use http_cache_policy::{BeforeRequest, CachePolicy};
+use reqwest::blocking;
+
+fn request() -> blocking::Request {
+    let req = Request::get("https://example.com/a")
+        .header(header::ACCEPT, "text/html")
+        .body(Vec::new())
+        .unwrap();
+    blocking::Request::try_from(req).unwrap()
+}
+
+fn response(cache_control: &str) -> blocking::Response {
+    Response::builder()
+        .header(header::CACHE_CONTROL, cache_control)
+        .header(header::VARY, "accept")
+        .body("hello")
+        .unwrap()
+        .into()
+}
+
+#[test]
+fn blocking_requests_and_responses_build_policies() {
+    let policy = CachePolicy::new(&request(), &response("max-age=60"));
+    assert!(policy.is_storable());
+    assert_eq!(policy.uri(), "https://example.com/a");
+    assert!(matches!(
+        policy.before_request(&request(), http_cache_policy::now()),
+        BeforeRequest::Fresh(_)
+    ));
+
+    let policy = CachePolicy::new(&request(), &response("no-store"));
+    assert!(!policy.is_storable());
+}
@@ -0,0 +1,102 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+};
+
+use bytes::Bytes;
+use http::{header, Extensions, StatusCode};
+use http_cache_policy::{reqwest_middleware::CacheMiddleware, storage::MemoryCache};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
+
+/// Answers every request itself with an `ETag` and `max-age=60` for `/fresh` (`0` otherwise)
+#[derive(Clone, Default)]
+struct Origin {
+    requests: Arc<Mutex<Vec<http::HeaderMap>>>,
+}
+
+#[async_trait::async_trait]
+impl Middleware for Origin {
+    async fn handle(
+        &self,
+        req: reqwest::Request,
+        _: &mut Extensions,
+        _: Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        let not_modified = req
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .map_or(false, |etag| etag == "\"v1\"");
+        let max_age = if req.url().path() == "/fresh" { 60 } else { 0 };
+        self.requests.lock().unwrap().push(req.headers().clone());
+
+        let res = http::Response::builder()
+            .header(header::CACHE_CONTROL, format!("max-age={max_age}"))
+            .header(header::ETAG, "\"v1\"");
+        let res = if not_modified {
+            res.status(StatusCode::NOT_MODIFIED).body(Bytes::new())
+        } else {
+            res.body(Bytes::from_static(b"hello"))
+        };
+        Ok(res.unwrap().into())
+    }
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    struct Noop;
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(Noop));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = Box::pin(fut);
+    loop {
+        if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+fn client(origin: &Origin) -> ClientWithMiddleware {
+    ClientBuilder::new(reqwest::Client::new())
+        .with(CacheMiddleware::new(Arc::new(MemoryCache::default())))
+        .with(origin.clone())
+        .build()
+}
+
+fn get(client: &ClientWithMiddleware, url: &str) -> (StatusCode, Bytes) {
+    block_on(async {
+        let res = client.get(url).send().await.unwrap();
+        (res.status(), res.bytes().await.unwrap())
+    })
+}
+
+#[test]
+fn serves_fresh_responses_from_the_store() {
+    let origin = Origin::default();
+    let client = client(&origin);
+
+    for _ in 0..3 {
+        let (status, body) = get(&client, "http://example.com/fresh");
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "hello");
+    }
+    assert_eq!(origin.requests.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn revalidates_stale_responses() {
+    let origin = Origin::default();
+    let client = client(&origin);
+
+    assert_eq!(get(&client, "http://example.com/stale").1, "hello");
+    let (status, body) = get(&client, "http://example.com/stale");
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "hello");
+
+    let requests = origin.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(!requests[0].contains_key(header::IF_NONE_MATCH));
+    assert_eq!(requests[1][header::IF_NONE_MATCH], "\"v1\"");
+}
@@ -1,5 +1,8 @@
 use http::{header, Method, Request, Response, StatusCode};
-use http_cache_policy::{CachePolicy, Config};
+use http_cache_policy::{
+    config::{StatusCodes, TtlOverride},
+    CachePolicy, Config,
+};
 use std::time::{Duration, SystemTime};
 
 use crate::{harness, private_config, req_cache_control, request_parts, response_parts};
@@ -82,10 +85,43 @@ fn pre_check_poison() {
     assert!(cache_control_header.contains("max-age=100"));
     assert!(cache_control_header.contains("custom"));
     assert!(cache_control_header.contains("foo=bar"));
+    assert_eq!(
+        policy.response_headers()[header::CACHE_CONTROL],
+        "max-age=100, custom, foo=bar"
+    );
 
     assert!(!res.headers.contains_key(header::PRAGMA));
 }
 
+#[test]
+fn pre_check_poison_keeps_the_rest_as_written() {
+    let now = SystemTime::now();
+    let response = response_parts(
+        Response::builder()
+            .header(
+                header::CACHE_CONTROL,
+                r#"Pre-Check=0, Max-Age=100, No-Cache, x-ext="a, \"b\"", post-check=0"#,
+            )
+            .header(
+                header::CACHE_CONTROL,
+                "no-store, x-Vendor=1,, must-revalidate",
+            ),
+    );
+
+    let policy = harness()
+        .assert_time_to_live(100)
+        .time(now)
+        .config(Config::default().ignore_cargo_cult(true))
+        .test_with_response(response);
+
+    let values: Vec<_> = policy
+        .response_headers()
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .collect();
+    assert_eq!(values, [r#"Max-Age=100, x-ext="a, \"b\"""#, "x-Vendor=1"]);
+}
+
 #[test]
 fn age_can_make_stale() {
     let response = response_parts(
@@ -283,6 +319,151 @@ fn uncacheable_412_precondition_failed() {
     harness().no_store().test_with_response(response);
 }
 
+#[test]
+fn uncacheable_206_partial_content_by_default() {
+    let response = response_parts(
+        Response::builder()
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CACHE_CONTROL, "public, max-age=1000"),
+    );
+    harness().no_store().test_with_response(response);
+}
+
+#[test]
+fn cacheable_206_partial_content_when_understood() {
+    let response = response_parts(
+        Response::builder()
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CACHE_CONTROL, "public, max-age=1000"),
+    );
+    let config = Config::default().understood_statuses(StatusCodes::UNDERSTOOD.with(206).with(226));
+    harness()
+        .config(config)
+        .assert_time_to_live(1000)
+        .test_with_response(response);
+}
+
+#[test]
+fn uncacheable_when_status_is_no_longer_understood() {
+    let response = response_parts(
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(header::CACHE_CONTROL, "public, max-age=1000"),
+    );
+    let config = Config::default().understood_statuses(StatusCodes::UNDERSTOOD.without(404));
+    harness()
+        .config(config)
+        .no_store()
+        .test_with_response(response);
+}
+
+#[test]
+fn uncacheable_404_when_not_heuristically_cacheable() {
+    let response = response_parts(
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(header::LAST_MODIFIED, "Mon, 07 Mar 2016 11:52:56 GMT"),
+    );
+    let config =
+        Config::default().heuristic_statuses(StatusCodes::HEURISTICALLY_CACHEABLE.without(404));
+    harness()
+        .config(config)
+        .no_store()
+        .test_with_response(response);
+}
+
+#[test]
+fn cacheable_451_when_heuristically_cacheable() {
+    let response = response_parts(
+        Response::builder()
+            .status(StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS)
+            .header(header::LAST_MODIFIED, "Mon, 07 Mar 2016 11:52:56 GMT"),
+    );
+    let config = Config::default()
+        .understood_statuses(StatusCodes::UNDERSTOOD.with(451))
+        .heuristic_statuses(StatusCodes::HEURISTICALLY_CACHEABLE.with(451));
+    harness().config(config).test_with_response(response);
+}
+
+#[test]
+fn explicit_freshness_ignores_heuristic_statuses() {
+    let response = response_parts(
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(header::CACHE_CONTROL, "max-age=100"),
+    );
+    let config = Config::default().heuristic_statuses(StatusCodes::empty());
+    harness()
+        .config(config)
+        .assert_time_to_live(100)
+        .test_with_response(response);
+}
+
+#[test]
+fn status_ttl_caps_redirects() {
+    let config = Config::default().status_ttl(
+        StatusCodes::range(300, 399),
+        TtlOverride::Max(Duration::from_secs(3600)),
+    );
+    let response = |status, cache_control| {
+        response_parts(
+            Response::builder()
+                .status(status)
+                .header(header::CACHE_CONTROL, cache_control),
+        )
+    };
+
+    harness()
+        .config(config.clone())
+        .assert_time_to_live(3600)
+        .test_with_response(response(StatusCode::MOVED_PERMANENTLY, "max-age=86400"));
+    harness()
+        .config(config.clone())
+        .assert_time_to_live(60)
+        .test_with_response(response(StatusCode::MOVED_PERMANENTLY, "max-age=60"));
+    harness()
+        .config(config)
+        .assert_time_to_live(86400)
+        .test_with_response(response(StatusCode::OK, "max-age=86400"));
+}
+
+#[test]
+fn status_ttl_replaces_lifetime() {
+    let config = Config::default()
+        .status_ttl(
+            StatusCodes::empty().with(404),
+            TtlOverride::Replace(Duration::from_secs(30)),
+        )
+        .status_ttl(
+            StatusCodes::range(400, 499),
+            TtlOverride::Replace(Duration::from_secs(5)),
+        );
+
+    harness()
+        .config(config.clone())
+        .assert_time_to_live(30)
+        .test_with_response(response_parts(
+            Response::builder().status(StatusCode::NOT_FOUND),
+        ));
+    harness()
+        .config(config.clone())
+        .assert_time_to_live(5)
+        .test_with_response(response_parts(
+            Response::builder()
+                .status(StatusCode::GONE)
+                .header(header::CACHE_CONTROL, "max-age=600"),
+        ));
+    // never makes an uncacheable response fresh
+    harness()
+        .config(config)
+        .stale_and_store()
+        .test_with_response(response_parts(
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header(header::CACHE_CONTROL, "no-cache"),
+        ));
+}
+
 #[test]
 fn expired_expires_cache_with_max_age() {
     let response = response_parts(
@@ -373,3 +554,132 @@ fn get_cached_response(
         _ => panic!("stale"),
     }
 }
+
+#[test]
+fn write_cached_headers_matches_before_request() {
+    let now = SystemTime::now();
+    let response = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::CONNECTION, "x-hop")
+            .header("x-hop", "1")
+            .header("x-kept", "1")
+            .header(header::AGE, "10"),
+    );
+    let policy = CachePolicy::with_config(
+        &request_parts(Request::builder()),
+        &response,
+        now,
+        Default::default(),
+    );
+
+    for later in [now + Duration::from_secs(5), now + Duration::from_secs(120)] {
+        let mut headers = response.headers.clone();
+        policy.write_cached_headers(&mut headers, later);
+
+        assert_eq!(headers, policy.stale_response(later).headers);
+        assert!(!headers.contains_key("x-hop"));
+        assert!(!headers.contains_key(header::CONNECTION));
+        assert!(headers.contains_key("x-kept"));
+    }
+
+    let mut headers = response.headers.clone();
+    policy.write_cached_headers(&mut headers, now + Duration::from_secs(5));
+    assert_eq!(headers[header::AGE], "15");
+    assert!(!headers.contains_key(header::WARNING));
+
+    let mut headers = response.headers;
+    policy.write_cached_headers(&mut headers, now + Duration::from_secs(120));
+    assert_eq!(headers[header::AGE], "130");
+    assert_eq!(headers[header::WARNING], r#"110 - "Response is Stale""#);
+}
+
+#[test]
+fn strips_configured_headers_before_storing() {
+    let config = Config::default().strip_response_headers(
+        [header::SET_COOKIE, "server-timing".parse().unwrap()]
+            .into_iter()
+            .collect(),
+    );
+    let policy = CachePolicy::with_config(
+        &request_parts(Request::builder()),
+        &response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=60")
+                .header(header::SET_COOKIE, "a=1")
+                .header(header::SET_COOKIE, "b=2")
+                .header("server-timing", "db;dur=53")
+                .header("x-kept", "1"),
+        ),
+        SystemTime::now(),
+        config,
+    );
+
+    assert!(policy.is_storable());
+    let headers = policy.response_headers();
+    assert!(!headers.contains_key(header::SET_COOKIE));
+    assert!(!headers.contains_key("server-timing"));
+    assert_eq!(headers["x-kept"], "1");
+    assert!(!policy
+        .stale_response(SystemTime::now())
+        .headers
+        .contains_key(header::SET_COOKIE));
+}
+
+#[test]
+fn drops_oversized_headers() {
+    let big_cookie = "a".repeat(100);
+    let response = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::SET_COOKIE, big_cookie.as_str())
+            .header(header::SET_COOKIE, "b=2")
+            .header(header::LINK, "</a>; rel=preload"),
+    );
+    let req = request_parts(Request::builder());
+    let config = Config::default().max_stored_header_bytes(64);
+
+    let policy = CachePolicy::with_config(&req, &response, SystemTime::now(), config.clone());
+    assert!(policy.is_storable());
+    let cookies: Vec<_> = policy
+        .response_headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .collect();
+    assert_eq!(cookies, ["b=2"]);
+    assert!(policy.response_headers().contains_key(header::LINK));
+
+    let policy = CachePolicy::with_config(
+        &req,
+        &response,
+        SystemTime::now(),
+        config.oversized_headers(http_cache_policy::config::OversizedHeaders::NotStorable),
+    );
+    assert!(!policy.is_storable());
+    assert_eq!(
+        policy.storability(),
+        http_cache_policy::Storability::HeadersTooLarge
+    );
+}
+
+#[test]
+fn status_and_header_tuples_are_responses() {
+    let req = request_parts(Request::builder());
+    let mut headers = http::HeaderMap::new();
+    headers.insert(header::CACHE_CONTROL, "max-age=60".parse().unwrap());
+
+    let borrowed = CachePolicy::new(&req, &(StatusCode::OK, &headers));
+    assert!(borrowed.is_storable());
+
+    let shared = std::sync::Arc::new(headers);
+    let policy = CachePolicy::new(
+        &(
+            Method::GET,
+            "/".parse().unwrap(),
+            std::sync::Arc::clone(&shared),
+        ),
+        &(StatusCode::OK, shared),
+    );
+    assert!(policy.is_storable());
+    assert_eq!(policy.response_headers(), borrowed.response_headers());
+}
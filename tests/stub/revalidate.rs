@@ -346,6 +346,28 @@ fn removes_warnings() {
         .contains_key(header::WARNING));
 }
 
+#[test]
+fn only_removes_1xx_warning_codes() {
+    let now = SystemTime::now();
+    let req = request_parts(Request::builder());
+    let policy = CachePolicy::new(
+        &req,
+        &response_parts(
+            Response::builder()
+                .header("cache-control", "max-age=2")
+                .header(
+                    header::WARNING,
+                    r#"199 - "a, 299 - b" "Tue, 15 Nov 1994 08:12:31 GMT", 1000 - "x", 1 - "y", 299 - "kept""#,
+                ),
+        ),
+    );
+
+    assert_eq!(
+        get_cached_response(&policy, &req, now).headers[header::WARNING],
+        r#"1000 - "x", 1 - "y", 299 - "kept""#
+    );
+}
+
 #[test]
 fn must_contain_any_etag() {
     let now = SystemTime::now();
@@ -465,6 +487,147 @@ fn should_not_send_the_last_modified_value_for_range_request() {
     assert!(!headers.contains_key(header::IF_MODIFIED_SINCE));
 }
 
+#[test]
+fn range_request_sends_strong_etag_as_if_range() {
+    let now = SystemTime::now();
+    let policy = simple_request_with_etagged_response();
+
+    let range_request = request_parts(
+        simple_request_builder()
+            .header(header::RANGE, "bytes=0-99")
+            .header(header::IF_RANGE, "\"client-etag\""),
+    );
+    let headers = get_revalidation_request(
+        &policy,
+        &range_request,
+        now + Duration::from_secs(3600 * 24),
+    )
+    .headers;
+
+    assert_eq!(headers.get(header::IF_RANGE).unwrap(), etag_value());
+    assert_eq!(headers.get(header::RANGE).unwrap(), "bytes=0-99");
+}
+
+#[test]
+fn range_request_sends_strong_last_modified_as_if_range() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::new(
+        &simple_request(),
+        &response_parts(
+            cacheable_response_builder()
+                .header(header::DATE, "Tue, 15 Nov 1994 12:45:27 GMT")
+                .header(header::LAST_MODIFIED, very_old_date()),
+        ),
+    );
+
+    let range_request = request_parts(simple_request_builder().header(header::RANGE, "bytes=0-99"));
+    let headers = get_revalidation_request(
+        &policy,
+        &range_request,
+        now + Duration::from_secs(3600 * 24),
+    )
+    .headers;
+
+    assert_eq!(headers.get(header::IF_RANGE).unwrap(), very_old_date());
+    assert!(!headers.contains_key(header::IF_MODIFIED_SINCE));
+}
+
+#[test]
+fn range_request_never_sends_weak_validators_as_if_range() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::new(
+        &simple_request(),
+        &response_parts(
+            cacheable_response_builder()
+                .header(header::ETAG, "W/\"weak\"")
+                // same second as the date, so only a weak validator
+                .header(header::DATE, very_old_date())
+                .header(header::LAST_MODIFIED, very_old_date()),
+        ),
+    );
+
+    let range_request = request_parts(
+        simple_request_builder()
+            .header(header::RANGE, "bytes=0-99")
+            .header(header::IF_RANGE, "\"client-etag\""),
+    );
+    let headers = get_revalidation_request(
+        &policy,
+        &range_request,
+        now + Duration::from_secs(3600 * 24),
+    )
+    .headers;
+
+    assert!(!headers.contains_key(header::IF_RANGE), "{headers:#?}");
+    assert_no_validators(&headers);
+}
+
+#[test]
+fn no_if_range_without_range() {
+    let now = SystemTime::now();
+    let policy = simple_request_with_etagged_response();
+
+    let headers = get_revalidation_request(
+        &policy,
+        &simple_request(),
+        now + Duration::from_secs(3600 * 24),
+    )
+    .headers;
+
+    assert!(!headers.contains_key(header::IF_RANGE));
+}
+
+#[test]
+fn before_request_mut_adds_validators_in_place() {
+    let now = SystemTime::now() + Duration::from_secs(3600 * 24);
+    let policy = simple_request_with_etagged_response();
+    let mut req = simple_request();
+
+    let result = policy.before_request_mut(&mut req, now);
+
+    assert!(matches!(
+        result,
+        http_cache_policy::BeforeRequestMut::Stale { matches: true, .. }
+    ));
+    assert_custom_header(&req.headers);
+    assert_no_connection(&req.headers);
+    assert_eq!(
+        req.headers,
+        get_revalidation_request(&policy, &simple_request(), now).headers
+    );
+    assert_eq!(
+        req.headers.get(header::IF_NONE_MATCH).unwrap(),
+        "\"123456789\""
+    );
+}
+
+#[test]
+fn before_request_mut_leaves_fresh_requests_alone() {
+    let policy = simple_request_with_etagged_response();
+    let mut req = simple_request();
+
+    let result = policy.before_request_mut(&mut req, SystemTime::now());
+
+    assert!(result.is_fresh());
+    assert_eq!(req.headers, simple_request().headers);
+}
+
+#[test]
+fn before_request_mut_leaves_mismatched_requests_alone() {
+    let now = SystemTime::now() + Duration::from_secs(3600 * 24);
+    let policy = simple_request_with_etagged_response();
+    let mut req = request_parts(simple_request_builder().uri("/elsewhere"));
+    let headers = req.headers.clone();
+
+    let result = policy.before_request_mut(&mut req, now);
+
+    assert!(matches!(
+        result,
+        http_cache_policy::BeforeRequestMut::Stale { matches: false, .. }
+    ));
+    assert_eq!(req.headers, headers);
+}
+
 fn get_cached_response(
     policy: &CachePolicy,
     req: &impl http_cache_policy::RequestLike,
@@ -482,7 +645,9 @@ fn get_revalidation_request(
     now: SystemTime,
 ) -> http::request::Parts {
     match policy.before_request(req, now) {
-        http_cache_policy::BeforeRequest::Stale { request, matches } => {
+        http_cache_policy::BeforeRequest::Stale {
+            request, matches, ..
+        } => {
             if !matches {
                 eprintln!("warning: req doesn't match {req:#?} vs {policy:#?}");
             }
@@ -491,3 +656,103 @@ fn get_revalidation_request(
         _ => panic!("no revalidation needed {req:#?} vs {policy:#?}"),
     }
 }
+
+#[test]
+fn etags_with_commas_stay_intact() {
+    let now = SystemTime::now();
+    let post_request = request_parts(
+        simple_request_builder()
+            .method(Method::POST)
+            .header(header::IF_NONE_MATCH, "W/\"a,b\", \"c, d\""),
+    );
+    let policy = CachePolicy::new(
+        &post_request,
+        &response_parts(cacheable_response_builder().header(header::ETAG, "\"e,f\"")),
+    );
+
+    let headers =
+        get_revalidation_request(&policy, &post_request, now + Duration::from_secs(3600 * 24))
+            .headers;
+
+    assert_eq!(
+        headers.get(header::IF_NONE_MATCH).unwrap(),
+        "\"c, d\", \"e,f\""
+    );
+}
+
+#[test]
+fn does_not_repeat_the_stored_etag() {
+    let now = SystemTime::now();
+    let policy = simple_request_with_etagged_response();
+
+    for (incoming, expected) in [
+        (r#""123456789""#, r#""123456789""#),
+        (r#""a", "123456789", "a""#, r#""a", "123456789""#),
+        (r#"W/"123456789", "a""#, r#""123456789", "a""#),
+    ] {
+        let req = request_parts(simple_request_builder().header(header::IF_NONE_MATCH, incoming));
+        let headers =
+            get_revalidation_request(&policy, &req, now + Duration::from_secs(3600 * 24)).headers;
+        assert_eq!(headers.get(header::IF_NONE_MATCH).unwrap(), expected);
+    }
+}
+
+#[test]
+fn non_ascii_headers_do_not_panic() {
+    let now = SystemTime::now();
+    let value = |bytes: &[u8]| HeaderValue::from_bytes(bytes).unwrap();
+    let req = request_parts(
+        simple_request_builder()
+            .header(header::IF_NONE_MATCH, value(b"\"\xff\", W/\"a\""))
+            .header(header::RANGE, "bytes=0-1"),
+    );
+    let policy = CachePolicy::with_config(
+        &req,
+        &response_parts(
+            cacheable_response_builder()
+                .header(
+                    header::CACHE_CONTROL,
+                    value(b"pre-check=0, post-check=0, x=\xff"),
+                )
+                .header(header::ETAG, value(b"\"\xe9\""))
+                .header(header::WARNING, value(b"199 - \"\xff\""))
+                .header(header::WARNING, "110 - \"stale\", 299 - \"ok\"")
+                .header(header::LAST_MODIFIED, value(b"\xff")),
+        ),
+        now,
+        http_cache_policy::Config::default().ignore_cargo_cult(true),
+    );
+
+    let later = now + Duration::from_secs(3600 * 24);
+    let headers = get_revalidation_request(&policy, &req, later).headers;
+    assert!(!headers.contains_key(header::IF_NONE_MATCH));
+    let headers = get_cached_response(&policy, &req, now).headers;
+    assert_eq!(headers[header::WARNING], "299 - \"ok\"");
+}
+
+#[test]
+fn try_variants_report_dates_out_of_range() {
+    let now = SystemTime::now();
+    let req = simple_request();
+    let policy = CachePolicy::new_at(&req, &response_parts(cacheable_response_builder()), now);
+
+    let far_future = SystemTime::UNIX_EPOCH + Duration::from_secs(300_000_000_000);
+    assert_eq!(
+        policy.try_stale_response(far_future).unwrap_err(),
+        http_cache_policy::Error::Date(far_future)
+    );
+    let mut headers = HeaderMap::new();
+    assert!(policy
+        .try_write_cached_headers(&mut headers, far_future)
+        .is_err());
+    // The infallible versions clamp it instead
+    let headers = policy.stale_response(far_future).headers;
+    assert_eq!(headers[header::DATE], "Fri, 31 Dec 9999 23:59:59 GMT");
+
+    assert!(policy.try_before_request(&req, now).unwrap().is_fresh());
+    let mut parts = simple_request();
+    assert!(policy
+        .try_before_request_mut(&mut parts, now)
+        .unwrap()
+        .is_fresh());
+}
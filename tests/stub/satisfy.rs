@@ -1,4 +1,5 @@
 use http::{header, Method, Request, Response};
+use http_cache_policy::{CachePolicy, Config};
 use std::time::Duration;
 use std::time::SystemTime;
 
@@ -67,6 +68,54 @@ fn not_when_hosts_mismatch() {
         .is_fresh());
 }
 
+#[test]
+fn when_uri_authority_matches_host() {
+    let now = SystemTime::now();
+    let origin_form = || {
+        Request::builder()
+            .uri("/a?b")
+            .header(header::HOST, "Example.com")
+    };
+    let absolute_form = || Request::builder().uri("https://example.com/a?b");
+    let response =
+        || response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=2"));
+
+    for (stored, presented, matches) in [
+        (origin_form(), absolute_form(), true),
+        (absolute_form(), origin_form(), true),
+        (
+            absolute_form(),
+            absolute_form().header(header::HOST, "other.com"),
+            true,
+        ),
+        (
+            origin_form(),
+            Request::builder().uri("https://other.com/a?b"),
+            false,
+        ),
+        (
+            origin_form(),
+            Request::builder().uri("https://example.com/a"),
+            false,
+        ),
+    ] {
+        let stored = request_parts(stored);
+        let presented = request_parts(presented);
+        let policy = CachePolicy::new_at(&stored, &response(), now);
+        assert_eq!(
+            policy.before_request(&presented, now).is_fresh(),
+            matches,
+            "{} {}",
+            stored.uri,
+            presented.uri
+        );
+
+        let strict = Config::default().strict_host_matching(true);
+        let policy = CachePolicy::with_config(&stored, &response(), now, strict);
+        assert!(!policy.before_request(&presented, now).is_fresh());
+    }
+}
+
 #[test]
 fn when_methods_match_head() {
     harness()
@@ -114,3 +163,26 @@ fn not_when_no_cache_requesting() {
         )
         .is_fresh());
 }
+
+#[test]
+fn satisfies_without_revalidation_agrees_with_before_request() {
+    let now = SystemTime::now();
+    let policy = harness().time(now).test_with_cache_control("max-age=200");
+
+    for (req, later) in [
+        (request_parts(Request::builder()), 100),
+        (request_parts(Request::builder()), 300),
+        (req_cache_control("max-stale"), 300),
+        (req_cache_control("no-cache"), 100),
+        (req_cache_control("min-fresh=150"), 100),
+        (request_parts(Request::builder().method(Method::POST)), 100),
+    ] {
+        let later = now + Duration::from_secs(later);
+        assert_eq!(
+            policy.satisfies_without_revalidation(&req, later),
+            policy.before_request(&req, later).is_fresh(),
+        );
+    }
+    assert!(policy.satisfies_without_revalidation(&request_parts(Request::builder()), now));
+    assert!(!policy.satisfies_without_revalidation(&req_cache_control("no-cache"), now));
+}
@@ -0,0 +1,164 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use http::{header, HeaderValue, Request, Response};
+use http_cache_policy::{
+    config::{HeaderNames, Methods, StatusCodes, StoredRequestHeaders, TtlOverride},
+    BeforeRequest, CachePolicy, Config, WithBodyDigest,
+};
+
+use crate::{request_parts, response_parts};
+
+fn policy() -> CachePolicy {
+    let req = request_parts(
+        Request::builder()
+            .method("QUERY")
+            .uri("https://example.com/a?b=c")
+            .header(header::ACCEPT, "text/html")
+            .header(header::COOKIE, "a=1"),
+    );
+    let mut res = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "public, max-age=60, ext=\"x y\"")
+            .header(header::SET_COOKIE, "a=1")
+            .header(header::SET_COOKIE, "b=2")
+            .header(header::VARY, "accept"),
+    );
+    // Not valid UTF-8, so it has to be written as bytes
+    res.headers
+        .insert("x-binary", HeaderValue::from_bytes(b"caf\xe9").unwrap());
+    let config = Config::default()
+        .cache_query(true)
+        .cacheable_methods(Methods::CACHEABLE.with(http::Method::PUT))
+        .understood_statuses(StatusCodes::UNDERSTOOD.with(206))
+        .status_ttl(
+            StatusCodes::empty().with(404),
+            TtlOverride::Replace(Duration::from_secs(10)),
+        )
+        .strip_response_headers(HeaderNames::from_static(&[header::SERVER]))
+        .max_stored_header_bytes(4096)
+        .ignored_query_params(["utm_*"]);
+    let digest = [1, 2, 3];
+    CachePolicy::with_config(
+        &WithBodyDigest::new(&req, &digest),
+        &res,
+        http_cache_policy::now(),
+        config,
+    )
+}
+
+fn assert_same(restored: &CachePolicy, policy: &CachePolicy) {
+    assert_eq!(restored.identity(), policy.identity());
+    assert_eq!(restored.response_time(), policy.response_time());
+    assert_eq!(restored.request_headers(), policy.request_headers());
+    assert_eq!(restored.response_headers(), policy.response_headers());
+    assert_eq!(restored.storability(), policy.storability());
+    assert_eq!(restored.freshness_lifetime(), policy.freshness_lifetime());
+    assert_eq!(
+        format!("{:?}", restored.options()),
+        format!("{:?}", policy.options())
+    );
+}
+
+#[test]
+fn round_trips_through_cbor() {
+    let policy = policy();
+    let mut bytes = Vec::new();
+    ciborium::into_writer(&policy, &mut bytes).unwrap();
+    let restored: CachePolicy = ciborium::from_reader(bytes.as_slice()).unwrap();
+    assert_same(&restored, &policy);
+}
+
+#[test]
+fn round_trips_through_messagepack() {
+    let policy = policy();
+    let restored: CachePolicy =
+        rmp_serde::from_slice(&rmp_serde::to_vec(&policy).unwrap()).unwrap();
+    assert_same(&restored, &policy);
+
+    let named = rmp_serde::to_vec_named(&policy).unwrap();
+    assert_same(&rmp_serde::from_slice(&named).unwrap(), &policy);
+}
+
+#[test]
+fn decisions_round_trip_through_binary_formats() {
+    let policy = policy();
+    let req = request_parts(Request::builder().uri("https://example.com/a?b=c"));
+    let decision = policy.before_request(&req, http_cache_policy::now());
+
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&decision, &mut cbor).unwrap();
+    let restored: BeforeRequest = ciborium::from_reader(cbor.as_slice()).unwrap();
+    assert_eq!(format!("{:?}", restored), format!("{:?}", decision));
+
+    let msgpack = rmp_serde::to_vec(&decision).unwrap();
+    let restored: BeforeRequest = rmp_serde::from_slice(&msgpack).unwrap();
+    assert_eq!(format!("{:?}", restored), format!("{:?}", decision));
+}
+
+#[test]
+fn vary_stored_request_headers_leave_out_credentials() {
+    let req = request_parts(
+        Request::builder()
+            .uri("https://example.com/a")
+            .header(header::HOST, "example.com")
+            .header(header::ACCEPT, "text/html")
+            .header(header::COOKIE, "session=secret"),
+    );
+    let res = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=60")
+            .header(header::VARY, "accept"),
+    );
+    let config = Config::default().stored_request_headers(StoredRequestHeaders::Vary);
+    let policy = CachePolicy::with_config(&req, &res, http_cache_policy::now(), config);
+
+    let json = serde_json::to_string(&policy).unwrap();
+    assert!(!json.contains("secret"));
+    let restored: CachePolicy = serde_json::from_str(&json).unwrap();
+    assert_same(&restored, &policy);
+    assert!(restored.matches(&req));
+}
+
+/// Written before the format was versioned
+const UNVERSIONED_JSON: &str = r#"{"req":{"accept":"text/html"},"res":{"cache-control":"max-age=60","etag":"\"v1\"","vary":"accept"},"uri":"https://example.com/a","status":200,"method":"GET","config":{"mode":"Shared","last_modified":0.1,"ignore_cargo_cult":false,"understood_statuses":[0,0,0,6400,7019282231721984,0,1143996416,9007199254740992,0,0,0,0,0,0,0,0],"heuristic_statuses":[0,0,0,22784,4556376185503744,0,1143996416,9007199254740992,0,0,0,0,0,0,0,0],"cache_query":false,"cacheable_methods":["GET","HEAD","POST"],"status_ttls":[],"strip_response_headers":[],"max_stored_header_bytes":null,"oversized_headers":"Drop","ignored_query_params":[],"stored_request_headers":"All"},"res_cc":{"max-age":"60"},"req_cc":{},"response_time":{"secs_since_epoch":1700000000,"nanos_since_epoch":0},"body_digest":null}"#;
+
+/// The same policy as [`UNVERSIONED_JSON`] in positional MessagePack
+const UNVERSIONED_MESSAGEPACK: &str = "9a81a66163636570749199746578742f68746d6c83ad63616368652d636f6e74726f6c919a6d61782d6167653d3630a465746167919422763122a4766172799196616363657074b568747470733a2f2f6578616d706c652e636f6d2f61ccc8a34745549da6536861726564ca3dcccccdc2dc0010000000cd1900cf0018f0000000000000ce44300000cf00200000000000000000000000000000dc0010000000cd5900cf001030000000000000ce44300000cf00200000000000000000000000000000c293a3474554a448454144a4504f53549090c0a444726f7090a3416c6c81a76d61782d616765a236308092ce6553f10000c0";
+
+fn assert_unversioned_policy(policy: &CachePolicy) {
+    let then = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    assert_eq!(policy.response_time(), then);
+    assert_eq!(policy.uri(), "https://example.com/a");
+    assert_eq!(policy.response_headers()[header::ETAG], "\"v1\"");
+    assert_eq!(policy.request_headers()[header::ACCEPT], "text/html");
+    assert_eq!(policy.time_to_live(then), Duration::from_secs(60));
+}
+
+#[test]
+fn reads_unversioned_entries() {
+    let policy: CachePolicy = serde_json::from_str(UNVERSIONED_JSON).unwrap();
+    assert_unversioned_policy(&policy);
+
+    let bytes = (0..UNVERSIONED_MESSAGEPACK.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&UNVERSIONED_MESSAGEPACK[i..i + 2], 16).unwrap())
+        .collect::<Vec<_>>();
+    let policy: CachePolicy = rmp_serde::from_slice(&bytes).unwrap();
+    assert_unversioned_policy(&policy);
+}
+
+#[test]
+fn writes_the_format_version() {
+    let json = serde_json::to_value(policy()).unwrap();
+    assert_eq!(json["version"], 1);
+}
+
+#[test]
+fn rejects_unknown_format_versions() {
+    let mut json: serde_json::Value = serde_json::from_str(UNVERSIONED_JSON).unwrap();
+    json["version"] = 2.into();
+    let err = serde_json::from_value::<CachePolicy>(json).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("unsupported `CachePolicy` format version 2"));
+}
@@ -0,0 +1,164 @@
+use std::{
+    convert::Infallible,
+    future::{ready, Future, Ready},
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+};
+
+use bytes::Bytes;
+use http::{header, Method, Request, Response, StatusCode};
+use http_body_util::{BodyExt, Full};
+use http_cache_policy::{server::ConditionalLayer, storage::MemoryCache};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Responds with `hello` and an `ETag`, using the `Cache-Control` that it was created with
+#[derive(Clone)]
+struct Handler {
+    cache_control: &'static str,
+    requests: Arc<Mutex<usize>>,
+}
+
+impl Handler {
+    fn new(cache_control: &'static str) -> Self {
+        Self {
+            cache_control,
+            requests: Arc::default(),
+        }
+    }
+
+    fn requests(&self) -> usize {
+        *self.requests.lock().unwrap()
+    }
+}
+
+impl Service<Request<()>> for Handler {
+    type Response = Response<Full<Bytes>>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _: Request<()>) -> Self::Future {
+        *self.requests.lock().unwrap() += 1;
+        let res = Response::builder()
+            .header(header::CACHE_CONTROL, self.cache_control)
+            .header(header::ETAG, "\"v1\"")
+            .body(Full::new(Bytes::from_static(b"hello")));
+        ready(Ok(res.unwrap()))
+    }
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    struct Noop;
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(Noop));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = Box::pin(fut);
+    loop {
+        if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+fn send<S>(service: &mut S, req: http::request::Builder) -> (StatusCode, Bytes)
+where
+    S: Service<
+        Request<()>,
+        Response = Response<http_cache_policy::server::ConditionalBody<Full<Bytes>>>,
+    >,
+    S::Error: std::fmt::Debug,
+{
+    let uri = "https://example.com/a";
+    let res = block_on(service.call(req.uri(uri).body(()).unwrap())).unwrap();
+    let status = res.status();
+    let body = block_on(res.into_body().collect()).unwrap().to_bytes();
+    (status, body)
+}
+
+#[test]
+fn fresh_responses_are_served_from_the_store() {
+    let handler = Handler::new("max-age=60");
+    let mut service =
+        ConditionalLayer::new(Arc::new(MemoryCache::default())).layer(handler.clone());
+
+    assert_eq!(
+        send(&mut service, Request::builder()),
+        (StatusCode::OK, Bytes::from("hello"))
+    );
+    assert_eq!(
+        send(&mut service, Request::builder()),
+        (StatusCode::OK, Bytes::from("hello"))
+    );
+    assert_eq!(handler.requests(), 1);
+}
+
+#[test]
+fn matching_validators_get_304s() {
+    let handler = Handler::new("max-age=60");
+    let mut service =
+        ConditionalLayer::new(Arc::new(MemoryCache::default())).layer(handler.clone());
+
+    let conditional = || Request::builder().header(header::IF_NONE_MATCH, "\"v1\"");
+    assert_eq!(
+        send(&mut service, conditional()).0,
+        StatusCode::NOT_MODIFIED
+    );
+    assert_eq!(
+        send(&mut service, conditional()).0,
+        StatusCode::NOT_MODIFIED
+    );
+    assert_eq!(handler.requests(), 1);
+
+    let stale = Request::builder().header(header::IF_NONE_MATCH, "\"v0\"");
+    assert_eq!(
+        send(&mut service, stale),
+        (StatusCode::OK, Bytes::from("hello"))
+    );
+
+    let failed = Request::builder().header(header::IF_MATCH, "\"v0\"");
+    assert_eq!(
+        send(&mut service, failed).0,
+        StatusCode::PRECONDITION_FAILED
+    );
+}
+
+#[test]
+fn unstorable_responses_pass_through() {
+    let handler = Handler::new("no-store");
+    let mut service =
+        ConditionalLayer::new(Arc::new(MemoryCache::default())).layer(handler.clone());
+
+    assert_eq!(
+        send(&mut service, Request::builder()),
+        (StatusCode::OK, Bytes::from("hello"))
+    );
+    let conditional = Request::builder().header(header::IF_NONE_MATCH, "\"v1\"");
+    assert_eq!(send(&mut service, conditional).0, StatusCode::NOT_MODIFIED);
+    assert_eq!(handler.requests(), 2);
+}
+
+#[test]
+fn unsafe_requests_invalidate_stored_responses() {
+    let handler = Handler::new("max-age=60");
+    let store = Arc::new(MemoryCache::default());
+    let mut service = ConditionalLayer::new(Arc::clone(&store)).layer(handler.clone());
+
+    send(&mut service, Request::builder());
+    assert_eq!(store.len(), 1);
+
+    let conditional = Request::builder()
+        .method(Method::POST)
+        .header(header::IF_NONE_MATCH, "\"v1\"");
+    assert_eq!(send(&mut service, conditional).0, StatusCode::OK);
+    assert_eq!(handler.requests(), 2);
+
+    send(&mut service, Request::builder());
+    assert_eq!(handler.requests(), 3);
+}
@@ -0,0 +1,225 @@
+use http::{header, Method, Request, Response};
+use http_cache_policy::{BeforeRequest, CachePolicy, Freshness, StaleAllowance, StaleReason};
+use std::time::{Duration, SystemTime};
+
+use crate::{request_parts, response_parts};
+
+fn request() -> http::request::Builder {
+    request_with("example.com", "en")
+}
+
+fn request_with(host: &str, language: &str) -> http::request::Builder {
+    Request::builder()
+        .uri("https://example.com/a")
+        .header(header::HOST, host)
+        .header(header::ACCEPT_LANGUAGE, language)
+}
+
+fn reason(policy: &CachePolicy, req: http::request::Builder, now: SystemTime) -> StaleReason {
+    match policy.before_request(&request_parts(req), now) {
+        BeforeRequest::Stale { reason, .. } => reason,
+        BeforeRequest::Fresh(_) => panic!("unexpectedly fresh"),
+    }
+}
+
+#[test]
+fn reports_why_entries_are_stale() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::with_config(
+        &request_parts(request()),
+        &response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=100, must-revalidate")
+                .header(header::VARY, "accept-language"),
+        ),
+        now,
+        Default::default(),
+    );
+    let later = now + Duration::from_secs(50);
+
+    for (req, expected) in [
+        (
+            request().uri("https://example.com/b"),
+            StaleReason::UriMismatch,
+        ),
+        (
+            request_with("example.org", "en").uri("/a"),
+            StaleReason::HostMismatch,
+        ),
+        (request().method(Method::POST), StaleReason::MethodMismatch),
+        (request_with("example.com", "de"), StaleReason::VaryMismatch),
+        (
+            request().header(header::CACHE_CONTROL, "no-cache"),
+            StaleReason::RequestNoCache,
+        ),
+        (
+            request().header(header::PRAGMA, "no-cache"),
+            StaleReason::RequestNoCache,
+        ),
+        (
+            request().header(header::CACHE_CONTROL, "max-age=10"),
+            StaleReason::RequestMaxAge,
+        ),
+        (
+            request().header(header::CACHE_CONTROL, "min-fresh=60"),
+            StaleReason::RequestMinFresh,
+        ),
+    ] {
+        assert_eq!(reason(&policy, req, later), expected);
+    }
+
+    let expired = now + Duration::from_secs(101);
+    assert_eq!(reason(&policy, request(), expired), StaleReason::Expired);
+    assert_eq!(
+        reason(
+            &policy,
+            request().header(header::CACHE_CONTROL, "max-stale"),
+            expired
+        ),
+        StaleReason::Expired,
+        "must-revalidate forbids serving stale"
+    );
+}
+
+#[test]
+fn mismatches_are_distinguished() {
+    assert!(StaleReason::VaryMismatch.is_mismatch());
+    assert!(StaleReason::MethodMismatch.is_mismatch());
+    assert!(!StaleReason::Expired.is_mismatch());
+    assert!(!StaleReason::RequestNoCache.is_mismatch());
+}
+
+#[test]
+fn classifies_freshness() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::with_config(
+        &request_parts(request()),
+        &response_parts(Response::builder().header(
+            header::CACHE_CONTROL,
+            "max-age=100, stale-while-revalidate=50, stale-if-error=500",
+        )),
+        now,
+        Default::default(),
+    );
+    let freshness = |req: http::request::Builder, secs| {
+        policy.freshness(&request_parts(req), now + Duration::from_secs(secs))
+    };
+
+    assert_eq!(freshness(request(), 50), Freshness::Fresh);
+    assert_eq!(
+        freshness(request().header(header::CACHE_CONTROL, "max-stale"), 120),
+        Freshness::StaleButUsable(StaleAllowance::MaxStale)
+    );
+    assert_eq!(
+        freshness(request(), 120),
+        Freshness::StaleButUsable(StaleAllowance::StaleWhileRevalidate)
+    );
+    assert_eq!(
+        freshness(request(), 200),
+        Freshness::StaleButUsable(StaleAllowance::StaleIfError)
+    );
+    assert_eq!(
+        freshness(request(), 700),
+        Freshness::MustRevalidate(StaleReason::Expired)
+    );
+    assert_eq!(
+        freshness(request().header(header::CACHE_CONTROL, "no-cache"), 120),
+        Freshness::MustRevalidate(StaleReason::RequestNoCache)
+    );
+    assert_eq!(
+        freshness(request().method(Method::POST), 50),
+        Freshness::MustRevalidate(StaleReason::MethodMismatch)
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn decisions_survive_serde() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::with_config(
+        &request_parts(request()),
+        &response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "max-age=100")
+                .header(header::ETAG, "\"v1\""),
+        ),
+        now,
+        Default::default(),
+    );
+
+    let fresh = policy.before_request(&request_parts(request()), now);
+    let json = serde_json::to_string(&fresh).unwrap();
+    match serde_json::from_str(&json).unwrap() {
+        BeforeRequest::Fresh(res) => assert_eq!(res.headers.get(header::ETAG).unwrap(), "\"v1\""),
+        other => panic!("unexpected {other:?}"),
+    }
+
+    let stale = policy.before_request(&request_parts(request()), now + Duration::from_secs(200));
+    let json = serde_json::to_string(&stale.clone()).unwrap();
+    match serde_json::from_str(&json).unwrap() {
+        BeforeRequest::Stale {
+            request,
+            matches,
+            reason,
+        } => {
+            assert_eq!(request.uri, "https://example.com/a");
+            assert_eq!(
+                request.headers.get(header::IF_NONE_MATCH).unwrap(),
+                "\"v1\""
+            );
+            assert!(matches);
+            assert_eq!(reason, StaleReason::Expired);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+
+    let not_modified = response_parts(
+        Response::builder()
+            .status(304)
+            .header(header::ETAG, "\"v1\""),
+    );
+    let after = policy.after_response(&request_parts(request()), &not_modified, now);
+    let json = serde_json::to_string(&after).unwrap();
+    match serde_json::from_str(&json).unwrap() {
+        http_cache_policy::AfterResponse::NotModified(policy, res) => {
+            assert!(policy.is_storable());
+            assert_eq!(res.status, 200);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn stale_responses_get_warning_110() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::with_config(
+        &request_parts(request()),
+        &response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=100")),
+        now,
+        Default::default(),
+    );
+    let warnings = |res: http::response::Parts| {
+        res.headers
+            .get_all(header::WARNING)
+            .iter()
+            .map(|v| v.to_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert!(warnings(policy.stale_response(now)).is_empty());
+    let later = now + Duration::from_secs(200);
+    let res = policy.stale_response(later);
+    assert_eq!(res.headers.get(header::AGE).unwrap(), "200");
+    assert_eq!(warnings(res), [r#"110 - "Response is Stale""#]);
+
+    // `before_request()` leaves the warning to callers that knowingly serve stale entries
+    let req = request_parts(request().header(header::CACHE_CONTROL, "max-stale"));
+    match policy.before_request(&req, later) {
+        BeforeRequest::Fresh(res) => assert!(warnings(res).is_empty()),
+        BeforeRequest::Stale { .. } => panic!("max-stale should allow reuse"),
+    }
+    match policy.before_request(&request_parts(request()), now) {
+        BeforeRequest::Fresh(res) => assert!(warnings(res).is_empty()),
+        BeforeRequest::Stale { .. } => panic!("unexpectedly stale"),
+    }
+}
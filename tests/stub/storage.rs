@@ -0,0 +1,244 @@
+use std::time::{Duration, SystemTime};
+
+use http::{header, Method, Request, Response, StatusCode};
+use http_cache_policy::{
+    storage::{CacheStorage, Lookup, MemoryCache, VariantSet},
+    AfterResponse, CachePolicy, Config,
+};
+
+fn get(language: &str) -> Request<()> {
+    Request::get("https://example.com/a")
+        .header(header::ACCEPT_LANGUAGE, language)
+        .body(())
+        .unwrap()
+}
+
+fn response(cache_control: &str) -> Response<()> {
+    Response::builder()
+        .header(header::CACHE_CONTROL, cache_control)
+        .header(header::ETAG, "\"v1\"")
+        .header(header::VARY, "accept-language")
+        .body(())
+        .unwrap()
+}
+
+#[test]
+fn serves_stored_variants() {
+    let cache = MemoryCache::new();
+    let now = SystemTime::now();
+
+    assert!(matches!(cache.lookup(&get("en"), now), Lookup::Miss));
+    assert!(cache.insert(&get("en"), &response("max-age=60"), "hello", now));
+    assert!(cache.insert(&get("fr"), &response("max-age=60"), "bonjour", now));
+    assert_eq!(cache.len(), 2);
+
+    assert!(matches!(
+        cache.lookup(&get("en"), now),
+        Lookup::Fresh(_, "hello")
+    ));
+    assert!(matches!(
+        cache.lookup(&get("fr"), now),
+        Lookup::Fresh(_, "bonjour")
+    ));
+    assert!(matches!(cache.lookup(&get("de"), now), Lookup::Miss));
+
+    // Replacing a variant doesn't touch the others
+    assert!(cache.insert(&get("en"), &response("max-age=60"), "hi", now));
+    assert_eq!(cache.len(), 2);
+    assert!(matches!(
+        cache.lookup(&get("en"), now),
+        Lookup::Fresh(_, "hi")
+    ));
+}
+
+#[test]
+fn unstorable_responses_are_not_stored() {
+    let cache = MemoryCache::new();
+    let now = SystemTime::now();
+
+    assert!(!cache.insert(&get("en"), &response("no-store"), "hello", now));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn stale_entries_are_revalidated() {
+    let cache = MemoryCache::new();
+    let now = SystemTime::now();
+    cache.insert(&get("en"), &response("max-age=60"), "hello", now);
+
+    let later = now + Duration::from_secs(120);
+    let (policy, request, body) = match cache.lookup(&get("en"), later) {
+        Lookup::Stale {
+            policy,
+            request,
+            body,
+        } => (policy, request, body),
+        other => panic!("expected a stale entry, got {:?}", other),
+    };
+    assert_eq!(request.headers[header::IF_NONE_MATCH], "\"v1\"");
+
+    let not_modified = Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .header(header::CACHE_CONTROL, "max-age=60")
+        .header(header::ETAG, "\"v1\"")
+        .body(())
+        .unwrap();
+    let (res, body) = cache
+        .update(&policy, body, &request, &not_modified, later)
+        .unwrap();
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(body, "hello");
+    assert!(matches!(
+        cache.lookup(&get("en"), later),
+        Lookup::Fresh(_, "hello")
+    ));
+
+    let modified = Response::builder()
+        .header(header::ETAG, "\"v2\"")
+        .body(())
+        .unwrap();
+    assert!(cache
+        .update(&policy, body, &request, &modified, later)
+        .is_none());
+}
+
+#[test]
+fn unsafe_requests_invalidate_every_variant() {
+    let cache = MemoryCache::new();
+    let now = SystemTime::now();
+    cache.insert(&get("en"), &response("max-age=60"), "hello", now);
+    cache.insert(&get("fr"), &response("max-age=60"), "bonjour", now);
+
+    let post = Request::builder()
+        .method(Method::POST)
+        .uri("https://example.com/a")
+        .body(())
+        .unwrap();
+    let res = Response::builder().body(()).unwrap();
+    assert!(!cache.insert(&post, &res, "", now));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn entries_can_be_deleted_by_key() {
+    let cache = MemoryCache::new();
+    let now = SystemTime::now();
+    cache.insert(&get("en"), &response("max-age=60"), "hello", now);
+    cache.insert(&get("fr"), &response("max-age=60"), "bonjour", now);
+
+    assert_eq!(cache.get(&CachePolicy::primary_key(&get("en"))).len(), 2);
+    let (policy, body) = cache.select(&get("en"), &Config::default()).unwrap();
+    assert_eq!(body, "hello");
+    cache.delete(&policy.cache_key());
+    assert_eq!(cache.len(), 1);
+    assert!(matches!(cache.lookup(&get("en"), now), Lookup::Miss));
+}
+
+fn policy(language: &str, res: &Response<()>) -> CachePolicy {
+    CachePolicy::new(&get(language), res)
+}
+
+#[test]
+fn variant_sets_select_matching_variants() {
+    let res = response("max-age=60");
+    let mut variants = VariantSet::new();
+    assert!(variants.insert(policy("en", &res), "hello").is_empty());
+    assert!(variants.insert(policy("fr", &res), "bonjour").is_empty());
+    assert_eq!(variants.len(), 2);
+
+    assert_eq!(
+        variants.select(&get("en")).map(|(_, body)| *body),
+        Some("hello")
+    );
+    assert_eq!(
+        variants.select(&get("fr")).map(|(_, body)| *body),
+        Some("bonjour")
+    );
+    assert!(variants.select(&get("de")).is_none());
+
+    let evicted = variants.insert(policy("en", &res), "hi");
+    assert_eq!(evicted.len(), 1);
+    assert_eq!(evicted[0].1, "hello");
+    assert_eq!(
+        variants.select(&get("en")).map(|(_, body)| *body),
+        Some("hi")
+    );
+
+    let key = policy("fr", &res).cache_key();
+    assert_eq!(variants.remove(&key).map(|(_, body)| body), Some("bonjour"));
+    assert_eq!(variants.len(), 1);
+}
+
+#[test]
+fn variant_sets_evict_variants_with_a_different_vary() {
+    let mut variants = VariantSet::new();
+    variants.insert(policy("en", &response("max-age=60")), "hello");
+    variants.insert(policy("fr", &response("max-age=60")), "bonjour");
+
+    let unvaried = Response::builder()
+        .header(header::CACHE_CONTROL, "max-age=60")
+        .body(())
+        .unwrap();
+    let evicted = variants.insert(policy("en", &unvaried), "hey");
+    assert_eq!(evicted.len(), 2);
+    assert_eq!(variants.len(), 1);
+    assert_eq!(
+        variants.select(&get("de")).map(|(_, body)| *body),
+        Some("hey")
+    );
+}
+
+#[test]
+fn variant_sets_update_revalidated_variants() {
+    let now = SystemTime::now();
+    let mut variants = VariantSet::new();
+    variants.insert(
+        CachePolicy::new_at(&get("en"), &response("max-age=60"), now),
+        "hello",
+    );
+
+    let later = now + Duration::from_secs(120);
+    assert!(variants.select(&get("en")).unwrap().0.is_stale(later));
+    let not_modified = Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .header(header::ETAG, "\"v1\"")
+        .body(())
+        .unwrap();
+    let after = variants.update(&get("en"), &not_modified, later).unwrap();
+    assert!(matches!(after, AfterResponse::NotModified(..)));
+    assert!(!variants.select(&get("en")).unwrap().0.is_stale(later));
+
+    assert!(variants.update(&get("de"), &not_modified, later).is_none());
+}
+
+#[test]
+fn variant_sets_evict_variants_that_updates_supersede() {
+    let now = SystemTime::now();
+    let mut variants = VariantSet::new();
+    variants.insert(
+        CachePolicy::new_at(&get("fr"), &response("max-age=60"), now),
+        "bonjour",
+    );
+    variants.insert(
+        CachePolicy::new_at(&get("en"), &response("max-age=60"), now),
+        "hello",
+    );
+
+    // Dropping `Vary` supersedes the other variant
+    let later = now + Duration::from_secs(120);
+    let not_modified = Response::builder()
+        .status(StatusCode::NOT_MODIFIED)
+        .header(header::ETAG, "\"v1\"")
+        .header(header::VARY, "")
+        .body(())
+        .unwrap();
+    variants.update(&get("en"), &not_modified, later).unwrap();
+    assert_eq!(variants.len(), 1);
+    assert_eq!(
+        variants.select(&get("de")).map(|(_, body)| *body),
+        Some("hello")
+    );
+
+    variants.update(&get("fr"), &not_modified, later).unwrap();
+    assert_eq!(variants.len(), 1);
+}
@@ -0,0 +1,97 @@
+use http::{header, HeaderValue, Request, Response};
+use http_cache_policy::{strict::ParseIssue, CachePolicy, Config};
+
+use crate::{request_parts, response_parts};
+
+fn issues(res: http::response::Builder) -> Vec<ParseIssue> {
+    let req = request_parts(Request::builder().uri("https://example.com/a"));
+    match CachePolicy::try_new(&req, &response_parts(res)) {
+        Ok(_) => Vec::new(),
+        Err(err) => err.issues().to_vec(),
+    }
+}
+
+#[test]
+fn well_formed_responses_are_accepted() {
+    let res = Response::builder()
+        .header(header::CACHE_CONTROL, "public, max-age=60, ext=\"a b\"")
+        .header(header::CACHE_CONTROL, "stale-if-error=\"30\"")
+        .header(header::DATE, "Mon, 01 Jan 2024 00:00:00 GMT")
+        .header(header::LAST_MODIFIED, "Sun, 31 Dec 2023 00:00:00 GMT")
+        .header(header::AGE, "10");
+    assert_eq!(issues(res), []);
+}
+
+#[test]
+fn malformed_cache_control_is_reported() {
+    let res = Response::builder().header(header::CACHE_CONTROL, "max-age=60, no store,, @=1");
+    assert_eq!(
+        issues(res),
+        [
+            ParseIssue::MalformedDirective("no store".into()),
+            ParseIssue::MalformedDirective("@=1".into()),
+        ]
+    );
+
+    let res = Response::builder().header(
+        header::CACHE_CONTROL,
+        HeaderValue::from_bytes(b"max-age=\xff").unwrap(),
+    );
+    assert_eq!(
+        issues(res),
+        [ParseIssue::MalformedDirective("max-age=\u{fffd}".into())]
+    );
+}
+
+#[test]
+fn conflicting_and_invalid_directives_are_reported() {
+    let res = Response::builder()
+        .header(header::CACHE_CONTROL, "max-age=60, s-maxage, max-age=30")
+        .header(
+            header::CACHE_CONTROL,
+            "stale-while-revalidate=-1, max-age=10",
+        );
+    assert_eq!(
+        issues(res),
+        [
+            ParseIssue::InvalidSeconds {
+                directive: "s-maxage".into(),
+                value: None,
+            },
+            ParseIssue::ConflictingDirective("max-age".into()),
+            ParseIssue::InvalidSeconds {
+                directive: "stale-while-revalidate".into(),
+                value: Some("-1".into()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn invalid_dates_and_ages_are_reported() {
+    let res = Response::builder()
+        .header(header::CACHE_CONTROL, "max-age=60")
+        .header(header::DATE, "yesterday")
+        .header(header::EXPIRES, "0")
+        .header(header::LAST_MODIFIED, "Mon, 01 Jan 2024 00:00:00 GMT")
+        .header(header::AGE, "+5");
+    assert_eq!(
+        issues(res),
+        [
+            ParseIssue::InvalidDate(header::DATE),
+            ParseIssue::InvalidDate(header::EXPIRES),
+            ParseIssue::InvalidAge,
+        ]
+    );
+}
+
+#[test]
+fn lenient_policies_report_issues_too() {
+    let req = request_parts(Request::builder().uri("https://example.com/a"));
+    let res = response_parts(Response::builder().header(header::AGE, "soon"));
+    let policy = CachePolicy::with_config(&req, &res, http_cache_policy::now(), Config::default());
+    assert_eq!(policy.parse_issues(), [ParseIssue::InvalidAge]);
+
+    let err = CachePolicy::try_new(&req, &res).unwrap_err();
+    assert_eq!(err.to_string(), "invalid `age`");
+}
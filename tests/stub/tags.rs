@@ -0,0 +1,31 @@
+use http::{Request, Response};
+use http_cache_policy::CachePolicy;
+
+use crate::{request_parts, response_parts};
+
+fn policy(response: http::response::Builder) -> CachePolicy {
+    CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(response),
+    )
+}
+
+#[test]
+fn no_tags() {
+    assert!(policy(Response::builder()).cache_tags().is_empty());
+}
+
+#[test]
+fn collects_tags_from_all_headers() {
+    let policy = policy(
+        Response::builder()
+            .header("cache-tag", "product, product-42 ,, ")
+            .header("cache-tag", "homepage")
+            .header("surrogate-key", " product-42  category-7")
+            .header("xkey", "user-1"),
+    );
+    assert_eq!(
+        policy.cache_tags(),
+        ["product", "product-42", "homepage", "category-7", "user-1"]
+    );
+}
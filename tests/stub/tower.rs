@@ -0,0 +1,144 @@
+use std::{
+    convert::Infallible,
+    future::{ready, Future, Ready},
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Wake, Waker},
+};
+
+use http::{header, Request, Response, StatusCode};
+use http_cache_policy::{storage::MemoryCache, tower::CacheLayer};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Responds with an `ETag` and `max-age=60` for `/fresh` (`0` otherwise) while recording requests
+///
+/// `/vary` responses are fresh too, and vary by `Accept-Language` which the body echoes
+#[derive(Clone, Default)]
+struct Origin {
+    requests: Arc<Mutex<Vec<http::request::Parts>>>,
+}
+
+impl Service<Request<()>> for Origin {
+    type Response = Response<String>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<()>) -> Self::Future {
+        let (parts, ()) = req.into_parts();
+        let not_modified = parts
+            .headers
+            .get(header::IF_NONE_MATCH)
+            .map_or(false, |etag| etag == "\"v1\"");
+        let vary = parts.uri.path() == "/vary";
+        let max_age = if parts.uri.path() == "/fresh" || vary {
+            60
+        } else {
+            0
+        };
+        let language = parts
+            .headers
+            .get(header::ACCEPT_LANGUAGE)
+            .map(|language| language.to_str().unwrap().to_owned());
+        self.requests.lock().unwrap().push(parts);
+
+        let res = Response::builder()
+            .header(header::CACHE_CONTROL, format!("max-age={max_age}"))
+            .header(header::ETAG, "\"v1\"");
+        let res = if vary {
+            res.header(header::VARY, "accept-language")
+                .body(language.unwrap_or_default())
+        } else if not_modified {
+            res.status(StatusCode::NOT_MODIFIED).body(String::new())
+        } else {
+            res.body("hello".to_owned())
+        };
+        ready(Ok(res.unwrap()))
+    }
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    struct Noop;
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(Noop));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = Box::pin(fut);
+    loop {
+        if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+            return out;
+        }
+    }
+}
+
+fn get(uri: &str) -> Request<()> {
+    Request::get(uri).body(()).unwrap()
+}
+
+#[test]
+fn serves_fresh_responses_from_the_store() {
+    let origin = Origin::default();
+    let mut service = CacheLayer::new(Arc::new(MemoryCache::default())).layer(origin.clone());
+
+    for _ in 0..3 {
+        let res = block_on(service.call(get("http://example.com/fresh"))).unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body(), "hello");
+    }
+    assert_eq!(origin.requests.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn revalidates_stale_responses() {
+    let origin = Origin::default();
+    let mut service = CacheLayer::new(Arc::new(MemoryCache::default())).layer(origin.clone());
+
+    let res = block_on(service.call(get("http://example.com/stale"))).unwrap();
+    assert_eq!(res.body(), "hello");
+    let res = block_on(service.call(get("http://example.com/stale"))).unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body(), "hello");
+
+    let requests = origin.requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(!requests[0].headers.contains_key(header::IF_NONE_MATCH));
+    assert_eq!(requests[1].headers[header::IF_NONE_MATCH], "\"v1\"");
+}
+
+#[test]
+fn unsafe_requests_invalidate_entries() {
+    let origin = Origin::default();
+    let mut service = CacheLayer::new(Arc::new(MemoryCache::default())).layer(origin.clone());
+
+    block_on(service.call(get("http://example.com/fresh"))).unwrap();
+    let post = Request::post("http://example.com/fresh").body(()).unwrap();
+    block_on(service.call(post)).unwrap();
+    block_on(service.call(get("http://example.com/fresh"))).unwrap();
+
+    assert_eq!(origin.requests.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn stores_every_variant() {
+    let origin = Origin::default();
+    let mut service = CacheLayer::new(Arc::new(MemoryCache::default())).layer(origin.clone());
+    let get = |language| {
+        Request::get("http://example.com/vary")
+            .header(header::ACCEPT_LANGUAGE, language)
+            .body(())
+            .unwrap()
+    };
+
+    for _ in 0..2 {
+        for language in ["en", "fr"] {
+            let res = block_on(service.call(get(language))).unwrap();
+            assert_eq!(res.body(), language);
+        }
+    }
+    assert_eq!(origin.requests.lock().unwrap().len(), 2);
+}
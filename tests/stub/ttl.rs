@@ -0,0 +1,186 @@
+use http::{header, Request, Response, StatusCode};
+use http_cache_policy::{CachePolicy, Config, FreshnessSource};
+use std::time::{Duration, SystemTime};
+
+use crate::{request_parts, response_parts};
+
+fn explain(res: http::response::Builder, config: Config) -> (Duration, FreshnessSource) {
+    let now = SystemTime::now();
+    let req = request_parts(Request::builder());
+    let policy = CachePolicy::with_config(&req, &response_parts(res), now, config);
+    let explanation = policy.explain(&req, now);
+    (explanation.freshness_lifetime, explanation.freshness_source)
+}
+
+#[test]
+fn default_ttl_only_applies_without_freshness_information() {
+    let minute = Duration::from_secs(60);
+    let config = Config::default().default_ttl(minute);
+
+    assert_eq!(
+        explain(Response::builder(), config.clone()),
+        (minute, FreshnessSource::DefaultTtl)
+    );
+    assert_eq!(
+        explain(Response::builder(), Config::default()),
+        (Duration::ZERO, FreshnessSource::Unknown)
+    );
+    assert_eq!(
+        explain(
+            Response::builder().header(header::CACHE_CONTROL, "max-age=5"),
+            config.clone()
+        ),
+        (Duration::from_secs(5), FreshnessSource::MaxAge)
+    );
+    assert_eq!(
+        explain(
+            Response::builder().header(header::CACHE_CONTROL, "no-cache"),
+            config.clone()
+        ),
+        (Duration::ZERO, FreshnessSource::NoCache)
+    );
+    // Not storable without explicit freshness information in the first place
+    assert_eq!(
+        explain(Response::builder().status(StatusCode::CREATED), config),
+        (Duration::ZERO, FreshnessSource::NotStorable)
+    );
+}
+
+#[test]
+fn max_ttl_caps_every_source() {
+    let minute = Duration::from_secs(60);
+    let config = Config::default().max_ttl(minute);
+
+    assert_eq!(
+        explain(
+            Response::builder().header(header::CACHE_CONTROL, "max-age=604800"),
+            config.clone()
+        ),
+        (minute, FreshnessSource::MaxAge)
+    );
+    assert_eq!(
+        explain(
+            Response::builder().header(header::CACHE_CONTROL, "max-age=5"),
+            config.clone()
+        ),
+        (Duration::from_secs(5), FreshnessSource::MaxAge)
+    );
+    assert_eq!(
+        explain(
+            Response::builder()
+                .header(header::DATE, "Tue, 15 Nov 1994 08:12:31 GMT")
+                .header(header::EXPIRES, "Tue, 15 Nov 1994 09:12:31 GMT"),
+            config.clone()
+        ),
+        (minute, FreshnessSource::Expires)
+    );
+    assert_eq!(
+        explain(
+            Response::builder(),
+            config.default_ttl(Duration::from_secs(3600))
+        ),
+        (minute, FreshnessSource::DefaultTtl)
+    );
+}
+
+#[test]
+fn min_ttl_lifts_short_lifetimes() {
+    let minute = Duration::from_secs(60);
+    let config = Config::default().min_ttl(minute);
+
+    assert_eq!(
+        explain(
+            Response::builder().header(header::CACHE_CONTROL, "max-age=1"),
+            config.clone()
+        ),
+        (minute, FreshnessSource::MaxAge)
+    );
+    assert_eq!(
+        explain(
+            Response::builder().header(header::CACHE_CONTROL, "max-age=3600"),
+            config.clone()
+        ),
+        (Duration::from_secs(3600), FreshnessSource::MaxAge)
+    );
+    assert_eq!(
+        explain(Response::builder(), config.clone()),
+        (minute, FreshnessSource::Unknown)
+    );
+    assert_eq!(
+        explain(
+            Response::builder().header(header::CACHE_CONTROL, "no-cache"),
+            config.clone()
+        ),
+        (Duration::ZERO, FreshnessSource::NoCache)
+    );
+    assert_eq!(
+        explain(
+            Response::builder().header(header::CACHE_CONTROL, "max-age=1"),
+            config.max_ttl(Duration::from_secs(10))
+        ),
+        (Duration::from_secs(10), FreshnessSource::MaxAge)
+    );
+}
+
+#[test]
+fn negative_ttls_for_error_responses() {
+    let config = Config::default()
+        .client_error_ttl(Duration::from_secs(30))
+        .server_error_ttl(Duration::from_secs(5));
+
+    for status in [StatusCode::NOT_FOUND, StatusCode::GONE] {
+        assert_eq!(
+            explain(Response::builder().status(status), config.clone()),
+            (Duration::from_secs(30), FreshnessSource::NegativeTtl)
+        );
+    }
+    for status in [StatusCode::INTERNAL_SERVER_ERROR, StatusCode::BAD_GATEWAY] {
+        assert_eq!(
+            explain(Response::builder().status(status), config.clone()),
+            (Duration::from_secs(5), FreshnessSource::NegativeTtl)
+        );
+    }
+    // Explicit freshness information still wins
+    assert_eq!(
+        explain(
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header(header::CACHE_CONTROL, "max-age=600"),
+            config.clone()
+        ),
+        (Duration::from_secs(600), FreshnessSource::MaxAge)
+    );
+    assert_eq!(
+        explain(
+            Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .header(header::CACHE_CONTROL, "no-store"),
+            config.clone()
+        ),
+        (Duration::ZERO, FreshnessSource::NotStorable)
+    );
+    assert_eq!(
+        explain(
+            Response::builder().status(StatusCode::FORBIDDEN),
+            config.clone()
+        ),
+        (Duration::ZERO, FreshnessSource::NotStorable)
+    );
+
+    // Each class has its own knob
+    let config = Config::default().client_error_ttl(Duration::from_secs(30));
+    assert_eq!(
+        explain(
+            Response::builder().status(StatusCode::INTERNAL_SERVER_ERROR),
+            config
+        ),
+        (Duration::ZERO, FreshnessSource::NotStorable)
+    );
+    assert_eq!(
+        explain(
+            Response::builder().status(StatusCode::NOT_FOUND),
+            Config::default()
+        ),
+        (Duration::ZERO, FreshnessSource::Unknown)
+    );
+}
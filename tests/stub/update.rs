@@ -1,8 +1,10 @@
 use http::header::HeaderName;
 use http::request::Parts as RequestParts;
-use http::{header, HeaderMap, Request, Response};
+use http::{header, HeaderMap, Method, Request, Response};
+use http_cache_policy::config::Methods;
 use http_cache_policy::AfterResponse;
 use http_cache_policy::CachePolicy;
+use http_cache_policy::Config;
 use std::time::Duration;
 use std::time::SystemTime;
 
@@ -39,11 +41,8 @@ fn request_parts_from_headers(headers: HeaderMap) -> RequestParts {
     let mut builder = Request::builder();
 
     for (key, value) in headers {
-        match key {
-            Some(x) => {
-                builder.headers_mut().unwrap().insert(x, value);
-            }
-            None => (),
+        if let Some(x) = key {
+            builder.headers_mut().unwrap().insert(x, value);
         }
     }
 
@@ -76,7 +75,10 @@ fn not_modified_response_headers_for_update(
     );
 
     match rev {
-        AfterResponse::Modified(..) => None,
+        AfterResponse::Modified(..)
+        | AfterResponse::Partial(..)
+        | AfterResponse::Mismatch(..)
+        | AfterResponse::FailedRevalidation(..) => None,
         AfterResponse::NotModified(_, res) => Some(res.headers),
     }
 }
@@ -129,7 +131,9 @@ fn get_revalidation_request(
     now: SystemTime,
 ) -> http::request::Parts {
     match policy.before_request(req, now) {
-        http_cache_policy::BeforeRequest::Stale { request, matches } => {
+        http_cache_policy::BeforeRequest::Stale {
+            request, matches, ..
+        } => {
             if !matches {
                 eprintln!("warning: req doesn't match {req:#?} vs {policy:?}");
             }
@@ -138,3 +142,340 @@ fn get_revalidation_request(
         _ => panic!("no revalidation needed"),
     }
 }
+
+fn partial_response_builder(etag: &str) -> http::response::Builder {
+    Response::builder()
+        .status(http::StatusCode::PARTIAL_CONTENT)
+        .header(header::ETAG, etag)
+        .header(header::CONTENT_RANGE, "bytes 0-9/100")
+        .header(header::CONTENT_LENGTH, "10")
+        .header(header::CACHE_CONTROL, "max-age=7200")
+}
+
+#[test]
+fn partial_response_with_matching_strong_etag_is_combined() {
+    let now = SystemTime::now();
+    let req =
+        request_parts(simple_request_builder_for_update(None).header(header::RANGE, "bytes=0-9"));
+    let policy = CachePolicy::new(
+        &req,
+        &response_parts(etagged_response_builder().header(header::CONTENT_LENGTH, "100")),
+    );
+
+    let partial = response_parts(partial_response_builder("\"123456789\""));
+    match policy.after_response(&req, &partial, now) {
+        AfterResponse::Partial(new_policy, res) => {
+            assert_eq!(res.status, http::StatusCode::OK);
+            assert_eq!(res.headers.get(header::CONTENT_LENGTH).unwrap(), "100");
+            assert!(!res.headers.contains_key(header::CONTENT_RANGE));
+            assert_eq!(
+                res.headers.get(header::CACHE_CONTROL).unwrap(),
+                "max-age=7200"
+            );
+            assert_eq!(new_policy.time_to_live(now), Duration::from_secs(7200));
+        }
+        _ => panic!("expected the partial response to be combinable"),
+    }
+}
+
+#[test]
+fn partial_response_with_different_or_weak_etag_is_not_combined() {
+    let now = SystemTime::now();
+    let req =
+        request_parts(simple_request_builder_for_update(None).header(header::RANGE, "bytes=0-9"));
+    let policy = CachePolicy::new(&req, &response_parts(etagged_response_builder()));
+
+    for etag in ["\"different\"", "W/\"123456789\""] {
+        let partial = response_parts(partial_response_builder(etag));
+        assert!(
+            policy.after_response(&req, &partial, now).is_modified(),
+            "{etag}"
+        );
+    }
+}
+
+#[test]
+fn head_response_freshens_stored_get() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::new(
+        &request_parts(simple_request_builder_for_update(None)),
+        &response_parts(
+            etagged_response_builder()
+                .header(header::CONTENT_LENGTH, "100")
+                .header("foo", "original"),
+        ),
+    );
+
+    let head = request_parts(simple_request_builder_for_update(None).method(Method::HEAD));
+    let head_response = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=7200")
+            .header(header::ETAG, "\"123456789\"")
+            .header(header::CONTENT_LENGTH, "100")
+            .header("foo", "updated"),
+    );
+    let (new_policy, res) = policy
+        .after_head_response(&head, &head_response, now)
+        .expect("HEAD response should freshen the stored GET");
+    assert_eq!(res.headers.get("foo").unwrap(), "updated");
+    assert_eq!(new_policy.time_to_live(now), Duration::from_secs(7200));
+    assert!(new_policy
+        .before_request(&request_parts(simple_request_builder_for_update(None)), now)
+        .is_fresh());
+}
+
+#[test]
+fn head_response_with_changed_validators_invalidates() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::new(
+        &request_parts(simple_request_builder_for_update(None)),
+        &response_parts(
+            etagged_response_builder()
+                .header(header::CONTENT_LENGTH, "100")
+                .header(header::LAST_MODIFIED, "Tue, 15 Nov 1994 12:45:26 GMT"),
+        ),
+    );
+    let head = request_parts(simple_request_builder_for_update(None).method(Method::HEAD));
+
+    for (name, value) in [
+        (header::CONTENT_LENGTH, "101"),
+        (header::ETAG, "\"other\""),
+        (header::LAST_MODIFIED, "Wed, 16 Nov 1994 12:45:26 GMT"),
+    ] {
+        let head_response = response_parts(Response::builder().header(&name, value));
+        assert!(
+            policy
+                .after_head_response(&head, &head_response, now)
+                .is_none(),
+            "{name}"
+        );
+    }
+
+    for status in [
+        http::StatusCode::NOT_FOUND,
+        http::StatusCode::SERVICE_UNAVAILABLE,
+    ] {
+        let error = response_parts(
+            Response::builder()
+                .status(status)
+                .header(header::CONTENT_LENGTH, "0"),
+        );
+        let (unmodified, res) = policy
+            .after_head_response(&head, &error, now)
+            .expect("errors don't describe the stored response");
+        assert_eq!(unmodified.response_time(), policy.response_time());
+        assert_eq!(res.headers[header::CONTENT_LENGTH], "100");
+    }
+}
+
+#[test]
+fn head_response_for_another_request_leaves_the_policy_alone() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::new_at(
+        &request_parts(
+            simple_request_builder_for_update(None).header(header::ACCEPT_LANGUAGE, "en"),
+        ),
+        &response_parts(
+            etagged_response_builder()
+                .header(header::VARY, "accept-language")
+                .header(header::CONTENT_LENGTH, "100"),
+        ),
+        now,
+    );
+    let changed = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=7200")
+            .header(header::CONTENT_LENGTH, "101"),
+    );
+    let later = now + Duration::from_secs(60);
+
+    let heads = [
+        simple_request_builder_for_update(None).header(header::ACCEPT_LANGUAGE, "fr"),
+        simple_request_builder_for_update(None)
+            .uri("/other")
+            .header(header::ACCEPT_LANGUAGE, "en"),
+    ];
+    for head in heads {
+        let head = request_parts(head.method(Method::HEAD));
+        let (unmodified, res) = policy
+            .after_head_response(&head, &changed, later)
+            .expect("the stored response shouldn't be invalidated");
+        assert_eq!(unmodified.identity(), policy.identity());
+        assert_eq!(unmodified.response_time(), now);
+        assert_eq!(res.headers[header::CONTENT_LENGTH], "100");
+    }
+}
+
+#[test]
+fn head_response_only_freshens_stored_gets_for_head_requests() {
+    let now = SystemTime::now();
+    let later = now + Duration::from_secs(60);
+    let changed = response_parts(
+        Response::builder()
+            .header(header::CACHE_CONTROL, "max-age=7200")
+            .header(header::CONTENT_LENGTH, "101"),
+    );
+    let stored = |method| {
+        CachePolicy::with_config(
+            &request_parts(simple_request_builder_for_update(None).method(method)),
+            &response_parts(
+                etagged_response_builder()
+                    .header(header::CACHE_CONTROL, "public, max-age=60")
+                    .header(header::CONTENT_LENGTH, "100"),
+            ),
+            now,
+            Config::default().cacheable_methods(Methods::CACHEABLE.with(Method::POST)),
+        )
+    };
+
+    for (policy, method) in [
+        (stored(Method::GET), Method::GET),
+        (stored(Method::POST), Method::HEAD),
+    ] {
+        let req = request_parts(simple_request_builder_for_update(None).method(method));
+        let (unmodified, res) = policy
+            .after_head_response(&req, &changed, later)
+            .expect("the stored response shouldn't be invalidated");
+        assert_eq!(unmodified.response_time(), now);
+        assert_eq!(res.headers[header::CONTENT_LENGTH], "100");
+    }
+}
+
+#[test]
+fn mismatched_not_modified_asks_for_the_full_resource() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::new(
+        &request_parts(simple_request_builder_for_update(None)),
+        &response_parts(etagged_response_builder()),
+    );
+    let revalidation = get_revalidation_request(
+        &policy,
+        &request_parts(simple_request_builder_for_update(None)),
+        now + Duration::from_secs(3600 * 24),
+    );
+    assert!(revalidation.headers.contains_key(header::IF_NONE_MATCH));
+
+    let not_modified = response_parts(
+        Response::builder()
+            .status(http::StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, "\"something-else\""),
+    );
+    match policy.after_response(&revalidation, &not_modified, now) {
+        AfterResponse::Mismatch(request) => {
+            assert_eq!(request.uri, revalidation.uri);
+            assert!(!request.headers.contains_key(header::IF_NONE_MATCH));
+            assert!(!request.headers.contains_key(header::IF_MODIFIED_SINCE));
+        }
+        _ => panic!("expected a mismatch"),
+    }
+}
+
+#[test]
+fn mismatched_last_modified_is_not_an_update() {
+    let now = SystemTime::now();
+    let policy = CachePolicy::new(
+        &request_parts(simple_request_builder_for_update(None)),
+        &response_parts(
+            cacheable_response_builder_for_update()
+                .header(header::LAST_MODIFIED, "Mon, 07 Oct 2024 12:00:00 GMT"),
+        ),
+    );
+    let not_modified = response_parts(
+        Response::builder()
+            .status(http::StatusCode::NOT_MODIFIED)
+            .header(header::LAST_MODIFIED, "Tue, 08 Oct 2024 12:00:00 GMT"),
+    );
+    let req = request_parts(simple_request_builder_for_update(None));
+    assert!(matches!(
+        policy.after_response(&req, &not_modified, now),
+        AfterResponse::Mismatch(_)
+    ));
+}
+
+#[test]
+fn reports_update_metadata() {
+    let now = SystemTime::now();
+    let req = request_parts(simple_request_builder_for_update(None));
+    let policy = CachePolicy::with_config(
+        &req,
+        &response_parts(
+            etagged_response_builder().header(HeaderName::from_static("x-other"), "original"),
+        ),
+        now,
+        Default::default(),
+    );
+
+    let same = response_parts(
+        etagged_response_builder()
+            .status(http::StatusCode::NOT_MODIFIED)
+            .header(HeaderName::from_static("x-other"), "original"),
+    );
+    let after = policy.after_response(&req, &same, now);
+    let metadata = after.update_metadata(&policy, now).unwrap();
+    assert!(!metadata.is_changed(), "{metadata:?}");
+    assert_eq!(metadata.time_to_live, Duration::from_secs(111));
+
+    let changed = response_parts(
+        Response::builder()
+            .status(http::StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, "\"123456789\"")
+            .header(header::CACHE_CONTROL, "max-age=222")
+            .header(HeaderName::from_static("x-other"), "updated"),
+    );
+    let after = policy.after_response(&req, &changed, now);
+    let metadata = after.update_metadata(&policy, now).unwrap();
+    assert!(metadata.is_changed());
+    assert!(!metadata.status_changed);
+    assert_eq!(
+        metadata.changed_headers,
+        [header::CACHE_CONTROL, HeaderName::from_static("x-other")]
+    );
+    assert_eq!(metadata.time_to_live, Duration::from_secs(222));
+
+    let mismatch = response_parts(
+        Response::builder()
+            .status(http::StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, "\"other\""),
+    );
+    let after = policy.after_response(&req, &mismatch, now);
+    assert!(after.policy().is_none());
+    assert!(after.update_metadata(&policy, now).is_none());
+}
+
+#[test]
+fn server_errors_dont_update_the_stored_response() {
+    let now = SystemTime::now();
+    let req = request_parts(simple_request_builder_for_update(None));
+    let policy = CachePolicy::with_config(
+        &req,
+        &response_parts(
+            etagged_response_builder().header(header::CACHE_CONTROL, "stale-if-error=600"),
+        ),
+        now,
+        Default::default(),
+    );
+    let later = now + Duration::from_secs(200);
+
+    for status in [500, 502, 503, 504] {
+        let error = response_parts(
+            Response::builder()
+                .status(status)
+                .header(header::CACHE_CONTROL, "no-store"),
+        );
+        let after = policy.after_response(&req, &error, later);
+        assert!(!after.is_modified());
+        match after {
+            AfterResponse::FailedRevalidation(stored, res) => {
+                assert_eq!(res.status, status);
+                assert!(stored.is_storable());
+                assert_eq!(stored.identity(), policy.identity());
+                assert_eq!(stored.response_time(), now);
+                assert!(stored.stale_if_error_until(later).is_some());
+            }
+            _ => panic!("expected a failed revalidation for {status}"),
+        }
+    }
+
+    let not_found = response_parts(Response::builder().status(404));
+    assert!(policy.after_response(&req, &not_found, later).is_modified());
+}
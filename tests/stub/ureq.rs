@@ -0,0 +1,54 @@
+use http::{header, Method};
+use http_cache_policy::{ureq as convert, BeforeRequest, CachePolicy};
+
+fn response() -> ureq::Response {
+    "HTTP/1.1 200 OK\r\n\
+     Cache-Control: max-age=0\r\n\
+     ETag: \"v1\"\r\n\
+     Set-Cookie: a=1\r\n\
+     Set-Cookie: b=2\r\n\
+     \r\n\
+     hello"
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn converts_requests_and_responses() {
+    let req = ureq::get("https://example.com/a").set("Accept", "text/html");
+    let (uri, method, headers) = convert::request(&req).unwrap();
+    assert_eq!(uri, "https://example.com/a");
+    assert_eq!(method, Method::GET);
+    assert_eq!(headers[header::ACCEPT], "text/html");
+
+    let (status, headers) = convert::response(&response()).unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(headers[header::ETAG], "\"v1\"");
+    assert_eq!(headers.get_all(header::SET_COOKIE).iter().count(), 2);
+}
+
+#[test]
+fn builds_revalidation_requests() {
+    let agent = ureq::agent();
+    let req = agent
+        .get("https://example.com/a")
+        .set("Accept", "text/html")
+        // Refers to the client's copy rather than the stored one, so it must not be sent
+        .set("If-Range", "\"client\"");
+    let policy = CachePolicy::new(
+        &convert::request(&req).unwrap(),
+        &convert::response(&response()).unwrap(),
+    );
+
+    let now = http_cache_policy::now();
+    let request = match policy.before_request(&convert::request(&req).unwrap(), now) {
+        BeforeRequest::Stale { request, .. } => request,
+        BeforeRequest::Fresh(_) => panic!("should be stale"),
+    };
+    let req = convert::revalidation_request(&agent, &request);
+    assert_eq!(req.url(), "https://example.com/a");
+    assert_eq!(req.method(), "GET");
+    assert_eq!(req.header("if-none-match"), Some("\"v1\""));
+    assert_eq!(req.header("accept"), Some("text/html"));
+    assert_eq!(req.header("if-range"), None);
+}
@@ -0,0 +1,38 @@
+use http::{header, HeaderMap, Method, Request, Response};
+use http_cache_policy::{url::UrlRequest, CachePolicy, RequestLike};
+use url::Url;
+
+use crate::{request_parts, response_parts};
+
+#[test]
+fn fragments_are_ignored() {
+    let url = Url::parse("https://example.com/a?b=c#d").unwrap();
+    let headers = HeaderMap::new();
+    let req = UrlRequest::new(&url, &Method::GET, &headers);
+    assert_eq!(req.uri(), "https://example.com/a?b=c");
+    assert!(req.is_same_uri(&"https://example.com/a?b=c".parse().unwrap()));
+    assert!(!req.is_same_uri(&"https://example.com/a".parse().unwrap()));
+}
+
+#[test]
+fn matches_requests_for_the_same_uri() {
+    let url = Url::parse("https://example.com/a").unwrap();
+    let headers = HeaderMap::new();
+    let req = UrlRequest::new(&url, &Method::GET, &headers);
+    let res = response_parts(Response::builder().header(header::CACHE_CONTROL, "max-age=60"));
+    let policy = CachePolicy::new(&req, &res);
+
+    assert!(policy.is_storable());
+    assert!(policy.matches(&request_parts(
+        Request::builder().uri("https://example.com/a")
+    )));
+    assert_eq!(
+        CachePolicy::primary_key(&req),
+        CachePolicy::primary_key(&request_parts(
+            Request::builder().uri("https://example.com/a")
+        ))
+    );
+
+    let other = Url::parse("https://example.com/b").unwrap();
+    assert!(!policy.matches(&UrlRequest::new(&other, &Method::GET, &headers)));
+}
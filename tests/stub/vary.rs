@@ -3,7 +3,7 @@ use std::time::SystemTime;
 use crate::request_parts;
 use crate::response_parts;
 
-use http::{header, Request, Response};
+use http::{header, HeaderName, Request, Response};
 use http_cache_policy::CachePolicy;
 
 #[test]
@@ -346,3 +346,61 @@ fn order_is_irrelevant() {
         )
         .is_fresh());
 }
+
+#[test]
+fn vary_headers_are_lowercased_and_deduplicated() {
+    let policy = CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(
+            Response::builder()
+                .header(header::VARY, "Accept-Language, X-Foo")
+                .header(header::VARY, "accept-language, ,dnt"),
+        ),
+    );
+    assert_eq!(
+        policy.vary_headers(),
+        [
+            header::ACCEPT_LANGUAGE,
+            HeaderName::from_static("x-foo"),
+            header::DNT
+        ]
+    );
+
+    let policy = CachePolicy::new(
+        &request_parts(Request::builder()),
+        &response_parts(Response::builder()),
+    );
+    assert!(policy.vary_headers().is_empty());
+}
+
+#[test]
+fn matches_ignores_freshness() {
+    let req = |language| {
+        request_parts(
+            Request::builder()
+                .uri("https://example.com/a")
+                .header(header::ACCEPT_LANGUAGE, language),
+        )
+    };
+    let policy = CachePolicy::new(
+        &req("en"),
+        &response_parts(
+            Response::builder()
+                .header(header::CACHE_CONTROL, "no-cache")
+                .header(header::VARY, "accept-language"),
+        ),
+    );
+    assert!(policy.matches(&req("en")));
+    assert!(!policy.matches(&req("de")));
+    assert!(!policy.matches(&request_parts(
+        Request::builder()
+            .method(http::Method::POST)
+            .uri("https://example.com/a")
+            .header(header::ACCEPT_LANGUAGE, "en"),
+    )));
+    assert!(!policy.matches(&request_parts(
+        Request::builder()
+            .uri("https://example.com/b")
+            .header(header::ACCEPT_LANGUAGE, "en"),
+    )));
+}